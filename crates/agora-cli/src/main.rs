@@ -0,0 +1,730 @@
+use agora_client::{
+    AgoraClient, BatchAckMode, CreateEventRequest, Event, EventPriority, EventStatus, HeraldInfo,
+    QueuedEventsQuery, RegisterHeraldRequest,
+};
+use anyhow::{Result, anyhow};
+use clap::{Parser, Subcommand, ValueEnum};
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+const ENV_AGORA_URL: &str = "AGORA_URL";
+const ENV_AGORA_TOKEN: &str = "AGORA_TOKEN";
+const DEFAULT_URL: &str = "http://localhost:3000";
+
+/// Herald ID synthetic events are pushed as, unless overridden.
+const CLI_HERALD_ID: &str = "agora-cli";
+
+fn get_server_url(url: Option<String>) -> String {
+    url.or_else(|| env::var(ENV_AGORA_URL).ok())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_URL.to_string())
+}
+
+fn get_token(token: Option<String>) -> Option<String> {
+    token
+        .or_else(|| env::var(ENV_AGORA_TOKEN).ok())
+        .filter(|s| !s.is_empty())
+}
+
+fn build_http_client() -> Client {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(Duration::from_secs(30))
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+#[derive(Parser)]
+#[command(name = "agora-cli")]
+#[command(about = "CLI client for Agora event hub")]
+#[command(version)]
+struct Cli {
+    /// Agora server URL (overrides AGORA_URL env var)
+    #[arg(short, long, global = true)]
+    url: Option<String>,
+
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Inspect and unregister heralds
+    Heralds {
+        #[command(subcommand)]
+        action: HeraldCommands,
+    },
+    /// Inspect, push, ack and tail events
+    Events {
+        #[command(subcommand)]
+        action: EventCommands,
+    },
+    /// Inspect event payload schemas
+    Schemas {
+        #[command(subcommand)]
+        action: SchemaCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaCommands {
+    /// List the event types that have a schema
+    List,
+    /// Show an event type's schema
+    Show {
+        /// Event type (e.g., chat.message)
+        event_type: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HeraldCommands {
+    /// List heralds
+    List,
+    /// Show a herald
+    Show {
+        /// Herald ID
+        id: String,
+    },
+    /// Unregister a herald
+    Unregister {
+        /// Herald ID
+        id: String,
+        /// The herald's token, or Agora's admin token for a herald that lost
+        /// its own (overrides AGORA_TOKEN env var)
+        #[arg(long)]
+        token: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum EventCommands {
+    /// List a consumer's queued events without delivering them
+    List {
+        /// Consumer whose queue to list (defaults to the server's default consumer)
+        #[arg(long)]
+        consumer: Option<String>,
+        /// Only pending or only delivered events
+        #[arg(long, value_enum)]
+        status: Option<QueueStatus>,
+        /// Filter by herald ID
+        #[arg(long)]
+        herald_id: Option<String>,
+        /// Filter by event type
+        #[arg(long)]
+        event_type: Option<String>,
+        /// Maximum number of events to list
+        #[arg(short, long, default_value = "20")]
+        limit: u32,
+    },
+    /// Push a synthetic event
+    Push {
+        /// Event type (e.g., chat.message)
+        event_type: String,
+        /// JSON payload
+        #[arg(long, conflicts_with = "file")]
+        payload: Option<String>,
+        /// Read the JSON payload from a file ("-" for stdin)
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Priority
+        #[arg(long, value_enum, default_value = "normal")]
+        priority: PriorityArg,
+        /// Herald ID to push as
+        #[arg(long, default_value = CLI_HERALD_ID)]
+        herald_id: String,
+        /// The herald's token (overrides AGORA_TOKEN env var). Without one,
+        /// the CLI registers the herald for the push and unregisters it after.
+        #[arg(long)]
+        token: Option<String>,
+        /// Idempotency key
+        #[arg(long)]
+        idempotency_key: Option<String>,
+        /// Coalesce key
+        #[arg(long)]
+        coalesce_key: Option<String>,
+        /// Hold the event back until this time (RFC3339)
+        #[arg(long)]
+        deliver_after: Option<String>,
+        /// Drop the event if not acked by this time (RFC3339)
+        #[arg(long)]
+        expires_at: Option<String>,
+    },
+    /// Ack events
+    Ack {
+        /// Event IDs
+        #[arg(required = true)]
+        ids: Vec<u64>,
+        /// Consumer to ack as (defaults to the server's default consumer)
+        #[arg(long)]
+        consumer: Option<String>,
+        /// Ack nothing unless every event can be acked
+        #[arg(long)]
+        all_or_nothing: bool,
+    },
+    /// Requeue events for immediate redelivery
+    Requeue {
+        /// Event IDs
+        #[arg(required = true)]
+        ids: Vec<u64>,
+        /// Consumer whose queue holds the events (defaults to the server's default consumer)
+        #[arg(long)]
+        consumer: Option<String>,
+    },
+    /// Print events live as they are pushed, until Ctrl-C
+    Tail {
+        /// Event type globs to include (repeatable; default all)
+        #[arg(long)]
+        event_type: Vec<String>,
+        /// Herald ID globs to include (repeatable; default all)
+        #[arg(long)]
+        herald_id: Vec<String>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum QueueStatus {
+    Pending,
+    Delivered,
+}
+
+impl From<QueueStatus> for EventStatus {
+    fn from(status: QueueStatus) -> Self {
+        match status {
+            QueueStatus::Pending => EventStatus::Pending,
+            QueueStatus::Delivered => EventStatus::Delivered,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum PriorityArg {
+    Low,
+    Normal,
+    High,
+    Urgent,
+}
+
+impl From<PriorityArg> for EventPriority {
+    fn from(priority: PriorityArg) -> Self {
+        match priority {
+            PriorityArg::Low => EventPriority::Low,
+            PriorityArg::Normal => EventPriority::Normal,
+            PriorityArg::High => EventPriority::High,
+            PriorityArg::Urgent => EventPriority::Urgent,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let cli = Cli::parse();
+    let client = AgoraClient::new(&get_server_url(cli.url), build_http_client());
+    let format = cli.format;
+
+    let result = match cli.command {
+        Commands::Heralds { action } => match action {
+            HeraldCommands::List => handle_heralds_list(&client, format).await,
+            HeraldCommands::Show { id } => handle_heralds_show(&id, &client, format).await,
+            HeraldCommands::Unregister { id, token } => {
+                handle_heralds_unregister(&id, token, client).await
+            }
+        },
+        Commands::Events { action } => match action {
+            EventCommands::List { consumer, status, herald_id, event_type, limit } => {
+                let query = QueuedEventsQuery {
+                    consumer,
+                    status: status.map(EventStatus::from),
+                    herald_id,
+                    event_type,
+                    limit: Some(limit),
+                };
+                handle_events_list(&query, &client, format).await
+            }
+            EventCommands::Push {
+                event_type,
+                payload,
+                file,
+                priority,
+                herald_id,
+                token,
+                idempotency_key,
+                coalesce_key,
+                deliver_after,
+                expires_at,
+            } => {
+                let request = async {
+                    Ok::<_, anyhow::Error>(CreateEventRequest {
+                        event_type,
+                        herald_id,
+                        priority: priority.into(),
+                        payload: read_payload(payload.as_deref(), file.as_deref())?,
+                        timestamp: OffsetDateTime::now_utc(),
+                        idempotency_key,
+                        deliver_after: deliver_after.as_deref().map(parse_datetime).transpose()?,
+                        expires_at: expires_at.as_deref().map(parse_datetime).transpose()?,
+                        coalesce_key,
+                    })
+                };
+                match request.await {
+                    Ok(request) => handle_events_push(request, token, client, format).await,
+                    Err(e) => Err(e),
+                }
+            }
+            EventCommands::Ack { ids, consumer, all_or_nothing } => {
+                let mode = if all_or_nothing {
+                    BatchAckMode::AllOrNothing
+                } else {
+                    BatchAckMode::BestEffort
+                };
+                handle_events_ack(ids, mode, with_consumer(client, consumer), format).await
+            }
+            EventCommands::Requeue { ids, consumer } => {
+                handle_events_requeue(ids, with_consumer(client, consumer), format).await
+            }
+            EventCommands::Tail { event_type, herald_id } => {
+                handle_events_tail(&event_type, &herald_id, &client, format).await
+            }
+        },
+        Commands::Schemas { action } => match action {
+            SchemaCommands::List => handle_schemas_list(&client, format).await,
+            SchemaCommands::Show { event_type } => {
+                handle_schemas_show(&event_type, &client, format).await
+            }
+        },
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+// === Command Handlers ===
+
+async fn handle_heralds_list(client: &AgoraClient, format: OutputFormat) -> Result<()> {
+    let heralds = client.list_heralds().await?;
+
+    match format {
+        OutputFormat::Json => print_json(&serde_json::json!({ "heralds": heralds }))?,
+        OutputFormat::Text => {
+            if heralds.is_empty() {
+                println!("No heralds registered.");
+            }
+            for herald in &heralds {
+                println!(
+                    "{} [{:?}] last heartbeat {}, {} dead letters{}",
+                    herald.id,
+                    herald.status,
+                    format_time(herald.last_heartbeat),
+                    herald.dead_letters,
+                    herald
+                        .description
+                        .as_ref()
+                        .map(|d| format!(" - {}", d))
+                        .unwrap_or_default()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_heralds_show(id: &str, client: &AgoraClient, format: OutputFormat) -> Result<()> {
+    let herald = client.get_herald(id).await?;
+
+    match format {
+        OutputFormat::Json => print_json(&herald)?,
+        OutputFormat::Text => print_herald(&herald),
+    }
+    Ok(())
+}
+
+async fn handle_heralds_unregister(
+    id: &str,
+    token: Option<String>,
+    client: AgoraClient,
+) -> Result<()> {
+    let token = get_token(token).ok_or_else(|| {
+        anyhow!("Unregistering needs the herald's or the admin token (--token or AGORA_TOKEN)")
+    })?;
+    client.with_herald_token(Some(token)).unregister(id).await?;
+    println!("Unregistered herald {}", id);
+    Ok(())
+}
+
+async fn handle_events_list(
+    query: &QueuedEventsQuery,
+    client: &AgoraClient,
+    format: OutputFormat,
+) -> Result<()> {
+    let response = client.list_queued_events(query).await?;
+
+    match format {
+        OutputFormat::Json => print_json(&response)?,
+        OutputFormat::Text => {
+            if response.events.is_empty() {
+                println!("No queued events.");
+            }
+            for queued in &response.events {
+                print_event(&queued.event);
+                println!("    deliveries: {}", queued.delivery_count);
+            }
+            if response.total > response.events.len() {
+                println!(
+                    "... {} more (raise --limit to see them)",
+                    response.total - response.events.len()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_events_push(
+    request: CreateEventRequest,
+    token: Option<String>,
+    client: AgoraClient,
+    format: OutputFormat,
+) -> Result<()> {
+    let herald_id = request.herald_id.clone();
+    let token = get_token(token);
+    let registered = token.is_none();
+
+    let client = match token {
+        Some(token) => client.with_herald_token(Some(token)),
+        None => {
+            let registration = RegisterHeraldRequest {
+                id: herald_id.clone(),
+                description: Some("agora-cli - synthetic events".to_string()),
+                schemas: HashMap::new(),
+            };
+            client.register_herald(registration).await.map_err(|e| {
+                anyhow!(
+                    "Failed to register herald {}: {} (pass its token with --token if it already exists)",
+                    herald_id,
+                    e
+                )
+            })?;
+            client
+        }
+    };
+
+    let result = client.push_event(request).await;
+    if registered && let Err(e) = client.unregister(&herald_id).await {
+        eprintln!("Warning: failed to unregister herald {}: {}", herald_id, e);
+    }
+    let event = result?;
+
+    match format {
+        OutputFormat::Json => print_json(&event)?,
+        OutputFormat::Text => {
+            println!("Pushed event {}", event.id);
+            print_event(&event);
+        }
+    }
+    Ok(())
+}
+
+async fn handle_events_ack(
+    ids: Vec<u64>,
+    mode: BatchAckMode,
+    client: AgoraClient,
+    format: OutputFormat,
+) -> Result<()> {
+    let requested = ids.len();
+    let result = client.ack_events(ids, mode).await?;
+
+    match format {
+        OutputFormat::Json => print_json(&result)?,
+        OutputFormat::Text => {
+            println!("Acked {} of {} events", result.acked_ids.len(), requested);
+            if !result.missing_ids.is_empty() {
+                println!("  Missing: {:?}", result.missing_ids);
+            }
+            if !result.failed_ids.is_empty() {
+                println!("  Failed: {:?}", result.failed_ids);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_events_requeue(
+    ids: Vec<u64>,
+    client: AgoraClient,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut failed = 0;
+    for id in ids {
+        match client.requeue_event(id).await {
+            Ok(event) => match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string(&event)?),
+                OutputFormat::Text => println!("Requeued event {}", event.id),
+            },
+            Err(e) => {
+                eprintln!("Failed to requeue event {}: {}", id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow!("{} events could not be requeued", failed));
+    }
+    Ok(())
+}
+
+async fn handle_events_tail(
+    event_types: &[String],
+    herald_ids: &[String],
+    client: &AgoraClient,
+    format: OutputFormat,
+) -> Result<()> {
+    // Read-only: registers no consumer, so takes no event from anyone and
+    // leaves nothing behind however the CLI exits
+    let mut subscription = client.watch(event_types, herald_ids).await?;
+    eprintln!("Tailing events (Ctrl-C to stop)...");
+
+    loop {
+        let event = tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            next = subscription.next() => match next {
+                Some(event) => event?,
+                None => return Err(anyhow!("Event stream closed by server")),
+            },
+        };
+
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(&event)?),
+            OutputFormat::Text => print_event(&event),
+        }
+    }
+}
+
+async fn handle_schemas_list(client: &AgoraClient, format: OutputFormat) -> Result<()> {
+    let schemas = client.list_schemas().await?;
+
+    match format {
+        OutputFormat::Json => print_json(&serde_json::json!({ "schemas": schemas }))?,
+        OutputFormat::Text => {
+            if schemas.is_empty() {
+                println!("No schemas registered.");
+            }
+            for schema in &schemas {
+                println!(
+                    "{} (from {})",
+                    schema.event_type,
+                    schema.herald_id.as_deref().unwrap_or("config")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_schemas_show(
+    event_type: &str,
+    client: &AgoraClient,
+    format: OutputFormat,
+) -> Result<()> {
+    let schema = client.get_schema(event_type).await?;
+
+    match format {
+        OutputFormat::Json => print_json(&schema)?,
+        // The schema itself is what a reader is after
+        OutputFormat::Text => print_json(&schema.schema)?,
+    }
+    Ok(())
+}
+
+// === Helper Functions ===
+
+fn with_consumer(client: AgoraClient, consumer: Option<String>) -> AgoraClient {
+    match consumer {
+        Some(consumer) => client.with_consumer(consumer),
+        None => client,
+    }
+}
+
+fn print_json(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_herald(herald: &HeraldInfo) {
+    println!("ID:             {}", herald.id);
+    println!(
+        "Description:    {}",
+        herald.description.as_deref().unwrap_or("")
+    );
+    println!("Status:         {:?}", herald.status);
+    println!("Registered:     {}", format_time(herald.registered_at));
+    println!("Last heartbeat: {}", format_time(herald.last_heartbeat));
+    println!("Dead letters:   {}", herald.dead_letters);
+    if let Some(limits) = &herald.limits {
+        let limit = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        println!(
+            "Rate:           {} in the last minute (limit {})",
+            limits.recent_events,
+            limit(limits.limits.events_per_minute.map(|n| n.to_string()))
+        );
+        println!(
+            "Pending:        {} (limit {}, {:?} when exceeded)",
+            limits.pending_events,
+            limit(limits.limits.max_pending.map(|n| n.to_string())),
+            limits.limits.on_exceed
+        );
+        println!(
+            "Max payload:    {}",
+            limit(
+                limits
+                    .limits
+                    .max_payload_bytes
+                    .map(|n| format!("{} bytes", n))
+            )
+        );
+        println!(
+            "Rejected:       {}, dropped: {}",
+            limits.rejected_events, limits.dropped_events
+        );
+    }
+}
+
+fn print_event(event: &Event) {
+    println!(
+        "[{}] #{} {} from {} ({}, {}): {}",
+        format_time(event.timestamp),
+        event.id,
+        event.event_type,
+        event.herald_id,
+        event.priority,
+        event.status,
+        event.payload
+    );
+}
+
+fn format_time(at: OffsetDateTime) -> String {
+    at.format(&Rfc3339).unwrap_or_else(|_| at.to_string())
+}
+
+/// Reads the payload from `--payload`, or from `--file` ("-" for stdin).
+/// Without either, the payload is an empty object.
+fn read_payload(payload: Option<&str>, file: Option<&Path>) -> Result<serde_json::Value> {
+    let text = match (payload, file) {
+        (Some(payload), _) => payload.to_string(),
+        (None, Some(path)) if path == Path::new("-") => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+        (None, Some(path)) => std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read payload file {}: {}", path.display(), e))?,
+        (None, None) => return Ok(serde_json::json!({})),
+    };
+    parse_payload(&text)
+}
+
+fn parse_payload(text: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(text).map_err(|e| anyhow!("Invalid JSON payload: {}", e))
+}
+
+fn parse_datetime(s: &str) -> Result<OffsetDateTime> {
+    OffsetDateTime::parse(s, &Rfc3339).map_err(|_| {
+        anyhow!(
+            "Invalid datetime: {}. Use RFC3339 (e.g., 2026-03-15T14:30:00Z)",
+            s
+        )
+    })
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_payload() {
+        assert_eq!(
+            parse_payload(r#"{"content": "hi"}"#).unwrap(),
+            serde_json::json!({ "content": "hi" })
+        );
+        assert!(parse_payload("not json").is_err());
+    }
+
+    #[test]
+    fn test_read_payload_defaults_to_empty_object() {
+        assert_eq!(read_payload(None, None).unwrap(), serde_json::json!({}));
+        assert!(read_payload(None, Some(Path::new("/nonexistent/payload.json"))).is_err());
+    }
+
+    #[test]
+    fn test_parse_datetime() {
+        let at = parse_datetime("2026-03-15T14:30:00Z").unwrap();
+        assert_eq!(at.unix_timestamp(), 1773585000);
+        assert!(parse_datetime("2026-03-15 14:30").is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_push() {
+        let cli = Cli::try_parse_from([
+            "agora-cli",
+            "--format",
+            "json",
+            "events",
+            "push",
+            "chat.message",
+            "--payload",
+            "{}",
+            "--priority",
+            "urgent",
+        ])
+        .unwrap();
+        assert!(matches!(cli.format, OutputFormat::Json));
+        let Commands::Events { action: EventCommands::Push { priority, herald_id, .. } } =
+            cli.command
+        else {
+            panic!("expected events push");
+        };
+        assert_eq!(EventPriority::from(priority), EventPriority::Urgent);
+        assert_eq!(herald_id, CLI_HERALD_ID);
+
+        // --payload and --file are exclusive
+        assert!(
+            Cli::try_parse_from([
+                "agora-cli",
+                "events",
+                "push",
+                "x",
+                "--payload",
+                "{}",
+                "--file",
+                "-"
+            ])
+            .is_err()
+        );
+    }
+}
@@ -1,29 +1,54 @@
 //! HTTP client for Agora event hub.
 
 use async_trait::async_trait;
-use reqwest::{Client, Error as ReqwestError, Response};
+use reqwest::{Client, Error as ReqwestError, RequestBuilder, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tracing::{debug, instrument};
 
-use agora_common::event::{Event, EventsListResponse};
-use agora_common::herald::{HeraldInfo, HeraldsListResponse};
+use agora_common::consumer::{ConsumerInfo, UpsertConsumerRequest};
+use agora_common::event::{
+    BatchAckMode, BatchUpdateEventsResponse, CreateEventRequest, Event, EventsListResponse,
+    QueuedEventsQuery, QueuedEventsResponse,
+};
+use agora_common::herald::{
+    HeartbeatResponse, HeraldInfo, HeraldsListResponse, RegisterHeraldRequest,
+    RegisterHeraldResponse,
+};
+use agora_common::schema::{EventSchema, EventSchemasResponse};
 
-use crate::AgoraClientTrait;
+use crate::{AgoraClientTrait, EventSubscription};
 
 /// Client error types.
 #[derive(Debug)]
 pub enum AgoraClientError {
     NetworkError(ReqwestError),
     ApiError(String),
+    /// The server answered with a non-success status.
+    HttpError(StatusCode, String),
     JsonError(serde_json::Error),
 }
 
+impl AgoraClientError {
+    /// The HTTP status the server answered with, if it answered at all.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            AgoraClientError::HttpError(status, _) => Some(*status),
+            _ => None,
+        }
+    }
+}
+
 impl fmt::Display for AgoraClientError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             AgoraClientError::NetworkError(e) => write!(f, "Network error: {}", e),
             AgoraClientError::ApiError(msg) => write!(f, "API error: {}", msg),
+            AgoraClientError::HttpError(status, msg) => {
+                write!(f, "API error: HTTP {}: {}", status, msg)
+            }
             AgoraClientError::JsonError(e) => write!(f, "JSON error: {}", e),
         }
     }
@@ -43,17 +68,67 @@ impl From<serde_json::Error> for AgoraClientError {
     }
 }
 
+/// Time allowed for a long-poll response beyond the requested wait.
+const LONG_POLL_GRACE: Duration = Duration::from_secs(30);
+
 /// HTTP client for Agora event hub.
 #[derive(Clone)]
 pub struct AgoraClient {
     client: Client,
     base_url: String,
+    /// Consumer to fetch and ack as; the server's default when unset.
+    consumer: Option<String>,
+    /// Token to authenticate herald requests with. Shared between clones, so
+    /// the token issued at registration is used by all of them.
+    herald_token: Arc<RwLock<Option<String>>>,
 }
 
 impl AgoraClient {
     /// Creates a new Agora client with the given base URL and HTTP client.
     pub fn new(base_url: &str, client: Client) -> Self {
-        Self { client, base_url: base_url.to_string() }
+        Self {
+            client,
+            base_url: base_url.to_string(),
+            consumer: None,
+            herald_token: Arc::default(),
+        }
+    }
+
+    /// Fetches, streams and acks events as the named consumer.
+    ///
+    /// The consumer must already exist (see [`AgoraClient::register_consumer`]).
+    pub fn with_consumer(mut self, consumer: impl Into<String>) -> Self {
+        self.consumer = Some(consumer.into());
+        self
+    }
+
+    /// Authenticates herald requests with a token preconfigured in Agora.
+    ///
+    /// Without one, the token Agora issues at registration is used.
+    pub fn with_herald_token(self, token: Option<String>) -> Self {
+        *self.herald_token.write().unwrap() = token;
+        self
+    }
+
+    /// Adds the herald bearer token to a request, if there is one.
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.herald_token.read().unwrap().as_deref() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Checks that a response without a body succeeded.
+    async fn handle_empty_response(response: Response) -> Result<(), AgoraClientError> {
+        let status = response.status();
+        debug!("Received response from {}: {}", response.url(), status);
+
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AgoraClientError::HttpError(status, error_text));
+        }
+
+        Ok(())
     }
 
     /// Handles HTTP response and converts to expected type.
@@ -70,10 +145,7 @@ impl AgoraClient {
                 .text()
                 .await
                 .unwrap_or_else(|_| "Failed to read error response".to_string());
-            return Err(AgoraClientError::ApiError(format!(
-                "HTTP {}: {}",
-                status, error_text
-            )));
+            return Err(AgoraClientError::HttpError(status, error_text));
         }
 
         let text = response.text().await?;
@@ -111,20 +183,117 @@ impl AgoraClient {
         let url = format!("{}/events/fetch", self.base_url);
         debug!("Fetching events from: {}", url);
 
-        let body = serde_json::json!({ "limit": limit.unwrap_or(10) });
+        let body = serde_json::json!({ "limit": limit.unwrap_or(10), "consumer": self.consumer });
         let response = self.client.post(&url).json(&body).send().await?;
         let result: EventsListResponse = Self::handle_response(response).await?;
 
         Ok(result.events)
     }
 
+    /// Long-polls for events (POST /events/fetch with `wait_ms`).
+    /// Returns as soon as events are deliverable, or empty once `wait_ms` expires.
+    #[instrument(skip(self))]
+    pub async fn fetch_events_wait(
+        &self,
+        limit: Option<u32>,
+        wait_ms: u64,
+    ) -> Result<Vec<Event>, AgoraClientError> {
+        let url = format!("{}/events/fetch", self.base_url);
+        debug!("Long-polling events from: {} (wait_ms={})", url, wait_ms);
+
+        let body = serde_json::json!({
+            "limit": limit.unwrap_or(10),
+            "wait_ms": wait_ms,
+            "consumer": self.consumer,
+        });
+        // The server holds the request open, so allow for the wait on top of the usual budget
+        let response = self
+            .client
+            .post(&url)
+            .json(&body)
+            .timeout(Duration::from_millis(wait_ms) + LONG_POLL_GRACE)
+            .send()
+            .await?;
+        let result: EventsListResponse = Self::handle_response(response).await?;
+
+        Ok(result.events)
+    }
+
+    /// Subscribes to events pushed by the server (GET /events/stream).
+    /// Streamed events are Delivered and must still be acknowledged.
+    #[instrument(skip(self))]
+    pub async fn subscribe(
+        &self,
+        limit: Option<u32>,
+    ) -> Result<EventSubscription, AgoraClientError> {
+        let url = format!("{}/events/stream", self.base_url);
+        debug!("Subscribing to events at: {}", url);
+
+        let mut request = self
+            .client
+            .get(&url)
+            .query(&[("limit", limit.unwrap_or(10))]);
+        if let Some(consumer) = &self.consumer {
+            request = request.query(&[("consumer", consumer)]);
+        }
+
+        Self::open_stream(&url, request).await
+    }
+
+    /// Watches events as they are pushed (GET /events/watch), filtered by
+    /// event type and herald ID globs (empty lists match everything).
+    ///
+    /// Read-only: no consumer is registered and nothing is delivered, so
+    /// watched events need no ack and nothing is left behind on exit.
+    #[instrument(skip(self))]
+    pub async fn watch(
+        &self,
+        event_types: &[String],
+        herald_ids: &[String],
+    ) -> Result<EventSubscription, AgoraClientError> {
+        let url = format!("{}/events/watch", self.base_url);
+        debug!("Watching events at: {}", url);
+
+        let request = self
+            .client
+            .get(&url)
+            .query(&[("event_types", event_types.join(",")), ("herald_ids", herald_ids.join(","))]);
+
+        Self::open_stream(&url, request).await
+    }
+
+    /// Opens a `text/event-stream` request and reads it in the background.
+    async fn open_stream(
+        url: &str,
+        request: RequestBuilder,
+    ) -> Result<EventSubscription, AgoraClientError> {
+        // The stream stays open indefinitely, so lift any client-wide request timeout
+        let response = request
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .timeout(Duration::MAX)
+            .send()
+            .await?;
+        let status = response.status();
+        debug!("Received response from {}: {}", url, status);
+
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AgoraClientError::ApiError(format!(
+                "HTTP {}: {}",
+                status, error_text
+            )));
+        }
+
+        Ok(EventSubscription::spawn(response))
+    }
+
     /// Acknowledges a single event.
     #[instrument(skip(self))]
     pub async fn ack_event(&self, event_id: u64) -> Result<Event, AgoraClientError> {
         let url = format!("{}/events/{}", self.base_url, event_id);
         debug!("Acknowledging event at: {}", url);
 
-        let body = serde_json::json!({ "status": "acked" });
+        let body = serde_json::json!({ "status": "acked", "consumer": self.consumer });
         let response = self.client.patch(&url).json(&body).send().await?;
         let event: Event = Self::handle_response(response).await?;
 
@@ -132,20 +301,101 @@ impl AgoraClient {
     }
 
     /// Batch acknowledges multiple events.
+    /// Returns the acked IDs along with those that were missing (unknown or
+    /// already acked) or failed; with [`BatchAckMode::AllOrNothing`], any of
+    /// the latter means nothing was acked.
     #[instrument(skip(self))]
-    pub async fn ack_events(&self, event_ids: Vec<u64>) -> Result<usize, AgoraClientError> {
+    pub async fn ack_events(
+        &self,
+        event_ids: Vec<u64>,
+        mode: BatchAckMode,
+    ) -> Result<BatchUpdateEventsResponse, AgoraClientError> {
         let url = format!("{}/events", self.base_url);
         debug!("Batch acknowledging {} events at: {}", event_ids.len(), url);
 
         let body = serde_json::json!({
             "event_ids": event_ids,
-            "status": "acked"
+            "status": "acked",
+            "consumer": self.consumer,
+            "mode": mode,
         });
         let response = self.client.patch(&url).json(&body).send().await?;
-        let result: serde_json::Value = Self::handle_response(response).await?;
+        let result: BatchUpdateEventsResponse = Self::handle_response(response).await?;
 
-        let updated = result["updated"].as_u64().unwrap_or(0) as usize;
-        Ok(updated)
+        Ok(result)
+    }
+
+    /// Requeues a queued event for immediate redelivery (PATCH /events/{id}).
+    #[instrument(skip(self))]
+    pub async fn requeue_event(&self, event_id: u64) -> Result<Event, AgoraClientError> {
+        let url = format!("{}/events/{}", self.base_url, event_id);
+        debug!("Requeueing event at: {}", url);
+
+        let body = serde_json::json!({ "status": "pending", "consumer": self.consumer });
+        let response = self.client.patch(&url).json(&body).send().await?;
+        let event: Event = Self::handle_response(response).await?;
+
+        Ok(event)
+    }
+
+    /// Lists a consumer's queued events without delivering them (GET /events).
+    #[instrument(skip(self))]
+    pub async fn list_queued_events(
+        &self,
+        query: &QueuedEventsQuery,
+    ) -> Result<QueuedEventsResponse, AgoraClientError> {
+        let url = format!("{}/events", self.base_url);
+        debug!("Listing queued events from: {}", url);
+
+        let response = self.client.get(&url).query(query).send().await?;
+        let result: QueuedEventsResponse = Self::handle_response(response).await?;
+
+        Ok(result)
+    }
+
+    /// Pushes an event as a herald (POST /events).
+    #[instrument(skip(self, request), fields(event_type = %request.event_type))]
+    pub async fn push_event(&self, request: CreateEventRequest) -> Result<Event, AgoraClientError> {
+        let url = format!("{}/events", self.base_url);
+        debug!("Pushing event to: {}", url);
+
+        let response = self
+            .authorized(self.client.post(&url))
+            .json(&request)
+            .send()
+            .await?;
+        let event: Event = Self::handle_response(response).await?;
+
+        Ok(event)
+    }
+
+    // === Consumer operations ===
+
+    /// Creates a consumer or replaces its filters (PUT /consumers/{name}).
+    /// New filters apply to events pushed from now on.
+    #[instrument(skip(self))]
+    pub async fn register_consumer(
+        &self,
+        name: &str,
+        request: UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo, AgoraClientError> {
+        let url = format!("{}/consumers/{}", self.base_url, name);
+        debug!("Registering consumer at: {}", url);
+
+        let response = self.client.put(&url).json(&request).send().await?;
+        let consumer: ConsumerInfo = Self::handle_response(response).await?;
+
+        Ok(consumer)
+    }
+
+    /// Deletes a consumer and its undelivered events (DELETE /consumers/{name}).
+    #[instrument(skip(self))]
+    pub async fn delete_consumer(&self, name: &str) -> Result<(), AgoraClientError> {
+        let url = format!("{}/consumers/{}", self.base_url, name);
+        debug!("Deleting consumer at: {}", url);
+
+        let response = self.client.delete(&url).send().await?;
+        Self::handle_empty_response(response).await
     }
 
     // === Herald operations ===
@@ -174,6 +424,76 @@ impl AgoraClient {
         Ok(herald)
     }
 
+    /// Registers as a herald (POST /heralds).
+    /// The token Agora confirms or issues authenticates later herald requests.
+    #[instrument(skip(self))]
+    pub async fn register_herald(
+        &self,
+        request: RegisterHeraldRequest,
+    ) -> Result<RegisterHeraldResponse, AgoraClientError> {
+        let url = format!("{}/heralds", self.base_url);
+        debug!("Registering herald at: {}", url);
+
+        let response = self
+            .authorized(self.client.post(&url))
+            .json(&request)
+            .send()
+            .await?;
+        let registered: RegisterHeraldResponse = Self::handle_response(response).await?;
+        *self.herald_token.write().unwrap() = Some(registered.token.clone());
+
+        Ok(registered)
+    }
+
+    /// Sends a herald heartbeat (POST /heralds/{id}/heartbeat).
+    /// Fails with HTTP 404 if Agora no longer knows the herald.
+    #[instrument(skip(self))]
+    pub async fn heartbeat(&self, id: &str) -> Result<HeartbeatResponse, AgoraClientError> {
+        let url = format!("{}/heralds/{}/heartbeat", self.base_url, id);
+        debug!("Sending heartbeat to: {}", url);
+
+        let response = self.authorized(self.client.post(&url)).send().await?;
+        let heartbeat: HeartbeatResponse = Self::handle_response(response).await?;
+
+        Ok(heartbeat)
+    }
+
+    /// Unregisters a herald (DELETE /heralds/{id}).
+    #[instrument(skip(self))]
+    pub async fn unregister(&self, id: &str) -> Result<(), AgoraClientError> {
+        let url = format!("{}/heralds/{}", self.base_url, id);
+        debug!("Unregistering herald at: {}", url);
+
+        let response = self.authorized(self.client.delete(&url)).send().await?;
+        Self::handle_empty_response(response).await
+    }
+
+    // === Schema operations ===
+
+    /// Lists the payload schemas of all event types that have one.
+    #[instrument(skip(self))]
+    pub async fn list_schemas(&self) -> Result<Vec<EventSchema>, AgoraClientError> {
+        let url = format!("{}/schemas", self.base_url);
+        debug!("Listing schemas from: {}", url);
+
+        let response = self.client.get(&url).send().await?;
+        let result: EventSchemasResponse = Self::handle_response(response).await?;
+
+        Ok(result.schemas)
+    }
+
+    /// Gets the payload schema of an event type.
+    #[instrument(skip(self))]
+    pub async fn get_schema(&self, event_type: &str) -> Result<EventSchema, AgoraClientError> {
+        let url = format!("{}/schemas/{}", self.base_url, event_type);
+        debug!("Getting schema from: {}", url);
+
+        let response = self.client.get(&url).send().await?;
+        let schema: EventSchema = Self::handle_response(response).await?;
+
+        Ok(schema)
+    }
+
     /// Gets the base URL this client is configured to use.
     pub fn base_url(&self) -> &str {
         &self.base_url
@@ -190,12 +510,36 @@ impl AgoraClientTrait for AgoraClient {
         AgoraClient::fetch_events(self, limit).await
     }
 
+    async fn fetch_events_wait(
+        &self,
+        limit: Option<u32>,
+        wait_ms: u64,
+    ) -> Result<Vec<Event>, AgoraClientError> {
+        AgoraClient::fetch_events_wait(self, limit, wait_ms).await
+    }
+
+    async fn subscribe(&self, limit: Option<u32>) -> Result<EventSubscription, AgoraClientError> {
+        AgoraClient::subscribe(self, limit).await
+    }
+
     async fn ack_event(&self, event_id: u64) -> Result<Event, AgoraClientError> {
         AgoraClient::ack_event(self, event_id).await
     }
 
-    async fn ack_events(&self, event_ids: Vec<u64>) -> Result<usize, AgoraClientError> {
-        AgoraClient::ack_events(self, event_ids).await
+    async fn ack_events(
+        &self,
+        event_ids: Vec<u64>,
+        mode: BatchAckMode,
+    ) -> Result<BatchUpdateEventsResponse, AgoraClientError> {
+        AgoraClient::ack_events(self, event_ids, mode).await
+    }
+
+    async fn register_consumer(
+        &self,
+        name: &str,
+        request: UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo, AgoraClientError> {
+        AgoraClient::register_consumer(self, name, request).await
     }
 
     async fn list_heralds(&self) -> Result<Vec<HeraldInfo>, AgoraClientError> {
@@ -206,6 +550,25 @@ impl AgoraClientTrait for AgoraClient {
         AgoraClient::get_herald(self, id).await
     }
 
+    async fn register_herald(
+        &self,
+        request: RegisterHeraldRequest,
+    ) -> Result<RegisterHeraldResponse, AgoraClientError> {
+        AgoraClient::register_herald(self, request).await
+    }
+
+    async fn heartbeat(&self, id: &str) -> Result<HeartbeatResponse, AgoraClientError> {
+        AgoraClient::heartbeat(self, id).await
+    }
+
+    async fn unregister(&self, id: &str) -> Result<(), AgoraClientError> {
+        AgoraClient::unregister(self, id).await
+    }
+
+    async fn push_event(&self, request: CreateEventRequest) -> Result<Event, AgoraClientError> {
+        AgoraClient::push_event(self, request).await
+    }
+
     fn base_url(&self) -> &str {
         &self.base_url
     }
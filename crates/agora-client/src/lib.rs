@@ -5,11 +5,21 @@
 
 mod client;
 pub mod mock;
+mod subscription;
 mod trait_def;
 
 pub use client::{AgoraClient, AgoraClientError};
+pub use subscription::EventSubscription;
 pub use trait_def::AgoraClientTrait;
 
 // Re-export commonly used types from agora
-pub use agora_common::event::{Event, EventId, EventPriority, EventStatus};
-pub use agora_common::herald::{HeraldInfo, HeraldStatus};
+pub use agora_common::consumer::{ConsumerInfo, UpsertConsumerRequest};
+pub use agora_common::event::{
+    BatchAckMode, BatchUpdateEventsResponse, CreateEventRequest, Event, EventId, EventPriority,
+    EventStatus, QueuedEvent, QueuedEventsQuery, QueuedEventsResponse,
+};
+pub use agora_common::herald::{
+    HeartbeatResponse, HeraldInfo, HeraldStatus, RegisterHeraldRequest, RegisterHeraldResponse,
+};
+pub use agora_common::schema::{EventSchema, InvalidPayloadResponse, PayloadError};
+pub use reqwest::StatusCode;
@@ -6,21 +6,32 @@
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
-use agora_common::event::Event;
-use agora_common::herald::HeraldInfo;
+use agora_common::consumer::{ConsumerInfo, UpsertConsumerRequest};
+use agora_common::event::{BatchAckMode, BatchUpdateEventsResponse, CreateEventRequest, Event};
+use agora_common::herald::{
+    HeartbeatResponse, HeraldInfo, RegisterHeraldRequest, RegisterHeraldResponse,
+};
 use async_trait::async_trait;
+use reqwest::StatusCode;
 
-use crate::{AgoraClientError, AgoraClientTrait};
+use crate::{AgoraClientError, AgoraClientTrait, EventSubscription};
 
 /// Record of a method call for verification
 #[derive(Debug, Clone, PartialEq)]
 pub enum MockCall {
     HealthCheck,
     FetchEvents { limit: Option<u32> },
+    FetchEventsWait { limit: Option<u32>, wait_ms: u64 },
+    Subscribe { limit: Option<u32> },
     AckEvent { event_id: u64 },
-    AckEvents { event_ids: Vec<u64> },
+    AckEvents { event_ids: Vec<u64>, mode: BatchAckMode },
+    RegisterConsumer { name: String, request: UpsertConsumerRequest },
     ListHeralds,
     GetHerald { id: String },
+    RegisterHerald { request: RegisterHeraldRequest },
+    Heartbeat { id: String },
+    Unregister { id: String },
+    PushEvent { request: CreateEventRequest },
 }
 
 /// Mock response types
@@ -28,18 +39,38 @@ pub enum MockCall {
 pub enum MockResponse {
     HealthCheck,
     Events(Vec<Event>),
+    Subscription(Vec<Event>),
     Event(Event),
-    AckCount(usize),
+    Acked(BatchUpdateEventsResponse),
+    Consumer(ConsumerInfo),
     Heralds(Vec<HeraldInfo>),
     Herald(HeraldInfo),
+    Registered(RegisterHeraldResponse),
+    Heartbeat(HeartbeatResponse),
     Empty,
 }
 
+/// Mock error types
+#[derive(Debug, Clone)]
+enum MockError {
+    Api(String),
+    Http(StatusCode, String),
+}
+
+impl From<MockError> for AgoraClientError {
+    fn from(error: MockError) -> Self {
+        match error {
+            MockError::Api(msg) => AgoraClientError::ApiError(msg),
+            MockError::Http(status, msg) => AgoraClientError::HttpError(status, msg),
+        }
+    }
+}
+
 /// Internal state for the mock client
 #[derive(Debug, Default)]
 struct MockState {
     /// Queue of responses to return for subsequent calls
-    responses: VecDeque<Result<MockResponse, String>>,
+    responses: VecDeque<Result<MockResponse, MockError>>,
     /// All calls made to this mock
     calls: Vec<MockCall>,
 }
@@ -98,8 +129,19 @@ impl MockAgoraClient {
         self
     }
 
+    /// Add a subscription response to the queue.
+    /// The subscription yields these events, then ends.
+    pub fn push_subscription(&mut self, events: Vec<Event>) -> &mut Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push_back(Ok(MockResponse::Subscription(events)));
+        self
+    }
+
     /// Add a single event response to the queue
-    pub fn push_event(&mut self, event: Event) -> &mut Self {
+    pub fn push_event_response(&mut self, event: Event) -> &mut Self {
         self.state
             .lock()
             .unwrap()
@@ -108,13 +150,31 @@ impl MockAgoraClient {
         self
     }
 
-    /// Add an ack count response to the queue
-    pub fn push_ack_count(&mut self, count: usize) -> &mut Self {
+    /// Add a batch ack response to the queue
+    pub fn push_ack_response(&mut self, response: BatchUpdateEventsResponse) -> &mut Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push_back(Ok(MockResponse::Acked(response)));
+        self
+    }
+
+    /// Add a batch ack response acking exactly `event_ids` to the queue
+    pub fn push_acked(&mut self, event_ids: Vec<u64>) -> &mut Self {
+        self.push_ack_response(BatchUpdateEventsResponse {
+            acked_ids: event_ids,
+            ..Default::default()
+        })
+    }
+
+    /// Add a consumer response to the queue
+    pub fn push_consumer(&mut self, consumer: ConsumerInfo) -> &mut Self {
         self.state
             .lock()
             .unwrap()
             .responses
-            .push_back(Ok(MockResponse::AckCount(count)));
+            .push_back(Ok(MockResponse::Consumer(consumer)));
         self
     }
 
@@ -138,6 +198,26 @@ impl MockAgoraClient {
         self
     }
 
+    /// Add a herald registration response to the queue
+    pub fn push_registered(&mut self, registered: RegisterHeraldResponse) -> &mut Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push_back(Ok(MockResponse::Registered(registered)));
+        self
+    }
+
+    /// Add a heartbeat response to the queue
+    pub fn push_heartbeat(&mut self, heartbeat: HeartbeatResponse) -> &mut Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push_back(Ok(MockResponse::Heartbeat(heartbeat)));
+        self
+    }
+
     /// Add an empty success response to the queue
     pub fn push_empty(&mut self) -> &mut Self {
         self.state
@@ -154,7 +234,17 @@ impl MockAgoraClient {
             .lock()
             .unwrap()
             .responses
-            .push_back(Err(error.into()));
+            .push_back(Err(MockError::Api(error.into())));
+        self
+    }
+
+    /// Add an error response with an HTTP status to the queue
+    pub fn push_http_error(&mut self, status: StatusCode, error: impl Into<String>) -> &mut Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .push_back(Err(MockError::Http(status, error.into())));
         self
     }
 
@@ -196,7 +286,7 @@ impl MockAgoraClient {
     }
 
     /// Get next response from queue
-    fn pop_response(&self) -> Option<Result<MockResponse, String>> {
+    fn pop_response(&self) -> Option<Result<MockResponse, MockError>> {
         self.state.lock().unwrap().responses.pop_front()
     }
 }
@@ -207,7 +297,7 @@ impl AgoraClientTrait for MockAgoraClient {
         self.record_call(MockCall::HealthCheck);
         match self.pop_response() {
             Some(Ok(MockResponse::HealthCheck)) => Ok(()),
-            Some(Err(e)) => Err(AgoraClientError::ApiError(e)),
+            Some(Err(e)) => Err(e.into()),
             _ => Ok(()),
         }
     }
@@ -217,33 +307,80 @@ impl AgoraClientTrait for MockAgoraClient {
         match self.pop_response() {
             Some(Ok(MockResponse::Events(events))) => Ok(events),
             Some(Ok(MockResponse::Empty)) => Ok(vec![]),
-            Some(Err(e)) => Err(AgoraClientError::ApiError(e)),
+            Some(Err(e)) => Err(e.into()),
+            _ => Ok(self.default_events_response.clone()),
+        }
+    }
+
+    async fn fetch_events_wait(
+        &self,
+        limit: Option<u32>,
+        wait_ms: u64,
+    ) -> Result<Vec<Event>, AgoraClientError> {
+        self.record_call(MockCall::FetchEventsWait { limit, wait_ms });
+        match self.pop_response() {
+            Some(Ok(MockResponse::Events(events))) => Ok(events),
+            Some(Ok(MockResponse::Empty)) => Ok(vec![]),
+            Some(Err(e)) => Err(e.into()),
             _ => Ok(self.default_events_response.clone()),
         }
     }
 
+    async fn subscribe(&self, limit: Option<u32>) -> Result<EventSubscription, AgoraClientError> {
+        self.record_call(MockCall::Subscribe { limit });
+        match self.pop_response() {
+            Some(Ok(MockResponse::Subscription(events))) => {
+                Ok(EventSubscription::from_events(events))
+            }
+            Some(Ok(MockResponse::Empty)) => Ok(EventSubscription::from_events(vec![])),
+            Some(Err(e)) => Err(e.into()),
+            _ => Ok(EventSubscription::from_events(
+                self.default_events_response.clone(),
+            )),
+        }
+    }
+
     // Design note: Single-value methods require explicit response configuration.
     // Unlike list methods that can return empty Vec as a sensible default,
     // single-value methods cannot create a meaningful default value.
-    // Always use push_event() before calling ack_event() in tests.
+    // Always use push_event_response() before calling ack_event() in tests.
     async fn ack_event(&self, event_id: u64) -> Result<Event, AgoraClientError> {
         self.record_call(MockCall::AckEvent { event_id });
         match self.pop_response() {
             Some(Ok(MockResponse::Event(event))) => Ok(event),
-            Some(Err(e)) => Err(AgoraClientError::ApiError(e)),
+            Some(Err(e)) => Err(e.into()),
             _ => Err(AgoraClientError::ApiError(
                 "No response configured for ack_event".to_string(),
             )),
         }
     }
 
-    async fn ack_events(&self, event_ids: Vec<u64>) -> Result<usize, AgoraClientError> {
-        self.record_call(MockCall::AckEvents { event_ids });
+    async fn ack_events(
+        &self,
+        event_ids: Vec<u64>,
+        mode: BatchAckMode,
+    ) -> Result<BatchUpdateEventsResponse, AgoraClientError> {
+        self.record_call(MockCall::AckEvents { event_ids, mode });
         match self.pop_response() {
-            Some(Ok(MockResponse::AckCount(count))) => Ok(count),
-            Some(Ok(MockResponse::Empty)) => Ok(0),
-            Some(Err(e)) => Err(AgoraClientError::ApiError(e)),
-            _ => Ok(0),
+            Some(Ok(MockResponse::Acked(response))) => Ok(response),
+            Some(Err(e)) => Err(e.into()),
+            _ => Ok(BatchUpdateEventsResponse::default()),
+        }
+    }
+
+    // Always use push_consumer() before calling register_consumer() in tests.
+    async fn register_consumer(
+        &self,
+        name: &str,
+        request: UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo, AgoraClientError> {
+        self.record_call(MockCall::RegisterConsumer { name: name.to_string(), request });
+        match self.pop_response() {
+            Some(Ok(MockResponse::Consumer(consumer))) => Ok(consumer),
+            Some(Err(e)) => Err(e.into()),
+            _ => Err(AgoraClientError::ApiError(
+                "No response configured for register_consumer".to_string(),
+            )),
         }
     }
 
@@ -252,7 +389,7 @@ impl AgoraClientTrait for MockAgoraClient {
         match self.pop_response() {
             Some(Ok(MockResponse::Heralds(heralds))) => Ok(heralds),
             Some(Ok(MockResponse::Empty)) => Ok(vec![]),
-            Some(Err(e)) => Err(AgoraClientError::ApiError(e)),
+            Some(Err(e)) => Err(e.into()),
             _ => Ok(vec![]),
         }
     }
@@ -265,13 +402,60 @@ impl AgoraClientTrait for MockAgoraClient {
         self.record_call(MockCall::GetHerald { id: id.to_string() });
         match self.pop_response() {
             Some(Ok(MockResponse::Herald(herald))) => Ok(herald),
-            Some(Err(e)) => Err(AgoraClientError::ApiError(e)),
+            Some(Err(e)) => Err(e.into()),
             _ => Err(AgoraClientError::ApiError(
                 "No response configured for get_herald".to_string(),
             )),
         }
     }
 
+    // Always use push_registered() before calling register_herald() in tests.
+    async fn register_herald(
+        &self,
+        request: RegisterHeraldRequest,
+    ) -> Result<RegisterHeraldResponse, AgoraClientError> {
+        self.record_call(MockCall::RegisterHerald { request });
+        match self.pop_response() {
+            Some(Ok(MockResponse::Registered(registered))) => Ok(registered),
+            Some(Err(e)) => Err(e.into()),
+            _ => Err(AgoraClientError::ApiError(
+                "No response configured for register_herald".to_string(),
+            )),
+        }
+    }
+
+    // Always use push_heartbeat() before calling heartbeat() in tests.
+    async fn heartbeat(&self, id: &str) -> Result<HeartbeatResponse, AgoraClientError> {
+        self.record_call(MockCall::Heartbeat { id: id.to_string() });
+        match self.pop_response() {
+            Some(Ok(MockResponse::Heartbeat(heartbeat))) => Ok(heartbeat),
+            Some(Err(e)) => Err(e.into()),
+            _ => Err(AgoraClientError::ApiError(
+                "No response configured for heartbeat".to_string(),
+            )),
+        }
+    }
+
+    async fn unregister(&self, id: &str) -> Result<(), AgoraClientError> {
+        self.record_call(MockCall::Unregister { id: id.to_string() });
+        match self.pop_response() {
+            Some(Err(e)) => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    // Always use push_event_response() before calling push_event() in tests.
+    async fn push_event(&self, request: CreateEventRequest) -> Result<Event, AgoraClientError> {
+        self.record_call(MockCall::PushEvent { request });
+        match self.pop_response() {
+            Some(Ok(MockResponse::Event(event))) => Ok(event),
+            Some(Err(e)) => Err(e.into()),
+            _ => Err(AgoraClientError::ApiError(
+                "No response configured for push_event".to_string(),
+            )),
+        }
+    }
+
     fn base_url(&self) -> &str {
         &self.base_url
     }
@@ -298,6 +482,8 @@ mod tests {
             priority: EventPriority::Normal,
             timestamp: time::OffsetDateTime::now_utc(),
             status: EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
         }
     }
 
@@ -309,6 +495,8 @@ mod tests {
             status: HeraldStatus::Active,
             registered_at: now,
             last_heartbeat: now,
+            dead_letters: 0,
+            limits: None,
         }
     }
 
@@ -335,11 +523,38 @@ mod tests {
         assert!(mock.was_called(|c| matches!(c, MockCall::FetchEvents { .. })));
     }
 
+    #[tokio::test]
+    async fn test_mock_fetch_events_wait() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_events(vec![create_test_event(1)]);
+
+        let result = mock.fetch_events_wait(None, 5000).await.unwrap();
+        assert_eq!(result.len(), 1);
+
+        assert_eq!(
+            mock.get_calls(),
+            vec![MockCall::FetchEventsWait { limit: None, wait_ms: 5000 }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_subscribe() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_subscription(vec![create_test_event(1), create_test_event(2)]);
+
+        let mut subscription = mock.subscribe(Some(5)).await.unwrap();
+        assert_eq!(subscription.next().await.unwrap().unwrap().id, 1);
+        assert_eq!(subscription.next().await.unwrap().unwrap().id, 2);
+        assert!(subscription.next().await.is_none());
+
+        assert!(mock.was_called(|c| matches!(c, MockCall::Subscribe { limit: Some(5) })));
+    }
+
     #[tokio::test]
     async fn test_mock_ack_event() {
         let mut mock = MockAgoraClient::new();
         let event = create_test_event(42);
-        mock.push_event(event);
+        mock.push_event_response(event);
 
         let result = mock.ack_event(42).await.unwrap();
         assert_eq!(result.id, 42);
@@ -350,12 +565,49 @@ mod tests {
     #[tokio::test]
     async fn test_mock_ack_events() {
         let mut mock = MockAgoraClient::new();
-        mock.push_ack_count(3);
+        mock.push_ack_response(BatchUpdateEventsResponse {
+            acked_ids: vec![1, 2],
+            missing_ids: vec![3],
+            failed_ids: vec![],
+        });
+
+        let result = mock
+            .ack_events(vec![1, 2, 3], BatchAckMode::BestEffort)
+            .await
+            .unwrap();
+        assert_eq!(result.acked_ids, vec![1, 2]);
+        assert_eq!(result.missing_ids, vec![3]);
+
+        assert!(mock.was_called(|c| matches!(
+            c,
+            MockCall::AckEvents { mode: BatchAckMode::BestEffort, .. }
+        )));
+    }
 
-        let result = mock.ack_events(vec![1, 2, 3]).await.unwrap();
-        assert_eq!(result, 3);
+    #[tokio::test]
+    async fn test_mock_register_consumer() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_consumer(ConsumerInfo {
+            name: "observer".to_string(),
+            event_types: vec!["chat.*".to_string()],
+            herald_ids: vec![],
+            created_at: time::OffsetDateTime::now_utc(),
+            pending: 0,
+            delivered: 0,
+        });
+
+        let request =
+            UpsertConsumerRequest { event_types: vec!["chat.*".to_string()], herald_ids: vec![] };
+        let result = mock
+            .register_consumer("observer", request.clone())
+            .await
+            .unwrap();
+        assert_eq!(result.name, "observer");
 
-        assert!(mock.was_called(|c| matches!(c, MockCall::AckEvents { .. })));
+        assert_eq!(
+            mock.get_calls(),
+            vec![MockCall::RegisterConsumer { name: "observer".to_string(), request }]
+        );
     }
 
     #[tokio::test]
@@ -383,6 +635,75 @@ mod tests {
         assert!(mock.was_called(|c| matches!(c, MockCall::GetHerald { id } if id == "herald-42")));
     }
 
+    #[tokio::test]
+    async fn test_mock_register_herald() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_registered(RegisterHeraldResponse {
+            herald: create_test_herald("herald-1"),
+            token: "secret".to_string(),
+        })
+        .push_heartbeat(HeartbeatResponse {
+            status: HeraldStatus::Active,
+            last_heartbeat: time::OffsetDateTime::now_utc(),
+        });
+
+        let request = RegisterHeraldRequest {
+            id: "herald-1".to_string(),
+            description: None,
+            schemas: Default::default(),
+        };
+        let registered = mock.register_herald(request.clone()).await.unwrap();
+        assert_eq!(registered.token, "secret");
+        mock.heartbeat("herald-1").await.unwrap();
+        mock.unregister("herald-1").await.unwrap();
+
+        assert_eq!(
+            mock.get_calls(),
+            vec![
+                MockCall::RegisterHerald { request },
+                MockCall::Heartbeat { id: "herald-1".to_string() },
+                MockCall::Unregister { id: "herald-1".to_string() },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_push_event() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_event_response(create_test_event(7));
+
+        let request = CreateEventRequest {
+            event_type: "test.event".to_string(),
+            herald_id: "test-herald".to_string(),
+            priority: EventPriority::Normal,
+            payload: serde_json::json!({}),
+            timestamp: time::OffsetDateTime::now_utc(),
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        };
+        let event = mock.push_event(request.clone()).await.unwrap();
+        assert_eq!(event.id, 7);
+
+        // Single-value methods need a configured response
+        assert!(mock.push_event(request.clone()).await.is_err());
+
+        assert_eq!(
+            mock.call_count(|c| *c == MockCall::PushEvent { request: request.clone() }),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_http_error_response() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_http_error(StatusCode::NOT_FOUND, "Herald not found");
+
+        let err = mock.heartbeat("gone").await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+    }
+
     #[tokio::test]
     async fn test_mock_error_response() {
         let mut mock = MockAgoraClient::new();
@@ -467,22 +788,28 @@ mod tests {
     #[tokio::test]
     async fn test_mock_multiple_ack_calls() {
         let mut mock = MockAgoraClient::new();
-        mock.push_ack_count(1).push_ack_count(3);
+        mock.push_acked(vec![1]).push_acked(vec![2, 3, 4]);
 
         // Ack single event
-        let result1 = mock.ack_events(vec![1]).await.unwrap();
-        assert_eq!(result1, 1);
+        let result1 = mock
+            .ack_events(vec![1], BatchAckMode::BestEffort)
+            .await
+            .unwrap();
+        assert_eq!(result1.acked_ids.len(), 1);
 
         // Ack multiple events
-        let result2 = mock.ack_events(vec![2, 3, 4]).await.unwrap();
-        assert_eq!(result2, 3);
+        let result2 = mock
+            .ack_events(vec![2, 3, 4], BatchAckMode::AllOrNothing)
+            .await
+            .unwrap();
+        assert_eq!(result2.acked_ids.len(), 3);
 
         // Verify both calls with exact parameters using PartialEq
         assert_eq!(
             mock.get_calls(),
             vec![
-                MockCall::AckEvents { event_ids: vec![1] },
-                MockCall::AckEvents { event_ids: vec![2, 3, 4] }
+                MockCall::AckEvents { event_ids: vec![1], mode: BatchAckMode::BestEffort },
+                MockCall::AckEvents { event_ids: vec![2, 3, 4], mode: BatchAckMode::AllOrNothing }
             ]
         );
     }
@@ -492,11 +819,14 @@ mod tests {
         let mut mock = MockAgoraClient::new();
         mock.push_health_check()
             .push_events(vec![create_test_event(1)])
-            .push_ack_count(1);
+            .push_acked(vec![1]);
 
         mock.health_check().await.unwrap();
         let _ = mock.fetch_events(None).await.unwrap();
-        let _ = mock.ack_events(vec![1]).await.unwrap();
+        let _ = mock
+            .ack_events(vec![1], BatchAckMode::BestEffort)
+            .await
+            .unwrap();
 
         // Verify exact call sequence using PartialEq
         assert_eq!(
@@ -504,7 +834,7 @@ mod tests {
             vec![
                 MockCall::HealthCheck,
                 MockCall::FetchEvents { limit: None },
-                MockCall::AckEvents { event_ids: vec![1] }
+                MockCall::AckEvents { event_ids: vec![1], mode: BatchAckMode::BestEffort }
             ]
         );
     }
@@ -0,0 +1,192 @@
+//! Server-push event subscription (GET /events/stream).
+
+use agora_common::event::Event;
+use reqwest::Response;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::AgoraClientError;
+
+/// Buffered events before the stream reader waits for the consumer.
+const SUBSCRIPTION_BUFFER: usize = 64;
+
+/// SSE message name Agora uses for delivered events.
+const EVENT_MESSAGE: &str = "event";
+
+/// Live stream of events pushed by Agora.
+///
+/// Streamed events are Delivered, not Acked: ack them as usual, or they are
+/// redelivered after the retry interval. Dropping the subscription closes the
+/// connection.
+#[derive(Debug)]
+pub struct EventSubscription {
+    receiver: mpsc::Receiver<Result<Event, AgoraClientError>>,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl EventSubscription {
+    /// Creates a subscription fed by a channel, e.g. for mocks and tests.
+    pub fn from_receiver(receiver: mpsc::Receiver<Result<Event, AgoraClientError>>) -> Self {
+        Self { receiver, reader: None }
+    }
+
+    /// Creates a subscription that yields the given events, then ends.
+    pub fn from_events(events: Vec<Event>) -> Self {
+        let (sender, receiver) = mpsc::channel(events.len().max(1));
+        for event in events {
+            // Capacity covers every event, so this cannot fail
+            let _ = sender.try_send(Ok(event));
+        }
+        Self::from_receiver(receiver)
+    }
+
+    /// Starts reading an open `text/event-stream` response in the background.
+    pub(crate) fn spawn(response: Response) -> Self {
+        let (sender, receiver) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let reader = tokio::spawn(read_stream(response, sender));
+        Self { receiver, reader: Some(reader) }
+    }
+
+    /// Waits for the next event.
+    ///
+    /// Returns `None` once the stream has ended; subscribe again to resume.
+    pub async fn next(&mut self) -> Option<Result<Event, AgoraClientError>> {
+        self.receiver.recv().await
+    }
+
+    /// Returns an already received event without waiting.
+    pub fn try_next(&mut self) -> Option<Result<Event, AgoraClientError>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        if let Some(reader) = &self.reader {
+            reader.abort();
+        }
+    }
+}
+
+/// Forwards events from the response body until it ends or the subscription is dropped.
+async fn read_stream(
+    mut response: Response,
+    sender: mpsc::Sender<Result<Event, AgoraClientError>>,
+) {
+    let mut parser = SseParser::default();
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                for data in parser.feed(&chunk) {
+                    let event = serde_json::from_str::<Event>(&data).map_err(Into::into);
+                    if sender.send(event).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Ok(None) => {
+                debug!("Event stream closed by server");
+                return;
+            }
+            Err(e) => {
+                let _ = sender.send(Err(e.into())).await;
+                return;
+            }
+        }
+    }
+}
+
+/// Incremental parser for the `text/event-stream` format.
+///
+/// Only `event` and `data` fields are used; comments (keep-alives), `id`
+/// and `retry` are ignored.
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    name: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feeds a chunk of the body and returns the data of each completed event message.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut messages = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                let name = self.name.take();
+                let data = std::mem::take(&mut self.data);
+                if name.as_deref() == Some(EVENT_MESSAGE) && !data.is_empty() {
+                    messages.push(data.join("\n"));
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.name = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser_reads_event_messages() {
+        let mut parser = SseParser::default();
+        let messages = parser.feed(b"event: event\nid: 1\ndata: {\"id\":1}\n\n");
+        assert_eq!(messages, vec!["{\"id\":1}".to_string()]);
+    }
+
+    #[test]
+    fn test_parser_handles_split_chunks_and_crlf() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"event: ev").is_empty());
+        assert!(parser.feed(b"ent\r\ndata: a\r\n").is_empty());
+        let messages = parser.feed(b"data: b\r\n\r\n");
+        assert_eq!(messages, vec!["a\nb".to_string()]);
+    }
+
+    #[test]
+    fn test_parser_ignores_comments_and_other_messages() {
+        let mut parser = SseParser::default();
+        let messages =
+            parser.feed(b":\n\nevent: other\ndata: x\n\ndata: y\n\nevent: event\ndata: z\n\n");
+        assert_eq!(messages, vec!["z".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_from_events_ends_after_events() {
+        let event = Event {
+            id: 7,
+            event_type: "test.event".to_string(),
+            herald_id: "test-herald".to_string(),
+            payload: serde_json::json!({}),
+            priority: agora_common::event::EventPriority::Normal,
+            timestamp: time::OffsetDateTime::now_utc(),
+            status: agora_common::event::EventStatus::Delivered,
+            deliver_after: None,
+            expires_at: None,
+        };
+        let mut subscription = EventSubscription::from_events(vec![event]);
+
+        assert_eq!(subscription.next().await.unwrap().unwrap().id, 7);
+        assert!(subscription.next().await.is_none());
+    }
+}
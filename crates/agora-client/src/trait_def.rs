@@ -2,11 +2,14 @@
 //!
 //! This trait allows for mocking in tests and dependency injection.
 
-use agora_common::event::Event;
-use agora_common::herald::HeraldInfo;
+use agora_common::consumer::{ConsumerInfo, UpsertConsumerRequest};
+use agora_common::event::{BatchAckMode, BatchUpdateEventsResponse, CreateEventRequest, Event};
+use agora_common::herald::{
+    HeartbeatResponse, HeraldInfo, RegisterHeraldRequest, RegisterHeraldResponse,
+};
 use async_trait::async_trait;
 
-use crate::AgoraClientError;
+use crate::{AgoraClientError, EventSubscription};
 
 /// Trait for Agora client operations
 #[async_trait]
@@ -18,11 +21,34 @@ pub trait AgoraClientTrait: Send + Sync {
     /// This changes state: Pending → Delivered
     async fn fetch_events(&self, limit: Option<u32>) -> Result<Vec<Event>, AgoraClientError>;
 
+    /// Long-polls for events (POST /events/fetch with `wait_ms`)
+    /// Returns as soon as events are deliverable, or empty once `wait_ms` expires
+    async fn fetch_events_wait(
+        &self,
+        limit: Option<u32>,
+        wait_ms: u64,
+    ) -> Result<Vec<Event>, AgoraClientError>;
+
+    /// Subscribes to events pushed by the server (GET /events/stream)
+    /// Streamed events are Delivered and must still be acknowledged
+    async fn subscribe(&self, limit: Option<u32>) -> Result<EventSubscription, AgoraClientError>;
+
     /// Acknowledges a single event
     async fn ack_event(&self, event_id: u64) -> Result<Event, AgoraClientError>;
 
-    /// Batch acknowledges multiple events
-    async fn ack_events(&self, event_ids: Vec<u64>) -> Result<usize, AgoraClientError>;
+    /// Batch acknowledges multiple events, reporting acked, missing and failed IDs
+    async fn ack_events(
+        &self,
+        event_ids: Vec<u64>,
+        mode: BatchAckMode,
+    ) -> Result<BatchUpdateEventsResponse, AgoraClientError>;
+
+    /// Creates a consumer or replaces its filters (PUT /consumers/{name})
+    async fn register_consumer(
+        &self,
+        name: &str,
+        request: UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo, AgoraClientError>;
 
     /// Lists all heralds
     async fn list_heralds(&self) -> Result<Vec<HeraldInfo>, AgoraClientError>;
@@ -30,6 +56,22 @@ pub trait AgoraClientTrait: Send + Sync {
     /// Gets a specific herald by ID
     async fn get_herald(&self, id: &str) -> Result<HeraldInfo, AgoraClientError>;
 
+    /// Registers as a herald (POST /heralds)
+    /// The token Agora confirms or issues authenticates later herald requests
+    async fn register_herald(
+        &self,
+        request: RegisterHeraldRequest,
+    ) -> Result<RegisterHeraldResponse, AgoraClientError>;
+
+    /// Sends a herald heartbeat (POST /heralds/{id}/heartbeat)
+    async fn heartbeat(&self, id: &str) -> Result<HeartbeatResponse, AgoraClientError>;
+
+    /// Unregisters a herald (DELETE /heralds/{id})
+    async fn unregister(&self, id: &str) -> Result<(), AgoraClientError>;
+
+    /// Pushes an event as a herald (POST /events)
+    async fn push_event(&self, request: CreateEventRequest) -> Result<Event, AgoraClientError>;
+
     /// Gets the base URL this client is configured to use
     fn base_url(&self) -> &str;
 }
@@ -0,0 +1,49 @@
+//! Consumer types for Agora event hub.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Consumer used when a request does not name one.
+///
+/// It always exists and matches every event unless its filters are changed.
+pub const DEFAULT_CONSUMER: &str = "default";
+
+/// A named consumer with its own delivery and ack state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumerInfo {
+    /// Unique consumer name.
+    pub name: String,
+    /// Event type globs (e.g. "chat.*"); empty matches every type.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Herald ID globs; empty matches every herald.
+    #[serde(default)]
+    pub herald_ids: Vec<String>,
+    /// Creation timestamp.
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// Events waiting to be delivered to this consumer.
+    #[serde(default)]
+    pub pending: usize,
+    /// Events delivered to this consumer and awaiting ack.
+    #[serde(default)]
+    pub delivered: usize,
+}
+
+/// Request to create a consumer or replace its filters.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpsertConsumerRequest {
+    /// Event type globs; empty matches every type.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Herald ID globs; empty matches every herald.
+    #[serde(default)]
+    pub herald_ids: Vec<String>,
+}
+
+/// Consumer list response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumersListResponse {
+    /// List of consumers.
+    pub consumers: Vec<ConsumerInfo>,
+}
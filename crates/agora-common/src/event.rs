@@ -7,7 +7,10 @@ use time::OffsetDateTime;
 pub type EventId = u64;
 
 /// Event priority levels.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+///
+/// Variants are declared from lowest to highest, so the derived `Ord`
+/// ranks `Urgent` above `Low`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum EventPriority {
     Low,
@@ -66,10 +69,24 @@ pub struct Event {
     pub timestamp: OffsetDateTime,
     /// Event processing status.
     pub status: EventStatus,
+    /// Not delivered before this time.
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deliver_after: Option<OffsetDateTime>,
+    /// Not delivered (again) after this time.
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<OffsetDateTime>,
 }
 
 /// Request to create a new event.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CreateEventRequest {
     /// Event type (e.g., "timer.trigger", "chat.message").
     pub event_type: String,
@@ -84,13 +101,51 @@ pub struct CreateEventRequest {
     /// Event occurrence timestamp (provided by herald, defaults to now).
     #[serde(default = "OffsetDateTime::now_utc", with = "time::serde::rfc3339")]
     pub timestamp: OffsetDateTime,
+    /// Herald-chosen key identifying this event across re-pushes. A push that
+    /// repeats a recent key returns the original event instead of a new one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Hold the event back until this time (e.g. a reminder).
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deliver_after: Option<OffsetDateTime>,
+    /// Drop or dead-letter the event if it has not been acked by this time,
+    /// instead of delivering it (e.g. a stale notification).
+    #[serde(
+        default,
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expires_at: Option<OffsetDateTime>,
+    /// Herald-chosen key grouping related events. If Agora has a coalescing
+    /// rule for the event type, undelivered events sharing the key are merged
+    /// into one, whose payload becomes an array of the merged payloads.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coalesce_key: Option<String>,
 }
 
 /// Request to update event status.
 #[derive(Debug, Clone, Deserialize)]
 pub struct UpdateEventRequest {
-    /// New event status.
+    /// New event status: acked, or pending to requeue the event.
     pub status: EventStatus,
+    /// Consumer whose queue holds the event (defaults to the default consumer).
+    #[serde(default)]
+    pub consumer: Option<String>,
+}
+
+/// How a batch ack treats IDs that cannot be acked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchAckMode {
+    /// Ack every event that can be acked and report the rest.
+    #[default]
+    BestEffort,
+    /// Ack every event or none: a missing or failed ID rolls back the batch.
+    AllOrNothing,
 }
 
 /// Request to batch update event status.
@@ -100,13 +155,25 @@ pub struct BatchUpdateEventsRequest {
     pub event_ids: Vec<EventId>,
     /// New status for all events.
     pub status: EventStatus,
+    /// Consumer acknowledging the events (defaults to the default consumer).
+    #[serde(default)]
+    pub consumer: Option<String>,
+    /// Whether the batch is acked best-effort or all-or-nothing.
+    #[serde(default)]
+    pub mode: BatchAckMode,
 }
 
 /// Response for batch update operation.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BatchUpdateEventsResponse {
     /// IDs of successfully acknowledged events.
     pub acked_ids: Vec<EventId>,
+    /// IDs not queued for the consumer (unknown or already acked).
+    #[serde(default)]
+    pub missing_ids: Vec<EventId>,
+    /// IDs that could not be acked because of a storage error.
+    #[serde(default)]
+    pub failed_ids: Vec<EventId>,
 }
 
 /// Events list response.
@@ -117,3 +184,126 @@ pub struct EventsListResponse {
     /// Total count.
     pub total: usize,
 }
+
+/// An event in a consumer's queue, with its delivery state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedEvent {
+    /// The event, with status Pending or Delivered.
+    pub event: Event,
+    /// Consumer whose queue holds the event.
+    pub consumer: String,
+    /// When the event was last delivered, if it awaits an ack.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub delivered_at: Option<OffsetDateTime>,
+    /// Number of times the event was delivered so far.
+    pub delivery_count: u32,
+    /// When the event is next delivered: its retry time if delivered, or its
+    /// `deliver_after` if held back.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub next_delivery_at: Option<OffsetDateTime>,
+}
+
+/// Filter for listing a consumer's queued events.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueuedEventsQuery {
+    /// Consumer whose queue to list (defaults to the default consumer).
+    pub consumer: Option<String>,
+    /// Only include events with this status (pending or delivered).
+    pub status: Option<EventStatus>,
+    /// Only include events from this herald.
+    pub herald_id: Option<String>,
+    /// Only include events of this type.
+    pub event_type: Option<String>,
+    /// Maximum number of events to return.
+    pub limit: Option<u32>,
+}
+
+/// Queued events response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedEventsResponse {
+    /// Queued events, in delivery order.
+    pub events: Vec<QueuedEvent>,
+    /// Total count matching the filter (may exceed the returned list).
+    pub total: usize,
+}
+
+/// An event that exceeded the retry limit and was moved out of the delivery queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The original event.
+    pub event: Event,
+    /// Consumer that failed to acknowledge the event.
+    pub consumer: String,
+    /// Number of times the event was delivered without being acknowledged.
+    pub delivery_count: u32,
+    /// Why the event was dead-lettered.
+    pub reason: String,
+    /// When the event was moved to the dead-letter table.
+    #[serde(with = "time::serde::rfc3339")]
+    pub dead_lettered_at: OffsetDateTime,
+}
+
+/// Dead-letter list response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLettersListResponse {
+    /// List of dead-lettered events.
+    pub dead_letters: Vec<DeadLetter>,
+    /// Total count matching the filter (may exceed the returned list).
+    pub total: usize,
+}
+
+/// Response for purging dead-lettered events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeDeadLettersResponse {
+    /// Number of dead-lettered events removed.
+    pub purged: u64,
+}
+
+/// An acked event kept in the archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedEvent {
+    /// The original event, with status Acked.
+    pub event: Event,
+    /// Consumer that acknowledged the event.
+    pub consumer: String,
+    /// When the event was last delivered, if it was delivered before the ack.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub delivered_at: Option<OffsetDateTime>,
+    /// When the event was acknowledged.
+    #[serde(with = "time::serde::rfc3339")]
+    pub acked_at: OffsetDateTime,
+    /// Number of redeliveries before the ack.
+    pub retry_count: u32,
+}
+
+/// Filter and pagination for the event history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventHistoryQuery {
+    /// Only include events from this herald.
+    pub herald_id: Option<String>,
+    /// Only include events of this type.
+    pub event_type: Option<String>,
+    /// Only include events with this priority.
+    pub priority: Option<EventPriority>,
+    /// Only include events acked by this consumer.
+    pub consumer: Option<String>,
+    /// Only include events that occurred at or after this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub since: Option<OffsetDateTime>,
+    /// Only include events that occurred before this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    /// Maximum number of events to return.
+    pub limit: Option<u32>,
+    /// Number of matching events to skip.
+    pub offset: Option<u32>,
+}
+
+/// Event history response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventHistoryResponse {
+    /// Archived events, most recent first.
+    pub events: Vec<ArchivedEvent>,
+    /// Total count matching the filter (may exceed the returned list).
+    pub total: usize,
+}
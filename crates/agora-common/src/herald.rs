@@ -1,6 +1,7 @@
 //! Herald types for Agora event hub.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use time::OffsetDateTime;
 
 /// Herald status based on heartbeat health.
@@ -30,20 +31,43 @@ pub struct HeraldInfo {
     /// Last heartbeat timestamp.
     #[serde(with = "time::serde::rfc3339")]
     pub last_heartbeat: OffsetDateTime,
+    /// Number of this herald's events currently in the dead-letter table.
+    #[serde(default)]
+    pub dead_letters: u64,
+    /// The herald's limits and how close it is to them, if it has any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<HeraldLimitStatus>,
 }
 
 /// Request to register a new herald.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RegisterHeraldRequest {
     /// Unique herald identifier.
     pub id: String,
     /// Herald description.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// JSON Schemas for the payloads of the event types the herald pushes,
+    /// keyed by event type. They replace the herald's earlier schemas.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub schemas: HashMap<String, serde_json::Value>,
+}
+
+/// Response for herald registration.
+///
+/// The token must be sent as `Authorization: Bearer <token>` when the herald
+/// re-registers, sends heartbeats, unregisters or pushes events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterHeraldResponse {
+    /// The registered herald.
+    #[serde(flatten)]
+    pub herald: HeraldInfo,
+    /// The herald's secret token.
+    pub token: String,
 }
 
 /// Response for heartbeat operation.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HeartbeatResponse {
     /// Current herald status.
     pub status: HeraldStatus,
@@ -52,6 +76,59 @@ pub struct HeartbeatResponse {
     pub last_heartbeat: OffsetDateTime,
 }
 
+/// What happens to a push that would exceed a herald's `max_pending`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitPolicy {
+    /// Reject the push with 429.
+    #[default]
+    Reject,
+    /// Drop the herald's lowest-priority, oldest undelivered event to make
+    /// room, if one is not above the new event's priority; otherwise reject.
+    DropOldest,
+}
+
+/// Limits on what one herald may push. Unset limits do not apply.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct HeraldLimits {
+    /// Most events accepted per minute (sliding window).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events_per_minute: Option<u32>,
+    /// Most of the herald's events queued (unacked) at once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pending: Option<u32>,
+    /// Largest payload accepted, in bytes of JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_payload_bytes: Option<u64>,
+    /// What happens to a push that would exceed `max_pending`.
+    #[serde(default)]
+    pub on_exceed: LimitPolicy,
+}
+
+impl HeraldLimits {
+    /// Whether any limit is set.
+    pub fn is_limited(&self) -> bool {
+        self.events_per_minute.is_some()
+            || self.max_pending.is_some()
+            || self.max_payload_bytes.is_some()
+    }
+}
+
+/// A herald's limits with its current standing against them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeraldLimitStatus {
+    #[serde(flatten)]
+    pub limits: HeraldLimits,
+    /// Events accepted in the last minute.
+    pub recent_events: u32,
+    /// The herald's events currently queued.
+    pub pending_events: u64,
+    /// Pushes rejected for exceeding a limit since Agora started.
+    pub rejected_events: u64,
+    /// Events dropped to make room for newer ones since Agora started.
+    pub dropped_events: u64,
+}
+
 /// Herald list response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HeraldsListResponse {
@@ -1,2 +1,4 @@
+pub mod consumer;
 pub mod event;
 pub mod herald;
+pub mod schema;
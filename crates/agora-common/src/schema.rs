@@ -0,0 +1,38 @@
+//! Event payload schema types for Agora event hub.
+
+use serde::{Deserialize, Serialize};
+
+/// A JSON Schema that payloads of one event type must conform to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventSchema {
+    /// Event type the schema applies to, e.g. `chat.message`.
+    pub event_type: String,
+    /// The JSON Schema.
+    pub schema: serde_json::Value,
+    /// Herald that registered the schema; absent for schemas from Agora's config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub herald_id: Option<String>,
+}
+
+/// Response for listing schemas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSchemasResponse {
+    pub schemas: Vec<EventSchema>,
+}
+
+/// One way a payload fails its event type's schema.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadError {
+    /// JSON Pointer to the offending part of the payload ("" for the payload itself).
+    pub path: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+/// Response body for a push rejected with 422 because its payload does not
+/// conform to the event type's schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvalidPayloadResponse {
+    pub event_type: String,
+    pub errors: Vec<PayloadError>,
+}
@@ -0,0 +1,142 @@
+//! Event builders.
+
+use agora_client::{CreateEventRequest, EventPriority};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// A payload type with the event type it is pushed as.
+pub trait EventPayload: Serialize {
+    /// Event type, e.g. `chat.message`.
+    const EVENT_TYPE: &'static str;
+}
+
+/// Builds an event for a herald to push.
+///
+/// Events default to normal priority, timestamped when built.
+#[derive(Debug, Clone)]
+pub struct EventBuilder {
+    event_type: String,
+    payload: serde_json::Value,
+    priority: EventPriority,
+    timestamp: Option<OffsetDateTime>,
+    idempotency_key: Option<String>,
+    deliver_after: Option<OffsetDateTime>,
+    expires_at: Option<OffsetDateTime>,
+    coalesce_key: Option<String>,
+}
+
+impl EventBuilder {
+    /// Creates a builder for an event with an untyped payload.
+    pub fn new(event_type: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            event_type: event_type.into(),
+            payload,
+            priority: EventPriority::Normal,
+            timestamp: None,
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        }
+    }
+
+    /// Creates a builder for a typed payload, using its event type.
+    pub fn typed<P: EventPayload>(payload: &P) -> serde_json::Result<Self> {
+        Ok(Self::new(P::EVENT_TYPE, serde_json::to_value(payload)?))
+    }
+
+    pub fn priority(mut self, priority: EventPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// When the event happened at the source.
+    pub fn timestamp(mut self, timestamp: OffsetDateTime) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Key Agora deduplicates repeated pushes of the same event by.
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Holds the event back until this time.
+    pub fn deliver_after(mut self, at: OffsetDateTime) -> Self {
+        self.deliver_after = Some(at);
+        self
+    }
+
+    /// Drops the event if it has not been acked by this time.
+    pub fn expires_at(mut self, at: OffsetDateTime) -> Self {
+        self.expires_at = Some(at);
+        self
+    }
+
+    /// Key Agora merges undelivered events by, if configured to.
+    pub fn coalesce_key(mut self, key: impl Into<String>) -> Self {
+        self.coalesce_key = Some(key.into());
+        self
+    }
+
+    /// Builds the request to push the event as `herald_id`.
+    pub fn build(self, herald_id: &str) -> CreateEventRequest {
+        CreateEventRequest {
+            event_type: self.event_type,
+            herald_id: herald_id.to_string(),
+            priority: self.priority,
+            payload: self.payload,
+            timestamp: self.timestamp.unwrap_or_else(OffsetDateTime::now_utc),
+            idempotency_key: self.idempotency_key,
+            deliver_after: self.deliver_after,
+            expires_at: self.expires_at,
+            coalesce_key: self.coalesce_key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    #[derive(Serialize)]
+    struct Ping {
+        seq: u32,
+    }
+
+    impl EventPayload for Ping {
+        const EVENT_TYPE: &'static str = "test.ping";
+    }
+
+    #[test]
+    fn test_typed_builder() {
+        let at = datetime!(2025-01-01 12:00 UTC);
+        let request = EventBuilder::typed(&Ping { seq: 7 })
+            .unwrap()
+            .priority(EventPriority::High)
+            .timestamp(at)
+            .idempotency_key("ping-7")
+            .build("test-herald");
+
+        assert_eq!(request.event_type, "test.ping");
+        assert_eq!(request.herald_id, "test-herald");
+        assert_eq!(request.payload, serde_json::json!({ "seq": 7 }));
+        assert_eq!(request.priority, EventPriority::High);
+        assert_eq!(request.timestamp, at);
+        assert_eq!(request.idempotency_key.as_deref(), Some("ping-7"));
+        assert!(request.coalesce_key.is_none());
+    }
+
+    #[test]
+    fn test_request_omits_unset_fields() {
+        let request = EventBuilder::new("test.ping", serde_json::json!({})).build("test-herald");
+        let body = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(body["priority"], "normal");
+        assert!(body["timestamp"].is_string());
+        assert!(body.get("idempotency_key").is_none());
+        assert!(body.get("deliver_after").is_none());
+    }
+}
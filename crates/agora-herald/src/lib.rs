@@ -0,0 +1,51 @@
+//! Runtime for Agora heralds.
+//!
+//! A herald implements [`Herald`] to turn items from its source into events;
+//! [`HeraldRunner`] does the rest: registering with Agora, heartbeats,
+//! re-registering when Agora forgets the herald, retrying pushes and shutting
+//! down cleanly.
+
+mod builder;
+mod runner;
+
+pub use builder::{EventBuilder, EventPayload};
+pub use epha_shutdown::shutdown_signal;
+pub use runner::HeraldRunner;
+
+// Re-export commonly used types
+pub use agora_client::{AgoraClient, AgoraClientTrait, EventPriority};
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+/// A source of events for Agora.
+#[async_trait]
+pub trait Herald: Send {
+    /// Herald ID to register and push events as.
+    const ID: &'static str;
+
+    /// Description shown in Agora's herald list.
+    const DESCRIPTION: &'static str;
+
+    /// An item read from the source, producing one event.
+    type Item: Send;
+
+    /// Reads new items from the source.
+    async fn poll(&mut self) -> anyhow::Result<Vec<Self::Item>>;
+
+    /// Builds the event for an item.
+    fn produce(&self, item: &Self::Item) -> anyhow::Result<EventBuilder>;
+
+    /// JSON Schemas of the herald's payloads, keyed by event type. They are
+    /// registered with Agora, which then rejects pushes that do not conform.
+    fn schemas(&self) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+
+    /// Called with the items whose events Agora accepted, e.g. to ack them at
+    /// the source. Items whose events failed are not passed back.
+    async fn pushed(&mut self, _items: Vec<Self::Item>) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
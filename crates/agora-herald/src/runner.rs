@@ -0,0 +1,417 @@
+//! Herald runner.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use agora_client::{
+    AgoraClientError, AgoraClientTrait, CreateEventRequest, Event, RegisterHeraldRequest,
+    StatusCode,
+};
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::Herald;
+
+/// Longest wait between registration attempts.
+const MAX_REGISTER_DELAY: Duration = Duration::from_secs(30);
+
+/// Runs a [`Herald`] against Agora.
+///
+/// The runner registers the herald (retrying with backoff until Agora is
+/// reachable), then polls it and pushes its events, heartbeating in between.
+/// If a heartbeat finds Agora no longer knows the herald, e.g. after Agora
+/// lost its state, the herald registers again. Pushes that fail on a network
+/// or server error are retried with backoff before the event is given up on.
+///
+/// If Agora rejects the herald's token at registration, retrying cannot help,
+/// so the runner fails instead.
+pub struct HeraldRunner<H: Herald> {
+    herald: H,
+    client: Arc<dyn AgoraClientTrait>,
+    poll_interval: Duration,
+    heartbeat_interval: Duration,
+    push_retries: u32,
+    retry_delay: Duration,
+}
+
+impl<H: Herald> HeraldRunner<H> {
+    /// Creates a runner polling every second and heartbeating every 30 seconds.
+    pub fn new(herald: H, client: Arc<dyn AgoraClientTrait>) -> Self {
+        Self {
+            herald,
+            client,
+            poll_interval: Duration::from_secs(1),
+            heartbeat_interval: Duration::from_secs(30),
+            push_retries: 3,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn with_heartbeat_interval(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// Sets how often a failed push is retried, and the delay before the
+    /// first retry. The delay doubles with each retry.
+    pub fn with_push_retries(mut self, retries: u32, delay: Duration) -> Self {
+        self.push_retries = retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Runs the herald until `shutdown` completes.
+    ///
+    /// A poll in progress is finished, so every event Agora accepted is
+    /// passed to [`Herald::pushed`], then the herald unregisters.
+    pub async fn run(mut self, shutdown: impl Future<Output = ()>) -> anyhow::Result<()> {
+        tokio::pin!(shutdown);
+
+        tokio::select! {
+            _ = &mut shutdown => {
+                info!("Shutting down herald {} before it registered", H::ID);
+                return Ok(());
+            }
+            registered = self.register_with_backoff() => registered?,
+        }
+
+        let mut poll_tick = interval(self.poll_interval);
+        let mut heartbeat_tick = interval(self.heartbeat_interval);
+        // Registering counts as the first heartbeat
+        heartbeat_tick.reset();
+
+        info!("Running herald {}", H::ID);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = poll_tick.tick() => {
+                    if let Err(e) = self.poll_and_push().await {
+                        warn!("Poll error: {}", e);
+                    }
+                }
+                _ = heartbeat_tick.tick() => self.heartbeat().await,
+            }
+        }
+
+        info!("Shutting down herald {}", H::ID);
+        if let Err(e) = self.client.unregister(H::ID).await {
+            warn!("Failed to unregister herald {}: {}", H::ID, e);
+        }
+
+        Ok(())
+    }
+
+    async fn register(&self) -> Result<(), AgoraClientError> {
+        let request = RegisterHeraldRequest {
+            id: H::ID.to_string(),
+            description: Some(H::DESCRIPTION.to_string()),
+            schemas: self.herald.schemas(),
+        };
+        self.client.register_herald(request).await?;
+        info!("Registered herald {} with Agora", H::ID);
+        Ok(())
+    }
+
+    async fn register_with_backoff(&self) -> anyhow::Result<()> {
+        let mut attempt = 0u32;
+        let mut delay = Duration::from_secs(1);
+        while let Err(e) = self.register().await {
+            if e.status() == Some(StatusCode::UNAUTHORIZED) {
+                anyhow::bail!(
+                    "Agora rejected the token of herald {}: configure the token Agora expects, \
+                     or unregister the herald with Agora's admin token so it can register afresh",
+                    H::ID
+                );
+            }
+            attempt += 1;
+            warn!(
+                "Agora registration failed (attempt {attempt}): {e}. Retrying in {}s...",
+                delay.as_secs()
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_REGISTER_DELAY);
+        }
+        Ok(())
+    }
+
+    async fn heartbeat(&self) {
+        match self.client.heartbeat(H::ID).await {
+            Ok(_) => debug!("Heartbeat sent successfully"),
+            Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => {
+                warn!("Agora does not know herald {}, registering again", H::ID);
+                if let Err(e) = self.register().await {
+                    warn!("Failed to register herald {} again: {}", H::ID, e);
+                }
+            }
+            Err(e) => warn!("Failed to send heartbeat: {}", e),
+        }
+    }
+
+    async fn poll_and_push(&mut self) -> anyhow::Result<()> {
+        let items = self.herald.poll().await?;
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        debug!("Pushing {} events", items.len());
+
+        let mut pushed = Vec::new();
+        for item in items {
+            let request = match self.herald.produce(&item) {
+                Ok(builder) => builder.build(H::ID),
+                Err(e) => {
+                    error!("Failed to build event: {}", e);
+                    continue;
+                }
+            };
+            let event_type = request.event_type.clone();
+            match self.push(request).await {
+                Ok(event) => {
+                    debug!("Pushed {} event {}", event_type, event.id);
+                    pushed.push(item);
+                }
+                Err(e) => error!("Failed to push {} event: {}", event_type, e),
+            }
+        }
+
+        if !pushed.is_empty() {
+            self.herald.pushed(pushed).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Pushes an event, retrying failures that may be temporary.
+    async fn push(&self, request: CreateEventRequest) -> Result<Event, AgoraClientError> {
+        let mut retries = 0;
+        let mut delay = self.retry_delay;
+        loop {
+            let err = match self.client.push_event(request.clone()).await {
+                Ok(event) => return Ok(event),
+                Err(e) => e,
+            };
+            if retries >= self.push_retries || !is_retryable(&err) {
+                return Err(err);
+            }
+            retries += 1;
+            warn!(
+                "Push failed (retry {retries}/{}): {err}. Retrying in {}ms...",
+                self.push_retries,
+                delay.as_millis()
+            );
+
+            // Agora rejects the token of a herald it no longer knows
+            if err.status() == Some(StatusCode::UNAUTHORIZED)
+                && let Err(e) = self.register().await
+            {
+                warn!("Failed to register herald {} again: {}", H::ID, e);
+            }
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+}
+
+/// Whether a failed request may succeed if repeated.
+fn is_retryable(err: &AgoraClientError) -> bool {
+    match err.status() {
+        Some(status) => {
+            status.is_server_error()
+                || matches!(
+                    status,
+                    StatusCode::UNAUTHORIZED
+                        | StatusCode::REQUEST_TIMEOUT
+                        | StatusCode::TOO_MANY_REQUESTS
+                )
+        }
+        None => matches!(err, AgoraClientError::NetworkError(_)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventBuilder;
+    use agora_client::mock::{MockAgoraClient, MockCall};
+    use agora_client::{
+        EventPriority, EventStatus, HeraldInfo, HeraldStatus, RegisterHeraldResponse,
+    };
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use time::OffsetDateTime;
+    use tokio::sync::oneshot;
+
+    /// Herald yielding fixed items once, then signalling it has run dry.
+    struct TestHerald {
+        items: Vec<u64>,
+        pushed: Arc<Mutex<Vec<u64>>>,
+        dry: Option<oneshot::Sender<()>>,
+    }
+
+    #[async_trait]
+    impl Herald for TestHerald {
+        const ID: &'static str = "test-herald";
+        const DESCRIPTION: &'static str = "Test herald";
+
+        type Item = u64;
+
+        async fn poll(&mut self) -> anyhow::Result<Vec<u64>> {
+            let items = std::mem::take(&mut self.items);
+            if items.is_empty()
+                && let Some(dry) = self.dry.take()
+            {
+                let _ = dry.send(());
+            }
+            Ok(items)
+        }
+
+        fn produce(&self, item: &u64) -> anyhow::Result<EventBuilder> {
+            Ok(EventBuilder::new(
+                "test.ping",
+                serde_json::json!({ "seq": item }),
+            ))
+        }
+
+        fn schemas(&self) -> HashMap<String, serde_json::Value> {
+            HashMap::from([(
+                "test.ping".to_string(),
+                serde_json::json!({ "type": "object" }),
+            )])
+        }
+
+        async fn pushed(&mut self, items: Vec<u64>) -> anyhow::Result<()> {
+            self.pushed.lock().unwrap().extend(items);
+            Ok(())
+        }
+    }
+
+    fn registered() -> RegisterHeraldResponse {
+        let now = OffsetDateTime::now_utc();
+        RegisterHeraldResponse {
+            herald: HeraldInfo {
+                id: TestHerald::ID.to_string(),
+                description: Some(TestHerald::DESCRIPTION.to_string()),
+                status: HeraldStatus::Active,
+                registered_at: now,
+                last_heartbeat: now,
+                dead_letters: 0,
+                limits: None,
+            },
+            token: "secret".to_string(),
+        }
+    }
+
+    fn event(id: u64) -> Event {
+        Event {
+            id,
+            event_type: "test.ping".to_string(),
+            herald_id: TestHerald::ID.to_string(),
+            payload: serde_json::json!({ "seq": id }),
+            priority: EventPriority::Normal,
+            timestamp: OffsetDateTime::now_utc(),
+            status: EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
+        }
+    }
+
+    fn pushes(mock: &MockAgoraClient) -> usize {
+        mock.call_count(|c| matches!(c, MockCall::PushEvent { .. }))
+    }
+
+    #[tokio::test]
+    async fn test_run_pushes_events_and_reports_pushed_items() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_registered(registered())
+            .push_event_response(event(1))
+            .push_http_error(StatusCode::BAD_REQUEST, "bad payload")
+            .push_http_error(StatusCode::SERVICE_UNAVAILABLE, "busy")
+            .push_event_response(event(3));
+        let mock = Arc::new(mock);
+
+        let pushed = Arc::new(Mutex::new(Vec::new()));
+        let (dry_tx, dry_rx) = oneshot::channel();
+        let herald = TestHerald { items: vec![1, 2, 3], pushed: pushed.clone(), dry: Some(dry_tx) };
+
+        HeraldRunner::new(herald, mock.clone())
+            .with_poll_interval(Duration::from_millis(1))
+            .with_push_retries(1, Duration::from_millis(1))
+            .run(async {
+                let _ = dry_rx.await;
+            })
+            .await
+            .unwrap();
+
+        // Item 2 is rejected outright; item 3 succeeds on retry
+        assert_eq!(*pushed.lock().unwrap(), vec![1, 3]);
+        assert_eq!(pushes(&mock), 4);
+
+        let calls = mock.get_calls();
+        assert!(matches!(&calls[0], MockCall::RegisterHerald { request }
+                if request.id == "test-herald" && request.schemas.contains_key("test.ping")));
+        assert!(
+            matches!(&calls[1], MockCall::PushEvent { request } if request.herald_id == "test-herald")
+        );
+        assert_eq!(
+            calls.last(),
+            Some(&MockCall::Unregister { id: "test-herald".to_string() })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_registers_again_when_herald_unknown() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_http_error(StatusCode::NOT_FOUND, "")
+            .push_registered(registered());
+        let mock = Arc::new(mock);
+
+        let herald = TestHerald { items: vec![], pushed: Arc::default(), dry: None };
+        let runner = HeraldRunner::new(herald, mock.clone());
+        runner.heartbeat().await;
+
+        let calls = mock.get_calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0],
+            MockCall::Heartbeat { id: "test-herald".to_string() }
+        );
+        assert!(matches!(&calls[1], MockCall::RegisterHerald { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_run_fails_when_registration_unauthorized() {
+        let mut mock = MockAgoraClient::new();
+        mock.push_http_error(StatusCode::UNAUTHORIZED, "");
+        let mock = Arc::new(mock);
+
+        let herald = TestHerald { items: vec![], pushed: Arc::default(), dry: None };
+        let result = HeraldRunner::new(herald, mock.clone())
+            .run(std::future::pending())
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            mock.call_count(|c| matches!(c, MockCall::RegisterHerald { .. })),
+            1
+        );
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        let http = |status| AgoraClientError::HttpError(status, String::new());
+        assert!(is_retryable(&http(StatusCode::BAD_GATEWAY)));
+        assert!(is_retryable(&http(StatusCode::TOO_MANY_REQUESTS)));
+        assert!(!is_retryable(&http(StatusCode::BAD_REQUEST)));
+        assert!(!is_retryable(&AgoraClientError::ApiError(
+            "bad".to_string()
+        )));
+    }
+}
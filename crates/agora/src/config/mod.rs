@@ -1,3 +1,5 @@
 mod schema;
 
-pub use schema::{Config, RetryConfig};
+pub use schema::{
+    CoalesceRule, Config, ExpirePolicy, HeraldStatusEventsConfig, RetryConfig, StorageBackend,
+};
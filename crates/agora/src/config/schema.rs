@@ -1,6 +1,8 @@
 //! Configuration schema for Agora.
 
+use agora_common::herald::HeraldLimits;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Agora server configuration.
@@ -8,15 +10,137 @@ use std::path::Path;
 pub struct Config {
     /// Server listen port.
     pub port: u16,
-    /// Path to SQLite database file.
+    /// Where events, consumers and heralds are stored, default: sqlite
+    #[serde(default)]
+    pub storage: StorageBackend,
+    /// Path to SQLite database file (unused with in-memory storage).
+    #[serde(default)]
     pub database_path: String,
     /// Interval between heartbeat timeout checks in milliseconds
     pub heartbeat_check_interval_ms: u64,
     /// Milliseconds without heartbeat before marking herald as Disconnected
     pub timeout_ms: i64,
+    /// How long shutdown waits for in-flight requests to finish (ms), default: 30000 (30s)
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout_ms: u64,
     /// Retry configuration for event delivery.
     #[serde(default)]
     pub retry: RetryConfig,
+    /// How long idempotency keys are remembered (ms), default: 86400000 (24h)
+    #[serde(default = "default_idempotency_window")]
+    pub idempotency_window_ms: u64,
+    /// What happens to events that pass their `expires_at` unacked.
+    #[serde(default)]
+    pub on_expire: ExpirePolicy,
+    /// Coalescing rules keyed by event type. Events of other types are never
+    /// coalesced, even with a `coalesce_key`.
+    #[serde(default)]
+    pub coalesce: HashMap<String, CoalesceRule>,
+    /// Archive of acked events, for `GET /events/history`.
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    /// Agora's own events on herald disconnects and reconnects.
+    #[serde(default)]
+    pub herald_status_events: HeraldStatusEventsConfig,
+    /// Per-herald settings, keyed by herald ID.
+    #[serde(default)]
+    pub heralds: HashMap<String, HeraldConfig>,
+    /// Bearer token for admin requests, e.g. unregistering a herald that lost
+    /// its issued token. Without one, no admin requests are accepted.
+    #[serde(default)]
+    pub admin_token: Option<String>,
+    /// File holding `admin_token`, read at startup, so the secret can be kept
+    /// out of the config.
+    #[serde(default)]
+    pub admin_token_file: Option<String>,
+    /// Limits for heralds without limits of their own, default: none
+    #[serde(default)]
+    pub herald_limits: HeraldLimits,
+    /// JSON Schemas for event payloads, keyed by event type. Heralds cannot
+    /// replace these with schemas of their own.
+    #[serde(default)]
+    pub schemas: HashMap<String, serde_json::Value>,
+}
+
+/// Settings for a known herald.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeraldConfig {
+    /// Preconfigured secret token. Without one, a token is issued when the
+    /// herald first registers.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// File holding `token`, read at startup, so the secret can be kept out
+    /// of the config.
+    #[serde(default)]
+    pub token_file: Option<String>,
+    /// Push `system.herald.*` events when this herald disconnects or
+    /// reconnects, default: true
+    #[serde(default = "default_true")]
+    pub status_events: bool,
+    /// Limits on this herald's pushes, replacing `herald_limits`.
+    #[serde(default)]
+    pub limits: Option<HeraldLimits>,
+}
+
+impl Default for HeraldConfig {
+    fn default() -> Self {
+        Self { token: None, token_file: None, status_events: true, limits: None }
+    }
+}
+
+/// `system.herald.disconnected` / `system.herald.reconnected` events.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeraldStatusEventsConfig {
+    /// Push the events at all, default: true
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// A herald's disconnect is not reported again within this long (ms) of
+    /// its last reported one, so a flapping herald does not flood the
+    /// queue, default: 300000 (5min)
+    #[serde(default = "default_flap_suppression")]
+    pub flap_suppression_ms: u64,
+}
+
+/// Storage backend for Agora's state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    /// SQLite database at `database_path`.
+    #[default]
+    Sqlite,
+    /// Process memory only: everything is lost on restart. For tests and
+    /// ephemeral deployments.
+    Memory,
+}
+
+/// Handling of events that expire before they are acked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpirePolicy {
+    /// Delete the event silently.
+    #[default]
+    Drop,
+    /// Move the event to the dead-letter table.
+    DeadLetter,
+}
+
+/// How undelivered events of one type that share a `coalesce_key` are merged.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CoalesceRule {
+    /// Most events merged into one; later ones start a new event, default: 100
+    #[serde(default = "default_coalesce_max_events")]
+    pub max_events: usize,
+}
+
+/// Archive configuration for acked events.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArchiveConfig {
+    /// Copy acked events to the archive, default: false
+    #[serde(default)]
+    pub enabled: bool,
+    /// How long archived events are kept (ms), default: 604800000 (7 days)
+    #[serde(default = "default_archive_retention")]
+    pub retention_ms: u64,
 }
 
 /// Retry configuration for event delivery.
@@ -31,6 +155,33 @@ pub struct RetryConfig {
     /// Max retry interval (ms), default: 300000 (5min)
     #[serde(default = "default_max_interval")]
     pub max_interval_ms: u64,
+    /// Max redeliveries before an event is dead-lettered, default: None (retry forever)
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+}
+
+fn default_shutdown_timeout() -> u64 {
+    30_000
+}
+
+fn default_idempotency_window() -> u64 {
+    86_400_000
+}
+
+fn default_coalesce_max_events() -> usize {
+    100
+}
+
+fn default_archive_retention() -> u64 {
+    604_800_000
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_flap_suppression() -> u64 {
+    300_000
 }
 
 fn default_base_interval() -> u64 {
@@ -43,12 +194,25 @@ fn default_max_interval() -> u64 {
     300000
 }
 
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self { enabled: false, retention_ms: default_archive_retention() }
+    }
+}
+
+impl Default for HeraldStatusEventsConfig {
+    fn default() -> Self {
+        Self { enabled: true, flap_suppression_ms: default_flap_suppression() }
+    }
+}
+
 impl Default for RetryConfig {
     fn default() -> Self {
         Self {
             base_interval_ms: default_base_interval(),
             multiplier: default_multiplier(),
             max_interval_ms: default_max_interval(),
+            max_retries: None,
         }
     }
 }
@@ -62,13 +226,30 @@ impl Config {
     pub fn load(path: &Path) -> Self {
         let content = std::fs::read_to_string(path)
             .unwrap_or_else(|e| panic!("Failed to read config '{}': {}", path.display(), e));
-        let config: Self = serde_json::from_str(&content)
+        let mut config: Self = serde_json::from_str(&content)
             .unwrap_or_else(|e| panic!("Failed to parse config '{}': {}", path.display(), e));
 
+        if let Some(file) = &config.admin_token_file {
+            assert!(
+                config.admin_token.is_none(),
+                "admin_token and admin_token_file are mutually exclusive"
+            );
+            config.admin_token = Some(read_secret("admin_token_file", file));
+        }
+        for (id, herald) in &mut config.heralds {
+            if let Some(file) = &herald.token_file {
+                assert!(
+                    herald.token.is_none(),
+                    "heralds.{id}.token and heralds.{id}.token_file are mutually exclusive"
+                );
+                herald.token = Some(read_secret(&format!("heralds.{id}.token_file"), file));
+            }
+        }
+
         // Validate required fields
         assert!(config.port != 0, "port cannot be 0");
         assert!(
-            !config.database_path.is_empty(),
+            config.storage == StorageBackend::Memory || !config.database_path.is_empty(),
             "database_path cannot be empty"
         );
         assert!(
@@ -76,6 +257,26 @@ impl Config {
             "heartbeat_check_interval_ms must be greater than 0"
         );
         assert!(config.timeout_ms > 0, "timeout_ms must be greater than 0");
+        for (event_type, rule) in &config.coalesce {
+            assert!(
+                rule.max_events > 0,
+                "coalesce.{event_type}.max_events must be greater than 0"
+            );
+        }
+        assert!(
+            config.admin_token.as_ref().is_none_or(|t| !t.is_empty()),
+            "admin_token cannot be empty"
+        );
+        assert_limits_valid(&config.herald_limits, "herald_limits");
+        for (id, herald) in &config.heralds {
+            assert!(
+                herald.token.as_ref().is_none_or(|t| !t.is_empty()),
+                "heralds.{id}.token cannot be empty"
+            );
+            if let Some(limits) = &herald.limits {
+                assert_limits_valid(limits, &format!("heralds.{id}.limits"));
+            }
+        }
 
         config
     }
@@ -85,3 +286,26 @@ impl Config {
         format!("[::]:{}", self.port)
     }
 }
+
+/// Reads a secret from a file, without surrounding whitespace.
+fn read_secret(field: &str, path: &str) -> String {
+    std::fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read {field} '{path}': {e}"))
+        .trim()
+        .to_string()
+}
+
+fn assert_limits_valid(limits: &HeraldLimits, path: &str) {
+    assert!(
+        limits.events_per_minute != Some(0),
+        "{path}.events_per_minute must be greater than 0"
+    );
+    assert!(
+        limits.max_pending != Some(0),
+        "{path}.max_pending must be greater than 0"
+    );
+    assert!(
+        limits.max_payload_bytes != Some(0),
+        "{path}.max_payload_bytes must be greater than 0"
+    );
+}
@@ -0,0 +1,5 @@
+//! Consumer management.
+
+mod types;
+
+pub use types::*;
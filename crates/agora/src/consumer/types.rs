@@ -0,0 +1,112 @@
+pub use agora_common::consumer::*;
+
+/// Whether the consumer's filters select events of this type from this herald.
+///
+/// An empty filter list matches everything; otherwise any one glob must match.
+pub fn consumer_matches(consumer: &ConsumerInfo, event_type: &str, herald_id: &str) -> bool {
+    filters_match(
+        &consumer.event_types,
+        &consumer.herald_ids,
+        event_type,
+        herald_id,
+    )
+}
+
+/// Like [`consumer_matches`], for filter lists not attached to a consumer.
+pub fn filters_match(
+    event_types: &[String],
+    herald_ids: &[String],
+    event_type: &str,
+    herald_id: &str,
+) -> bool {
+    let any_match = |globs: &[String], value: &str| {
+        globs.is_empty() || globs.iter().any(|glob| glob_match(glob, value))
+    };
+    any_match(event_types, event_type) && any_match(herald_ids, herald_id)
+}
+
+/// Matches `text` against a glob where `*` stands for any run of characters,
+/// dots included (`chat.*` matches `chat.message` and `chat.room.join`).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let text = text.as_bytes();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it currently covers up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, covered)) = backtrack {
+            // Let the last `*` swallow one more character and retry
+            p = star + 1;
+            t = covered + 1;
+            backtrack = Some((star, covered + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    fn consumer(event_types: &[&str], herald_ids: &[&str]) -> ConsumerInfo {
+        ConsumerInfo {
+            name: "test".to_string(),
+            event_types: event_types.iter().map(|s| s.to_string()).collect(),
+            herald_ids: herald_ids.iter().map(|s| s.to_string()).collect(),
+            created_at: OffsetDateTime::now_utc(),
+            pending: 0,
+            delivered: 0,
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("chat.*", "chat.message"));
+        assert!(glob_match("chat.*", "chat.room.join"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*.trigger", "kairos.trigger"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("kairos.trigger", "kairos.trigger"));
+        assert!(!glob_match("chat.*", "chat"));
+        assert!(!glob_match("kairos.trigger", "kairos.triggered"));
+        assert!(!glob_match("a*b", "aXbY"));
+    }
+
+    #[test]
+    fn test_consumer_matches_filters() {
+        assert!(consumer_matches(
+            &consumer(&[], &[]),
+            "chat.message",
+            "atrium"
+        ));
+
+        let chat = consumer(&["chat.*", "kairos.trigger"], &[]);
+        assert!(consumer_matches(&chat, "chat.message", "atrium"));
+        assert!(consumer_matches(&chat, "kairos.trigger", "kairos"));
+        assert!(!consumer_matches(&chat, "system.startup", "agora"));
+
+        let atrium_chat = consumer(&["chat.*"], &["atrium*"]);
+        assert!(consumer_matches(
+            &atrium_chat,
+            "chat.message",
+            "atrium-herald"
+        ));
+        assert!(!consumer_matches(
+            &atrium_chat,
+            "chat.message",
+            "matrix-herald"
+        ));
+    }
+}
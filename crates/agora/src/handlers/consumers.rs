@@ -0,0 +1,115 @@
+//! Consumer HTTP handlers.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use tracing::{error, info, instrument, warn};
+
+use crate::consumer::{
+    ConsumerInfo, ConsumersListResponse, DEFAULT_CONSUMER, UpsertConsumerRequest,
+};
+use crate::server::AppState;
+
+/// Resolves the consumer named in a request, falling back to the default.
+/// Unknown consumers are rejected with 404.
+pub(crate) async fn resolve_consumer(
+    state: &AppState,
+    consumer: Option<String>,
+) -> Result<String, StatusCode> {
+    let consumer = consumer.unwrap_or_else(|| DEFAULT_CONSUMER.to_string());
+    if state.event_queue.get_consumer(&consumer).await.is_none() {
+        warn!("Unknown consumer: {}", consumer);
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(consumer)
+}
+
+/// HTTP handler for consumer operations.
+pub struct ConsumerHandler;
+
+impl ConsumerHandler {
+    /// List all consumers (GET /consumers).
+    #[instrument(skip(state))]
+    pub async fn list(State(state): State<AppState>) -> Json<ConsumersListResponse> {
+        info!("Listing consumers");
+
+        let consumers = state.event_queue.list_consumers().await;
+        info!("Found {} consumers", consumers.len());
+
+        Json(ConsumersListResponse { consumers })
+    }
+
+    /// Get a single consumer (GET /consumers/{name}).
+    #[instrument(skip(state))]
+    pub async fn get(
+        State(state): State<AppState>,
+        Path(name): Path<String>,
+    ) -> Result<Json<ConsumerInfo>, StatusCode> {
+        info!("Getting consumer: {}", name);
+
+        match state.event_queue.get_consumer(&name).await {
+            Some(consumer) => Ok(Json(consumer)),
+            None => {
+                info!("Consumer not found: {}", name);
+                Err(StatusCode::NOT_FOUND)
+            }
+        }
+    }
+
+    /// Create a consumer or replace its filters (PUT /consumers/{name}).
+    #[instrument(skip(state))]
+    pub async fn upsert(
+        State(state): State<AppState>,
+        Path(name): Path<String>,
+        Json(request): Json<UpsertConsumerRequest>,
+    ) -> Result<Json<ConsumerInfo>, StatusCode> {
+        info!(
+            "Upserting consumer {}: event_types={:?}, herald_ids={:?}",
+            name, request.event_types, request.herald_ids
+        );
+
+        if name.trim().is_empty() {
+            warn!("Rejecting consumer with empty name");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        match state.event_queue.upsert_consumer(&name, &request).await {
+            Ok(consumer) => {
+                info!("Upserted consumer: {}", name);
+                Ok(Json(consumer))
+            }
+            Err(e) => {
+                error!("Failed to upsert consumer {}: {}", name, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// Delete a consumer and its undelivered events (DELETE /consumers/{name}).
+    #[instrument(skip(state))]
+    pub async fn delete(
+        State(state): State<AppState>,
+        Path(name): Path<String>,
+    ) -> Result<StatusCode, StatusCode> {
+        info!("Deleting consumer: {}", name);
+
+        if name == DEFAULT_CONSUMER {
+            warn!("Rejecting delete of the default consumer");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        match state.event_queue.delete_consumer(&name).await {
+            Ok(true) => Ok(StatusCode::NO_CONTENT),
+            Ok(false) => {
+                info!("Consumer not found for delete: {}", name);
+                Err(StatusCode::NOT_FOUND)
+            }
+            Err(e) => {
+                error!("Failed to delete consumer {}: {}", name, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Dead-letter HTTP handlers.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use serde::Deserialize;
+use tracing::{error, info, instrument, warn};
+
+use crate::consumer::DEFAULT_CONSUMER;
+use crate::event::{DeadLetter, DeadLettersListResponse, Event, PurgeDeadLettersResponse};
+use crate::server::AppState;
+
+/// Query parameters for listing and purging dead-lettered events.
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    /// Only include events dead-lettered for this consumer.
+    pub consumer: Option<String>,
+    /// Only include events from this herald.
+    pub herald_id: Option<String>,
+    /// Only include events of this type.
+    pub event_type: Option<String>,
+    /// Maximum number of events to return (list only).
+    pub limit: Option<u32>,
+}
+
+/// Query parameters for operations on a single dead-lettered event.
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterConsumerQuery {
+    /// Consumer the event was dead-lettered for (defaults to `default`).
+    pub consumer: Option<String>,
+}
+
+impl DeadLetterConsumerQuery {
+    fn consumer(&self) -> &str {
+        self.consumer.as_deref().unwrap_or(DEFAULT_CONSUMER)
+    }
+}
+
+/// HTTP handler for dead-letter operations.
+pub struct DeadLetterHandler;
+
+impl DeadLetterHandler {
+    /// List dead-lettered events (GET /dead-letters).
+    #[instrument(skip(state))]
+    pub async fn list(
+        State(state): State<AppState>,
+        Query(query): Query<DeadLetterQuery>,
+    ) -> Result<Json<DeadLettersListResponse>, StatusCode> {
+        info!("Listing dead letters");
+
+        match state
+            .event_queue
+            .list_dead_letters(
+                query.consumer.as_deref(),
+                query.herald_id.as_deref(),
+                query.event_type.as_deref(),
+                query.limit.unwrap_or(100),
+            )
+            .await
+        {
+            Ok((dead_letters, total)) => {
+                info!("Found {} dead letters", total);
+                Ok(Json(DeadLettersListResponse { dead_letters, total }))
+            }
+            Err(e) => {
+                error!("Failed to list dead letters: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// Get a single dead-lettered event (GET /dead-letters/{id}).
+    #[instrument(skip(state))]
+    pub async fn get(
+        State(state): State<AppState>,
+        Path(id): Path<u64>,
+        Query(query): Query<DeadLetterConsumerQuery>,
+    ) -> Result<Json<DeadLetter>, StatusCode> {
+        info!("Getting dead letter: {}", id);
+
+        match state
+            .event_queue
+            .get_dead_letter(query.consumer(), id)
+            .await
+        {
+            Ok(Some(dead_letter)) => Ok(Json(dead_letter)),
+            Ok(None) => {
+                info!("Dead letter not found: {}", id);
+                Err(StatusCode::NOT_FOUND)
+            }
+            Err(e) => {
+                error!("Failed to get dead letter {}: {}", id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// Move a dead-lettered event back into the queue (POST /dead-letters/{id}/requeue).
+    #[instrument(skip(state))]
+    pub async fn requeue(
+        State(state): State<AppState>,
+        Path(id): Path<u64>,
+        Query(query): Query<DeadLetterConsumerQuery>,
+    ) -> Result<Json<Event>, StatusCode> {
+        info!("Requeuing dead letter: {}", id);
+
+        match state
+            .event_queue
+            .requeue_dead_letter(query.consumer(), id)
+            .await
+        {
+            Ok(Some(event)) => {
+                info!("Requeued event {}", id);
+                Ok(Json(event))
+            }
+            Ok(None) => {
+                warn!("Dead letter {} not found for requeue", id);
+                Err(StatusCode::NOT_FOUND)
+            }
+            Err(e) => {
+                error!("Failed to requeue dead letter {}: {}", id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// Delete a single dead-lettered event (DELETE /dead-letters/{id}).
+    #[instrument(skip(state))]
+    pub async fn delete(
+        State(state): State<AppState>,
+        Path(id): Path<u64>,
+        Query(query): Query<DeadLetterConsumerQuery>,
+    ) -> Result<StatusCode, StatusCode> {
+        info!("Deleting dead letter: {}", id);
+
+        match state
+            .event_queue
+            .delete_dead_letter(query.consumer(), id)
+            .await
+        {
+            Ok(true) => Ok(StatusCode::NO_CONTENT),
+            Ok(false) => {
+                info!("Dead letter not found for delete: {}", id);
+                Err(StatusCode::NOT_FOUND)
+            }
+            Err(e) => {
+                error!("Failed to delete dead letter {}: {}", id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// Purge dead-lettered events matching the filter (DELETE /dead-letters).
+    #[instrument(skip(state))]
+    pub async fn purge(
+        State(state): State<AppState>,
+        Query(query): Query<DeadLetterQuery>,
+    ) -> Result<Json<PurgeDeadLettersResponse>, StatusCode> {
+        info!("Purging dead letters");
+
+        match state
+            .event_queue
+            .purge_dead_letters(
+                query.consumer.as_deref(),
+                query.herald_id.as_deref(),
+                query.event_type.as_deref(),
+            )
+            .await
+        {
+            Ok(purged) => {
+                info!("Purged {} dead letters", purged);
+                Ok(Json(PurgeDeadLettersResponse { purged }))
+            }
+            Err(e) => {
+                error!("Failed to purge dead letters: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
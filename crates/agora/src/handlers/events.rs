@@ -1,17 +1,31 @@
 //! Event HTTP handlers.
 
+use std::convert::Infallible;
+use std::time::Duration;
+
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header::RETRY_AFTER},
+    response::{
+        IntoResponse, Json, Response,
+        sse::{self, KeepAlive, Sse},
+    },
 };
+use futures::{Stream, StreamExt, stream};
 use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{error, info, instrument, warn};
 
+use crate::consumer::filters_match;
 use crate::event::{
-    BatchUpdateEventsRequest, BatchUpdateEventsResponse, CreateEventRequest, Event, EventStatus,
-    EventsListResponse, UpdateEventRequest,
+    BatchUpdateEventsRequest, BatchUpdateEventsResponse, CreateEventRequest, Event,
+    EventHistoryQuery, EventHistoryResponse, EventStatus, EventsListResponse, QueuedEventsQuery,
+    QueuedEventsResponse, UpdateEventRequest,
 };
+use crate::handlers::consumers::resolve_consumer;
+use crate::handlers::heralds::authorize_herald;
+use crate::herald::LimitError;
+use crate::schema::InvalidPayloadResponse;
 use crate::server::AppState;
 
 /// Request body for POST /events/fetch.
@@ -19,45 +33,135 @@ use crate::server::AppState;
 pub struct FetchEventsRequest {
     /// Maximum number of events to return.
     pub limit: Option<u32>,
+    /// Long-poll: if no events are deliverable, wait up to this long (capped
+    /// at `MAX_FETCH_WAIT_MS`) for one to arrive instead of returning empty.
+    pub wait_ms: Option<u64>,
+    /// Consumer to fetch for (defaults to `default`).
+    pub consumer: Option<String>,
+}
+
+/// Upper bound for `FetchEventsRequest::wait_ms`.
+const MAX_FETCH_WAIT_MS: u64 = 60_000;
+
+/// Query parameters for GET /events/stream.
+#[derive(Debug, Deserialize)]
+pub struct StreamEventsQuery {
+    /// Maximum number of events to deliver per batch.
+    pub limit: Option<u32>,
+    /// Consumer to stream for (defaults to `default`).
+    pub consumer: Option<String>,
+}
+
+/// Query parameters for GET /events/watch.
+#[derive(Debug, Deserialize)]
+pub struct WatchEventsQuery {
+    /// Comma-separated event type globs to include (default all).
+    pub event_types: Option<String>,
+    /// Comma-separated herald ID globs to include (default all).
+    pub herald_ids: Option<String>,
 }
 
+/// Splits a comma-separated filter parameter into its globs.
+fn split_globs(globs: Option<&str>) -> Vec<String> {
+    globs
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|glob| !glob.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// How long a stream waits for events before checking again.
+const STREAM_WAIT: Duration = Duration::from_secs(30);
+
 /// HTTP handler for event operations.
 pub struct EventHandler;
 
 impl EventHandler {
     /// Push a new event (POST /events).
-    #[instrument(skip(state))]
+    ///
+    /// Requires the bearer token of the herald named in `herald_id`. An
+    /// `expires_at` that is not after `deliver_after` is rejected with 400,
+    /// a payload not conforming to the event type's schema with 422. Pushes
+    /// beyond the herald's limits are rejected with 429.
+    #[instrument(skip(state, headers))]
     pub async fn create(
         State(state): State<AppState>,
+        headers: HeaderMap,
         Json(request): Json<CreateEventRequest>,
-    ) -> Result<Json<Event>, StatusCode> {
+    ) -> Result<Json<Event>, Response> {
         info!(
             "Creating event: type={}, herald={}",
             request.event_type, request.herald_id
         );
 
+        authorize_herald(&state, &headers, &request.herald_id)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        if let (Some(deliver_after), Some(expires_at)) = (request.deliver_after, request.expires_at)
+            && expires_at <= deliver_after
+        {
+            warn!("Rejecting event that expires before it becomes deliverable");
+            return Err(StatusCode::BAD_REQUEST.into_response());
+        }
+
+        if let Err(errors) = state
+            .schema_registry
+            .validate(&request.event_type, &request.payload)
+            .await
+        {
+            warn!(
+                "Rejecting {} event with {} schema violations",
+                request.event_type,
+                errors.len()
+            );
+            let body = InvalidPayloadResponse { event_type: request.event_type, errors };
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+        }
+
+        let herald_id = request.herald_id.clone();
         match state.event_queue.push(request).await {
             Ok(event) => {
                 info!("Created event with id={}", event.id);
                 Ok(Json(event))
             }
-            Err(e) => {
-                error!("Failed to create event: {}", e);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(e) => Err(limit_response(&herald_id, e)),
         }
     }
 
     /// Fetch events for delivery (POST /events/fetch).
     /// Changes state: Pending → Delivered.
+    ///
+    /// With `wait_ms`, blocks until an event is deliverable, the wait expires
+    /// or Agora shuts down.
     #[instrument(skip(state))]
     pub async fn fetch(
         State(state): State<AppState>,
         Json(query): Json<FetchEventsRequest>,
     ) -> Result<Json<EventsListResponse>, StatusCode> {
-        info!("Fetching events: limit={}", query.limit.unwrap_or(10));
+        let limit = query.limit.unwrap_or(10);
+        let wait_ms = query.wait_ms.unwrap_or(0).min(MAX_FETCH_WAIT_MS);
+        let consumer = resolve_consumer(&state, query.consumer).await?;
+        info!(
+            "Fetching events: consumer={}, limit={}, wait_ms={}",
+            consumer, limit, wait_ms
+        );
 
-        let events = state.event_queue.fetch(query.limit.unwrap_or(10)).await;
+        let events = if wait_ms > 0 {
+            state
+                .event_queue
+                .fetch_wait(
+                    &consumer,
+                    limit,
+                    Duration::from_millis(wait_ms),
+                    &state.shutdown,
+                )
+                .await
+        } else {
+            state.event_queue.fetch(&consumer, limit).await
+        };
 
         let total = events.len();
         info!("Fetched {} events", total);
@@ -65,7 +169,180 @@ impl EventHandler {
         Ok(Json(EventsListResponse { events, total }))
     }
 
+    /// Query acked events from the archive (GET /events/history).
+    ///
+    /// Returns an empty history unless archiving is enabled.
+    #[instrument(skip(state))]
+    pub async fn history(
+        State(state): State<AppState>,
+        Query(query): Query<EventHistoryQuery>,
+    ) -> Result<Json<EventHistoryResponse>, StatusCode> {
+        info!("Querying event history: {:?}", query);
+
+        match state
+            .event_queue
+            .event_history(&query, query.limit.unwrap_or(100))
+            .await
+        {
+            Ok((events, total)) => {
+                info!("Found {} archived events ({} total)", events.len(), total);
+                Ok(Json(EventHistoryResponse { events, total }))
+            }
+            Err(e) => {
+                error!("Failed to query event history: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+
+    /// List a consumer's queued events (GET /events).
+    ///
+    /// Read-only: unlike POST /events/fetch, nothing is marked delivered.
+    #[instrument(skip(state))]
+    pub async fn list(
+        State(state): State<AppState>,
+        Query(query): Query<QueuedEventsQuery>,
+    ) -> Result<Json<QueuedEventsResponse>, StatusCode> {
+        info!("Listing queued events: {:?}", query);
+
+        let consumer = resolve_consumer(&state, query.consumer.clone()).await?;
+        let (events, total) = state
+            .event_queue
+            .list_queued(&consumer, &query, query.limit.unwrap_or(100))
+            .await;
+        info!("Found {} queued events ({} total)", events.len(), total);
+
+        Ok(Json(QueuedEventsResponse { events, total }))
+    }
+
+    /// Stream events as Server-Sent Events (GET /events/stream).
+    ///
+    /// Each event is sent as soon as it becomes deliverable, as an SSE message
+    /// named `event` with the event JSON as data. Streamed events are marked
+    /// Delivered exactly like fetched ones, so they must still be acked via
+    /// PATCH /events, or they are redelivered after the retry interval.
+    ///
+    /// Events are only fetched while the stream is being read: the waiting
+    /// fetch belongs to the response body, so it is dropped along with the
+    /// body as soon as the client disconnects, and claims nothing after.
+    ///
+    /// The stream ends when Agora shuts down.
+    #[instrument(skip(state))]
+    pub async fn stream(
+        State(state): State<AppState>,
+        Query(query): Query<StreamEventsQuery>,
+    ) -> Result<Sse<impl Stream<Item = Result<sse::Event, Infallible>>>, StatusCode> {
+        let limit = query.limit.unwrap_or(10);
+        let consumer = resolve_consumer(&state, query.consumer).await?;
+        info!(
+            "Opening event stream: consumer={}, limit={}",
+            consumer, limit
+        );
+
+        let shutdown = state.shutdown.clone();
+        let waiting = shutdown.clone();
+        let batches = stream::unfold(state.event_queue, move |queue| {
+            let consumer = consumer.clone();
+            let shutdown = waiting.clone();
+            async move {
+                loop {
+                    let events = queue
+                        .fetch_wait(&consumer, limit, STREAM_WAIT, &shutdown)
+                        .await;
+                    if !events.is_empty() {
+                        info!("Streaming {} events to {}", events.len(), consumer);
+                        return Some((stream::iter(events), queue));
+                    }
+                    if shutdown.is_cancelled() {
+                        return None;
+                    }
+                }
+            }
+        });
+
+        let messages = batches.flatten().filter_map(|event| async move {
+            match sse::Event::default()
+                .event("event")
+                .id(event.id.to_string())
+                .json_data(&event)
+            {
+                Ok(message) => Some(Ok(message)),
+                Err(e) => {
+                    error!("Failed to encode event {} for stream: {}", event.id, e);
+                    None
+                }
+            }
+        });
+        let messages = messages.take_until(shutdown.cancelled_owned());
+
+        Ok(Sse::new(messages).keep_alive(KeepAlive::default()))
+    }
+
+    /// Watch pushed events as Server-Sent Events (GET /events/watch).
+    ///
+    /// Unlike GET /events/stream, this is read-only: it registers no consumer
+    /// and delivers nothing, so events need no ack and nothing is left behind
+    /// when the watcher goes away. Each event matching the filters is sent
+    /// once pushed, as an SSE message named `event`. A watcher that falls
+    /// behind skips the events it missed.
+    ///
+    /// The stream ends when Agora shuts down.
+    #[instrument(skip(state))]
+    pub async fn watch(
+        State(state): State<AppState>,
+        Query(query): Query<WatchEventsQuery>,
+    ) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+        let event_types = split_globs(query.event_types.as_deref());
+        let herald_ids = split_globs(query.herald_ids.as_deref());
+        info!(
+            "Opening watch stream: event_types={:?}, herald_ids={:?}",
+            event_types, herald_ids
+        );
+
+        let events = stream::unfold(state.event_queue.watch(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Watch stream fell behind, skipped {} events", skipped)
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        let messages = events.filter_map(move |event| {
+            let matches = filters_match(
+                &event_types,
+                &herald_ids,
+                &event.event_type,
+                &event.herald_id,
+            );
+            async move {
+                if !matches {
+                    return None;
+                }
+                match sse::Event::default()
+                    .event("event")
+                    .id(event.id.to_string())
+                    .json_data(&event)
+                {
+                    Ok(message) => Some(Ok(message)),
+                    Err(e) => {
+                        error!("Failed to encode event {} for watch: {}", event.id, e);
+                        None
+                    }
+                }
+            }
+        });
+        let messages = messages.take_until(state.shutdown.cancelled_owned());
+
+        Sse::new(messages).keep_alive(KeepAlive::default())
+    }
+
     /// Update event status (PATCH /events/{id}).
+    ///
+    /// `acked` acks the event; `pending` requeues it for immediate redelivery.
     #[instrument(skip(state))]
     pub async fn update(
         State(state): State<AppState>,
@@ -74,13 +351,21 @@ impl EventHandler {
     ) -> Result<Json<Event>, StatusCode> {
         info!("Updating event {}: status={:?}", id, request.status);
 
-        // Only allow acking events
-        if request.status != EventStatus::Acked {
-            warn!("Rejecting non-ack status update for event {}", id);
-            return Err(StatusCode::BAD_REQUEST);
+        match request.status {
+            EventStatus::Acked => {}
+            EventStatus::Pending => return Self::requeue(&state, id, request.consumer).await,
+            EventStatus::Delivered => {
+                warn!("Rejecting delivered status update for event {}", id);
+                return Err(StatusCode::BAD_REQUEST);
+            }
         }
 
-        match state.event_queue.update_status(id, request.status).await {
+        let consumer = resolve_consumer(&state, request.consumer).await?;
+        match state
+            .event_queue
+            .update_status(&consumer, id, request.status)
+            .await
+        {
             Ok(Some(event)) => {
                 info!("Acked event {}", id);
                 Ok(Json(event))
@@ -96,6 +381,24 @@ impl EventHandler {
         }
     }
 
+    async fn requeue(
+        state: &AppState,
+        id: u64,
+        consumer: Option<String>,
+    ) -> Result<Json<Event>, StatusCode> {
+        let consumer = resolve_consumer(state, consumer).await?;
+        match state.event_queue.requeue(&consumer, id).await {
+            Some(event) => {
+                info!("Requeued event {} for {}", id, consumer);
+                Ok(Json(event))
+            }
+            None => {
+                warn!("Event {} not found for requeue", id);
+                Err(StatusCode::NOT_FOUND)
+            }
+        }
+    }
+
     /// Batch update event status (PATCH /events).
     #[instrument(skip(state))]
     pub async fn batch_update(
@@ -117,14 +420,25 @@ impl EventHandler {
             return Err(StatusCode::BAD_REQUEST);
         }
 
+        let consumer = resolve_consumer(&state, request.consumer).await?;
         match state
             .event_queue
-            .batch_update_status(request.event_ids, request.status)
+            .batch_update_status(&consumer, request.event_ids, request.status, request.mode)
             .await
         {
-            Ok(acked_ids) => {
-                info!("Batch acked {} events: {:?}", acked_ids.len(), acked_ids);
-                Ok(Json(BatchUpdateEventsResponse { acked_ids }))
+            Ok(outcome) => {
+                info!(
+                    "Batch acked {} events: {:?} (missing: {:?}, failed: {:?})",
+                    outcome.acked.len(),
+                    outcome.acked,
+                    outcome.missing,
+                    outcome.failed
+                );
+                Ok(Json(BatchUpdateEventsResponse {
+                    acked_ids: outcome.acked,
+                    missing_ids: outcome.missing,
+                    failed_ids: outcome.failed,
+                }))
             }
             Err(e) => {
                 error!("Failed to batch update events: {}", e);
@@ -133,3 +447,218 @@ impl EventHandler {
         }
     }
 }
+
+/// Response for a push refused by the herald's limits, or failed in storage.
+fn limit_response(herald_id: &str, error: LimitError) -> Response {
+    match error {
+        LimitError::Storage(e) => {
+            error!("Failed to create event from {}: {}", herald_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+        LimitError::RateLimited { retry_after, .. } => {
+            warn!("Rejecting event from {}: {}", herald_id, error);
+            let seconds = retry_after.as_secs_f64().ceil() as u64;
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, seconds.to_string())],
+            )
+                .into_response()
+        }
+        LimitError::PayloadTooLarge { .. } | LimitError::TooManyPending { .. } => {
+            warn!("Rejecting event from {}: {}", herald_id, error);
+            StatusCode::TOO_MANY_REQUESTS.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventPriority;
+    use time::OffsetDateTime;
+    use tokio::time::Instant;
+
+    fn fetch_request(wait_ms: u64) -> Json<FetchEventsRequest> {
+        Json(FetchEventsRequest { limit: None, wait_ms: Some(wait_ms), consumer: None })
+    }
+
+    fn test_request() -> CreateEventRequest {
+        CreateEventRequest {
+            event_type: "test".to_string(),
+            herald_id: "test-herald".to_string(),
+            priority: EventPriority::Normal,
+            payload: serde_json::json!({}),
+            timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        }
+    }
+
+    /// Opens GET /events/stream on a raw connection and reads past the
+    /// response head.
+    async fn open_stream(addr: std::net::SocketAddr) -> tokio::net::TcpStream {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut conn = tokio::net::TcpStream::connect(addr).await.unwrap();
+        conn.write_all(b"GET /events/stream HTTP/1.1\r\nHost: agora\r\n\r\n")
+            .await
+            .unwrap();
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(conn.read_u8().await.unwrap());
+        }
+        assert!(head.starts_with(b"HTTP/1.1 200"));
+        conn
+    }
+
+    #[test]
+    fn test_split_globs() {
+        assert!(split_globs(None).is_empty());
+        assert!(split_globs(Some("")).is_empty());
+        assert_eq!(
+            split_globs(Some("chat.*, kairos.trigger,")),
+            vec!["chat.*", "kairos.trigger"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_waits_for_wait_ms_when_empty() {
+        let state = AppState::for_tests().await;
+        tokio::time::pause();
+
+        let started = Instant::now();
+        let response = EventHandler::fetch(State(state), fetch_request(5_000))
+            .await
+            .unwrap();
+
+        assert!(response.events.is_empty());
+        let waited = started.elapsed();
+        assert!(waited >= Duration::from_secs(5) && waited < Duration::from_secs(6));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_wakes_on_push() {
+        let state = AppState::for_tests().await;
+        tokio::time::pause();
+
+        let started = Instant::now();
+        let fetch = tokio::spawn(EventHandler::fetch(
+            State(state.clone()),
+            fetch_request(30_000),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let event = state.event_queue.push(test_request()).await.unwrap();
+
+        let response = fetch.await.unwrap().unwrap();
+        assert_eq!(response.events.len(), 1);
+        assert_eq!(response.events[0].id, event.id);
+        assert!(started.elapsed() < Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_returns_on_shutdown() {
+        let state = AppState::for_tests().await;
+        tokio::time::pause();
+
+        let started = Instant::now();
+        let fetch = tokio::spawn(EventHandler::fetch(
+            State(state.clone()),
+            fetch_request(MAX_FETCH_WAIT_MS),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        state.shutdown.cancel();
+
+        let response = fetch.await.unwrap().unwrap();
+        assert!(response.events.is_empty());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_caps_wait_ms() {
+        let state = AppState::for_tests().await;
+        tokio::time::pause();
+
+        let started = Instant::now();
+        let response = EventHandler::fetch(State(state), fetch_request(10 * MAX_FETCH_WAIT_MS))
+            .await
+            .unwrap();
+
+        assert!(response.events.is_empty());
+        let cap = Duration::from_millis(MAX_FETCH_WAIT_MS);
+        let waited = started.elapsed();
+        assert!(waited >= cap && waited < cap + Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_stream_leaves_events_for_the_next() {
+        use tokio::io::AsyncReadExt;
+
+        let state = AppState::for_tests().await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, crate::server::router(state.clone())).into_future());
+
+        // A subscriber goes away while its stream waits for events
+        drop(open_stream(addr).await);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let event = state.event_queue.push(test_request()).await.unwrap();
+        let mut next = open_stream(addr).await;
+        let mut received = String::new();
+        let read = tokio::time::timeout(Duration::from_secs(1), async {
+            let mut buf = [0; 1024];
+            while !received.contains("event: event") {
+                let n = next.read(&mut buf).await.unwrap();
+                received.push_str(&String::from_utf8_lossy(&buf[..n]));
+            }
+        })
+        .await;
+
+        assert!(read.is_ok(), "event not streamed to the next subscriber");
+        assert!(received.contains(&format!("id: {}", event.id)));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_ends_waiting_fetch_and_stream() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let state = AppState::for_tests().await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(epha_shutdown::serve(
+            listener,
+            crate::server::router(state.clone()),
+            state.shutdown.clone(),
+            Duration::from_secs(30),
+        ));
+
+        // A long poll and a stream are both waiting for events
+        let mut stream = open_stream(addr).await;
+        let mut fetch = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let body = format!(r#"{{"wait_ms":{}}}"#, MAX_FETCH_WAIT_MS);
+        let request = format!(
+            "POST /events/fetch HTTP/1.1\r\nHost: agora\r\nConnection: close\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        fetch.write_all(request.as_bytes()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        state.shutdown.cancel();
+        let ended = tokio::time::timeout(Duration::from_secs(1), async {
+            let mut response = Vec::new();
+            fetch.read_to_end(&mut response).await.unwrap();
+            stream.read_to_end(&mut Vec::new()).await.unwrap();
+            server.await.unwrap().unwrap();
+            response
+        })
+        .await;
+
+        let response = ended.expect("fetch, stream or server still open after shutdown");
+        assert!(response.starts_with(b"HTTP/1.1 200"));
+        assert!(String::from_utf8_lossy(&response).ends_with(r#"{"events":[],"total":0}"#));
+    }
+}
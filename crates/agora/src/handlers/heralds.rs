@@ -1,27 +1,128 @@
 //! Herald HTTP handlers.
 
 use axum::extract::State;
+use axum::http::{HeaderMap, header::AUTHORIZATION};
 use axum::{extract::Path, http::StatusCode, response::Json};
-use tracing::{info, instrument};
+use tracing::{error, info, instrument, warn};
 
-use crate::herald::{HeartbeatResponse, HeraldInfo, HeraldsListResponse, RegisterHeraldRequest};
+use crate::herald::{
+    HeartbeatResponse, HeraldError, HeraldInfo, HeraldsListResponse, RegisterHeraldRequest,
+    RegisterHeraldResponse, SYSTEM_HERALD_ID,
+};
+use crate::schema::SchemaError;
 use crate::server::AppState;
 
+/// Extracts the token from an `Authorization: Bearer <token>` header.
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Rejects the request with 401 unless it carries herald `id`'s token.
+pub(crate) async fn authorize_herald(
+    state: &AppState,
+    headers: &HeaderMap,
+    id: &str,
+) -> Result<(), StatusCode> {
+    if state
+        .herald_registry
+        .authenticate(id, bearer_token(headers))
+        .await
+    {
+        Ok(())
+    } else {
+        warn!("Rejecting request with invalid token for herald {}", id);
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Like [`authorize_herald`], but also accepts the admin token.
+async fn authorize_herald_or_admin(
+    state: &AppState,
+    headers: &HeaderMap,
+    id: &str,
+) -> Result<(), StatusCode> {
+    if state
+        .herald_registry
+        .authenticate_admin(bearer_token(headers))
+    {
+        info!("Admin request for herald {}", id);
+        return Ok(());
+    }
+    authorize_herald(state, headers, id).await
+}
+
 /// HTTP handler for herald operations.
 pub struct HeraldHandler;
 
 impl HeraldHandler {
     /// Register a new herald (POST /heralds).
-    #[instrument(skip(state))]
+    ///
+    /// Known heralds must present their token; new ones are issued one. The
+    /// herald's schemas replace its earlier ones: an invalid schema is
+    /// rejected with 400, one conflicting with another source's with 409.
+    #[instrument(skip(state, headers))]
     pub async fn register(
         State(state): State<AppState>,
-        Json(request): Json<RegisterHeraldRequest>,
-    ) -> Result<Json<HeraldInfo>, StatusCode> {
+        headers: HeaderMap,
+        Json(mut request): Json<RegisterHeraldRequest>,
+    ) -> Result<Json<RegisterHeraldResponse>, StatusCode> {
         info!("Registering herald: {}", request.id);
 
-        let info = state.herald_registry.register(request).await;
-        info!("Registered herald: {}", info.id);
-        Ok(Json(info))
+        if request.id == SYSTEM_HERALD_ID {
+            warn!(
+                "Rejecting registration with reserved herald ID {}",
+                request.id
+            );
+            return Err(StatusCode::BAD_REQUEST);
+        }
+
+        let schemas = std::mem::take(&mut request.schemas);
+        let schemas = match state.schema_registry.prepare(&request.id, schemas).await {
+            Ok(schemas) => schemas,
+            Err(e @ SchemaError::Invalid { .. }) => {
+                warn!("Rejecting registration of herald {}: {}", request.id, e);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            Err(e @ SchemaError::Conflict { .. }) => {
+                warn!("Rejecting registration of herald {}: {}", request.id, e);
+                return Err(StatusCode::CONFLICT);
+            }
+            Err(SchemaError::Storage(e)) => {
+                error!("Failed to check schemas: {}", e);
+                return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            }
+        };
+
+        match state
+            .herald_registry
+            .register(request, bearer_token(&headers))
+            .await
+        {
+            Ok((herald, token)) => {
+                info!("Registered herald: {}", herald.id);
+                if let Err(e) = state.schema_registry.replace(schemas).await {
+                    error!("Failed to register schemas of herald {}: {}", herald.id, e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                state.herald_status_events.active(&herald.id).await;
+                Ok(Json(RegisterHeraldResponse { herald, token }))
+            }
+            Err(HeraldError::Unauthorized(id)) => {
+                warn!(
+                    "Rejecting registration with invalid token for herald {}",
+                    id
+                );
+                Err(StatusCode::UNAUTHORIZED)
+            }
+            Err(HeraldError::Storage(e)) => {
+                error!("Failed to register herald: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
     }
 
     /// List all heralds (GET /heralds).
@@ -29,7 +130,21 @@ impl HeraldHandler {
     pub async fn list(State(state): State<AppState>) -> Json<HeraldsListResponse> {
         info!("Listing heralds");
 
-        let heralds = state.herald_registry.list().await;
+        let mut heralds = state.herald_registry.list().await;
+        match state.event_queue.dead_letter_counts().await {
+            Ok(counts) => {
+                for herald in &mut heralds {
+                    herald.dead_letters = counts.get(&herald.id).copied().unwrap_or(0);
+                }
+            }
+            Err(e) => warn!("Failed to count dead letters: {}", e),
+        }
+        for herald in &mut heralds {
+            herald.limits = state
+                .herald_limiter
+                .status(&herald.id, &state.event_queue)
+                .await;
+        }
         info!("Found {} heralds", heralds.len());
 
         Json(HeraldsListResponse { heralds })
@@ -44,8 +159,13 @@ impl HeraldHandler {
         info!("Getting herald: {}", id);
 
         match state.herald_registry.get(&id).await {
-            Some(info) => {
+            Some(mut info) => {
                 info!("Found herald: {}", id);
+                match state.event_queue.dead_letter_counts().await {
+                    Ok(counts) => info.dead_letters = counts.get(&id).copied().unwrap_or(0),
+                    Err(e) => warn!("Failed to count dead letters: {}", e),
+                }
+                info.limits = state.herald_limiter.status(&id, &state.event_queue).await;
                 Ok(Json(info))
             }
             None => {
@@ -56,39 +176,135 @@ impl HeraldHandler {
     }
 
     /// Update herald heartbeat (POST /heralds/{id}/heartbeat).
-    #[instrument(skip(state))]
+    ///
+    /// Unknown heralds get 404 (so they know to re-register) before the token is checked.
+    #[instrument(skip(state, headers))]
     pub async fn heartbeat(
         State(state): State<AppState>,
+        headers: HeaderMap,
         Path(id): Path<String>,
     ) -> Result<Json<HeartbeatResponse>, StatusCode> {
         info!("Herald heartbeat: {}", id);
 
+        if state.herald_registry.get(&id).await.is_none() {
+            info!("Herald not found for heartbeat: {}", id);
+            return Err(StatusCode::NOT_FOUND);
+        }
+        authorize_herald(&state, &headers, &id).await?;
+
         match state.herald_registry.heartbeat(&id).await {
-            Some(response) => {
+            Ok(Some(response)) => {
                 info!("Updated heartbeat for herald: {}", id);
+                state.herald_status_events.active(&id).await;
                 Ok(Json(response))
             }
-            None => {
+            Ok(None) => {
                 info!("Herald not found for heartbeat: {}", id);
                 Err(StatusCode::NOT_FOUND)
             }
+            Err(e) => {
+                error!("Failed to record heartbeat for herald {}: {}", id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
         }
     }
 
     /// Unregister a herald (DELETE /heralds/{id}).
-    #[instrument(skip(state))]
+    ///
+    /// Requires the herald's token or the admin token; the latter frees the
+    /// ID of a herald that lost its issued token, so it can register again.
+    #[instrument(skip(state, headers))]
     pub async fn unregister(
         State(state): State<AppState>,
+        headers: HeaderMap,
         Path(id): Path<String>,
     ) -> Result<StatusCode, StatusCode> {
         info!("Unregistering herald: {}", id);
 
-        if state.herald_registry.unregister(&id).await {
-            info!("Unregistered herald: {}", id);
-            Ok(StatusCode::NO_CONTENT)
-        } else {
+        if state.herald_registry.get(&id).await.is_none() {
             info!("Herald not found for unregister: {}", id);
-            Err(StatusCode::NOT_FOUND)
+            return Err(StatusCode::NOT_FOUND);
         }
+        authorize_herald_or_admin(&state, &headers, &id).await?;
+
+        match state.herald_registry.unregister(&id).await {
+            Ok(true) => {
+                info!("Unregistered herald: {}", id);
+                if let Err(e) = state.schema_registry.remove_herald(&id).await {
+                    error!("Failed to remove schemas of herald {}: {}", id, e);
+                }
+                Ok(StatusCode::NO_CONTENT)
+            }
+            Ok(false) => {
+                info!("Herald not found for unregister: {}", id);
+                Err(StatusCode::NOT_FOUND)
+            }
+            Err(e) => {
+                error!("Failed to unregister herald {}: {}", id, e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::herald::{HeraldRegistry, SqliteHeraldStore};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_unregisters_locked_out_herald() {
+        let mut state = AppState::for_tests().await;
+        let store = SqliteHeraldStore::new(":memory:").await.unwrap();
+        state.herald_registry = Arc::new(
+            HeraldRegistry::new(store, HashMap::new())
+                .await
+                .unwrap()
+                .with_admin_token(Some("admin".to_string())),
+        );
+        let request = RegisterHeraldRequest {
+            id: "h".to_string(),
+            description: None,
+            schemas: HashMap::new(),
+        };
+        let registered = HeraldHandler::register(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(request.clone()),
+        )
+        .await
+        .unwrap();
+        assert!(!registered.token.is_empty());
+
+        // The herald restarted and lost its issued token
+        let locked_out = HeraldHandler::register(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(request.clone()),
+        )
+        .await;
+        assert_eq!(locked_out.unwrap_err(), StatusCode::UNAUTHORIZED);
+        let status =
+            HeraldHandler::unregister(State(state.clone()), bearer("guess"), Path("h".to_string()))
+                .await;
+        assert_eq!(status, Err(StatusCode::UNAUTHORIZED));
+
+        let status =
+            HeraldHandler::unregister(State(state.clone()), bearer("admin"), Path("h".to_string()))
+                .await;
+        assert_eq!(status, Ok(StatusCode::NO_CONTENT));
+        assert!(
+            HeraldHandler::register(State(state), HeaderMap::new(), Json(request))
+                .await
+                .is_ok()
+        );
     }
 }
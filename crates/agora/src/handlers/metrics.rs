@@ -0,0 +1,38 @@
+//! Metrics HTTP handler.
+
+use axum::{
+    extract::State,
+    http::{StatusCode, header::CONTENT_TYPE},
+    response::IntoResponse,
+};
+use tracing::{debug, error, instrument};
+
+use crate::server::AppState;
+
+/// Content type of the Prometheus text format.
+const PROMETHEUS_TEXT: &str = "text/plain; version=0.0.4";
+
+/// HTTP handler for metrics.
+pub struct MetricsHandler;
+
+impl MetricsHandler {
+    /// Prometheus metrics (GET /metrics).
+    #[instrument(skip(state))]
+    pub async fn render(State(state): State<AppState>) -> Result<impl IntoResponse, StatusCode> {
+        debug!("Rendering metrics");
+
+        if let Err(e) = state.event_queue.record_metrics().await {
+            error!("Failed to collect queue metrics: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        state.herald_registry.record_metrics(&state.metrics).await;
+
+        match state.metrics.render() {
+            Ok(body) => Ok(([(CONTENT_TYPE, PROMETHEUS_TEXT)], body)),
+            Err(e) => {
+                error!("Failed to render metrics: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
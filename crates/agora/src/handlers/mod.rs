@@ -1,7 +1,15 @@
 //! HTTP handlers.
 
+pub mod consumers;
+pub mod dead_letters;
 pub mod events;
 pub mod heralds;
+pub mod metrics;
+pub mod schemas;
 
+pub use consumers::ConsumerHandler;
+pub use dead_letters::DeadLetterHandler;
 pub use events::EventHandler;
 pub use heralds::HeraldHandler;
+pub use metrics::MetricsHandler;
+pub use schemas::SchemaHandler;
@@ -0,0 +1,41 @@
+//! Schema HTTP handlers.
+
+use axum::extract::{Path, State};
+use axum::{http::StatusCode, response::Json};
+use tracing::{info, instrument};
+
+use crate::schema::{EventSchema, EventSchemasResponse};
+use crate::server::AppState;
+
+/// HTTP handler for payload schemas.
+pub struct SchemaHandler;
+
+impl SchemaHandler {
+    /// List all schemas (GET /schemas).
+    #[instrument(skip(state))]
+    pub async fn list(State(state): State<AppState>) -> Json<EventSchemasResponse> {
+        info!("Listing schemas");
+
+        let schemas = state.schema_registry.list().await;
+        info!("Found {} schemas", schemas.len());
+
+        Json(EventSchemasResponse { schemas })
+    }
+
+    /// Get the schema for an event type (GET /schemas/{event_type}).
+    #[instrument(skip(state))]
+    pub async fn get(
+        State(state): State<AppState>,
+        Path(event_type): Path<String>,
+    ) -> Result<Json<EventSchema>, StatusCode> {
+        info!("Getting schema: {}", event_type);
+
+        match state.schema_registry.get(&event_type).await {
+            Some(schema) => Ok(Json(schema)),
+            None => {
+                info!("Schema not found: {}", event_type);
+                Err(StatusCode::NOT_FOUND)
+            }
+        }
+    }
+}
@@ -0,0 +1,476 @@
+//! Per-herald push limits.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::event::CreateEventRequest;
+use crate::herald::{HeraldLimitStatus, HeraldLimits, LimitPolicy, SYSTEM_HERALD_ID};
+use crate::queue::EventQueue;
+
+/// Window `events_per_minute` is counted over.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Why a push was not admitted.
+#[derive(Debug, thiserror::Error)]
+pub enum LimitError {
+    #[error("payload of {size} bytes exceeds max_payload_bytes ({max})")]
+    PayloadTooLarge { size: u64, max: u64 },
+    /// A push may be admitted again after `retry_after`.
+    #[error("events_per_minute ({limit}) reached")]
+    RateLimited { limit: u32, retry_after: Duration },
+    #[error("max_pending ({max}) reached")]
+    TooManyPending { max: u32 },
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Default)]
+struct LimitState {
+    /// When the pushes of the last minute were admitted, oldest first.
+    recent: VecDeque<Instant>,
+    rejected: u64,
+    dropped: u64,
+}
+
+impl LimitState {
+    fn prune(&mut self, now: Instant) {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+    }
+}
+
+/// Enforces each herald's [`HeraldLimits`] on its pushes, so one herald
+/// cannot flood the queue and crowd out everyone else's events.
+///
+/// Heralds without limits of their own get the default ones; Agora's own
+/// status events are never limited.
+pub struct HeraldLimiter {
+    default: HeraldLimits,
+    configured: HashMap<String, HeraldLimits>,
+    /// Each herald's state behind a lock of its own, so one herald's pushes
+    /// never wait on another's.
+    states: std::sync::Mutex<HashMap<String, Arc<Mutex<LimitState>>>>,
+}
+
+/// A push admitted by [`HeraldLimiter::admit`]. Other limited pushes from
+/// the same herald wait until it is dropped, so the push should be queued
+/// first: then every push is checked against the ones accepted before it.
+pub struct Admission<'a> {
+    limits: &'a HeraldLimits,
+    state: Option<OwnedMutexGuard<LimitState>>,
+}
+
+impl HeraldLimiter {
+    pub fn new(default: HeraldLimits, mut configured: HashMap<String, HeraldLimits>) -> Self {
+        configured.insert(SYSTEM_HERALD_ID.to_string(), HeraldLimits::default());
+        Self { default, configured, states: Default::default() }
+    }
+
+    /// A herald's state, created on first use.
+    fn state(&self, herald_id: &str) -> Arc<Mutex<LimitState>> {
+        let mut states = self.states.lock().unwrap();
+        states.entry(herald_id.to_string()).or_default().clone()
+    }
+
+    /// The limits that apply to a herald.
+    pub fn limits(&self, herald_id: &str) -> &HeraldLimits {
+        self.configured.get(herald_id).unwrap_or(&self.default)
+    }
+
+    /// Admits a push within the herald's `max_payload_bytes` and
+    /// `events_per_minute`, or says which of them refuses it. A push that adds
+    /// an event must then pass [`Admission::make_room`] too.
+    pub async fn admit(&self, request: &CreateEventRequest) -> Result<Admission<'_>, LimitError> {
+        let limits = self.limits(&request.herald_id);
+        if !limits.is_limited() {
+            return Ok(Admission { limits, state: None });
+        }
+
+        // Held until the push is queued, so concurrent pushes cannot all pass
+        // the same check
+        let mut state = self.state(&request.herald_id).lock_owned().await;
+        if let Err(e) = Self::check(&mut state, request, limits) {
+            state.rejected += 1;
+            return Err(e);
+        }
+        Ok(Admission { limits, state: Some(state) })
+    }
+
+    /// Checks the payload size and rate limits.
+    fn check(
+        herald: &mut LimitState,
+        request: &CreateEventRequest,
+        limits: &HeraldLimits,
+    ) -> Result<(), LimitError> {
+        if let Some(max) = limits.max_payload_bytes {
+            let size = serde_json::to_vec(&request.payload)
+                .map_err(anyhow::Error::from)?
+                .len() as u64;
+            if size > max {
+                return Err(LimitError::PayloadTooLarge { size, max });
+            }
+        }
+
+        if let Some(limit) = limits.events_per_minute {
+            let now = Instant::now();
+            herald.prune(now);
+            if herald.recent.len() >= limit as usize {
+                let oldest = herald.recent.front().copied().unwrap_or(now);
+                let retry_after = RATE_WINDOW.saturating_sub(now.duration_since(oldest));
+                return Err(LimitError::RateLimited { limit, retry_after });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A herald's limits and its standing against them, if it has any.
+    pub async fn status(&self, herald_id: &str, queue: &EventQueue) -> Option<HeraldLimitStatus> {
+        let limits = self.limits(herald_id);
+        if !limits.is_limited() {
+            return None;
+        }
+
+        let pending_events = queue.herald_pending(herald_id).await as u64;
+        let state = self.state(herald_id);
+        let mut herald = state.lock().await;
+        herald.prune(Instant::now());
+        Some(HeraldLimitStatus {
+            limits: limits.clone(),
+            recent_events: herald.recent.len() as u32,
+            pending_events,
+            rejected_events: herald.rejected,
+            dropped_events: herald.dropped,
+        })
+    }
+}
+
+impl Admission<'_> {
+    /// Holds a push that adds an event to the herald's `max_pending`.
+    ///
+    /// Under [`LimitPolicy::DropOldest`], a push that would exceed it drops
+    /// the herald's least important undelivered events to make room; it is
+    /// refused only if there are none it outranks.
+    pub async fn make_room(
+        &mut self,
+        queue: &EventQueue,
+        request: &CreateEventRequest,
+    ) -> Result<(), LimitError> {
+        let Some(state) = &mut self.state else {
+            return Ok(());
+        };
+        match Self::check_pending(queue, request, self.limits).await {
+            Ok(dropped) => {
+                state.dropped += dropped;
+                Ok(())
+            }
+            Err(e) => {
+                state.rejected += 1;
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns how many events were dropped to make room.
+    async fn check_pending(
+        queue: &EventQueue,
+        request: &CreateEventRequest,
+        limits: &HeraldLimits,
+    ) -> Result<u64, LimitError> {
+        let herald_id = &request.herald_id;
+        let Some(max) = limits.max_pending else {
+            return Ok(0);
+        };
+
+        let mut dropped = 0;
+        while queue.herald_pending(herald_id).await >= max as usize {
+            if limits.on_exceed == LimitPolicy::Reject {
+                return Err(LimitError::TooManyPending { max });
+            }
+            match queue.drop_lowest(herald_id, request.priority).await? {
+                Some(_) => dropped += 1,
+                None => {
+                    warn!(
+                        "Herald {} is at max_pending ({}) with nothing to drop",
+                        herald_id, max
+                    );
+                    return Err(LimitError::TooManyPending { max });
+                }
+            }
+        }
+        Ok(dropped)
+    }
+
+    /// Counts the push, once queued or merged, towards `events_per_minute`.
+    pub fn accept(mut self) {
+        if let Some(state) = &mut self.state
+            && self.limits.events_per_minute.is_some()
+        {
+            state.recent.push_back(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CoalesceRule, ExpirePolicy, RetryConfig};
+    use crate::consumer::DEFAULT_CONSUMER;
+    use crate::event::EventPriority;
+    use crate::queue::MemoryEventStore;
+    use time::OffsetDateTime;
+
+    /// A queue holding pushes to `limiter`, as the server sets it up.
+    async fn queue(limiter: &Arc<HeraldLimiter>) -> EventQueue {
+        EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap()
+        .with_limiter(limiter.clone())
+    }
+
+    fn request(herald_id: &str, priority: EventPriority) -> CreateEventRequest {
+        CreateEventRequest {
+            event_type: "test".to_string(),
+            herald_id: herald_id.to_string(),
+            priority,
+            payload: serde_json::json!({ "content": "hello" }),
+            timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        }
+    }
+
+    async fn push(queue: &EventQueue, req: CreateEventRequest) -> Result<(), LimitError> {
+        queue.push(req).await.map(|_| ())
+    }
+
+    #[tokio::test]
+    async fn test_rate_and_payload_limits() {
+        let limits = HeraldLimits {
+            events_per_minute: Some(2),
+            max_payload_bytes: Some(32),
+            ..Default::default()
+        };
+        let limiter = Arc::new(HeraldLimiter::new(
+            HeraldLimits::default(),
+            HashMap::from([("h".to_string(), limits)]),
+        ));
+        let queue = queue(&limiter).await;
+
+        let mut big = request("h", EventPriority::Normal);
+        big.payload = serde_json::json!({ "content": "x".repeat(64) });
+        assert!(matches!(
+            push(&queue, big).await,
+            Err(LimitError::PayloadTooLarge { max: 32, .. })
+        ));
+
+        push(&queue, request("h", EventPriority::Normal))
+            .await
+            .unwrap();
+        push(&queue, request("h", EventPriority::Normal))
+            .await
+            .unwrap();
+        match push(&queue, request("h", EventPriority::Normal)).await {
+            Err(LimitError::RateLimited { limit: 2, retry_after }) => {
+                assert!(retry_after > Duration::ZERO && retry_after <= RATE_WINDOW)
+            }
+            other => panic!("expected rate limit, got {:?}", other),
+        }
+
+        // Heralds without limits are not limited
+        for _ in 0..5 {
+            push(&queue, request("other", EventPriority::Normal))
+                .await
+                .unwrap();
+        }
+        assert!(limiter.status("other", &queue).await.is_none());
+
+        let status = limiter.status("h", &queue).await.unwrap();
+        assert_eq!(status.recent_events, 2);
+        assert_eq!(status.pending_events, 2);
+        assert_eq!(status.rejected_events, 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_pending_policies() {
+        let reject = HeraldLimits { max_pending: Some(1), ..Default::default() };
+        let drop_oldest = HeraldLimits { on_exceed: LimitPolicy::DropOldest, ..reject.clone() };
+        let limiter = Arc::new(HeraldLimiter::new(
+            reject,
+            HashMap::from([("dropping".to_string(), drop_oldest)]),
+        ));
+        let queue = queue(&limiter).await;
+
+        // The default limits reject
+        push(&queue, request("h", EventPriority::Normal))
+            .await
+            .unwrap();
+        assert!(matches!(
+            push(&queue, request("h", EventPriority::Urgent)).await,
+            Err(LimitError::TooManyPending { max: 1 })
+        ));
+
+        // Dropping makes room only for events at least as important
+        push(&queue, request("dropping", EventPriority::Normal))
+            .await
+            .unwrap();
+        assert!(matches!(
+            push(&queue, request("dropping", EventPriority::Low)).await,
+            Err(LimitError::TooManyPending { max: 1 })
+        ));
+        push(&queue, request("dropping", EventPriority::High))
+            .await
+            .unwrap();
+
+        let status = limiter.status("dropping", &queue).await.unwrap();
+        assert_eq!(status.pending_events, 1);
+        assert_eq!(status.dropped_events, 1);
+        assert_eq!(status.rejected_events, 1);
+        assert_eq!(status.limits.on_exceed, LimitPolicy::DropOldest);
+
+        // Agora's own events are never limited
+        assert!(limiter.limits(SYSTEM_HERALD_ID).max_pending.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_bypass_limits() {
+        let limits = HeraldLimits {
+            events_per_minute: Some(1),
+            max_pending: Some(1),
+            on_exceed: LimitPolicy::DropOldest,
+            ..Default::default()
+        };
+        let limiter = Arc::new(HeraldLimiter::new(limits, HashMap::new()));
+        let queue = queue(&limiter).await;
+        let keyed = CreateEventRequest {
+            idempotency_key: Some("k".to_string()),
+            ..request("h", EventPriority::Normal)
+        };
+
+        let original = queue.push(keyed.clone()).await.unwrap();
+        // A retry returns the original instead of being rate limited or
+        // dropping the original to make room for itself
+        let retried = queue
+            .push(CreateEventRequest { priority: EventPriority::Urgent, ..keyed })
+            .await
+            .unwrap();
+        assert_eq!(retried.id, original.id);
+        assert_eq!(queue.herald_pending("h").await, 1);
+
+        let status = limiter.status("h", &queue).await.unwrap();
+        assert_eq!(status.recent_events, 1);
+        assert_eq!(status.rejected_events, 0);
+        assert_eq!(status.dropped_events, 0);
+    }
+
+    #[tokio::test]
+    async fn test_merges_count_towards_limits() {
+        let limits = HeraldLimits {
+            events_per_minute: Some(2),
+            max_payload_bytes: Some(32),
+            max_pending: Some(1),
+            ..Default::default()
+        };
+        let limiter = Arc::new(HeraldLimiter::new(limits, HashMap::new()));
+        let rules = HashMap::from([("test".to_string(), CoalesceRule { max_events: 100 })]);
+        let queue = queue(&limiter).await.with_coalesce_rules(rules);
+        let merging = || CreateEventRequest {
+            coalesce_key: Some("k".to_string()),
+            ..request("h", EventPriority::Normal)
+        };
+
+        let first = queue.push(merging()).await.unwrap();
+        // Merging adds no event, so max_pending does not refuse it
+        let merged = queue.push(merging()).await.unwrap();
+        assert_eq!(merged.id, first.id);
+
+        // But it counts towards the rate and payload limits
+        assert!(matches!(
+            push(&queue, merging()).await,
+            Err(LimitError::RateLimited { limit: 2, .. })
+        ));
+        let mut big = merging();
+        big.payload = serde_json::json!({ "content": "x".repeat(64) });
+        assert!(matches!(
+            push(&queue, big).await,
+            Err(LimitError::PayloadTooLarge { max: 32, .. })
+        ));
+
+        let events = queue.fetch(DEFAULT_CONSUMER, 10).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload.as_array().unwrap().len(), 2);
+        let status = limiter.status("h", &queue).await.unwrap();
+        assert_eq!(status.recent_events, 2);
+        assert_eq!(status.rejected_events, 2);
+    }
+
+    #[tokio::test]
+    async fn test_heralds_are_admitted_independently() {
+        let limits = HeraldLimits { events_per_minute: Some(10), ..Default::default() };
+        let limiter = HeraldLimiter::new(limits, HashMap::new());
+
+        let held = limiter
+            .admit(&request("slow", EventPriority::Normal))
+            .await
+            .unwrap();
+        // Another herald is not held up by the admitted push
+        let other = tokio::time::timeout(
+            Duration::from_secs(1),
+            limiter.admit(&request("fast", EventPriority::Normal)),
+        )
+        .await
+        .expect("admission waited on another herald");
+        other.unwrap().accept();
+
+        // The same herald waits until the push is queued
+        let same = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.admit(&request("slow", EventPriority::Normal)),
+        )
+        .await;
+        assert!(same.is_err());
+        held.accept();
+        limiter
+            .admit(&request("slow", EventPriority::Normal))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_pushes_respect_limits() {
+        let limits =
+            HeraldLimits { events_per_minute: Some(3), max_pending: Some(2), ..Default::default() };
+        let limiter = Arc::new(HeraldLimiter::new(limits, HashMap::new()));
+        let queue = Arc::new(queue(&limiter).await);
+
+        let pushes = (0..10).map(|_| {
+            let queue = queue.clone();
+            tokio::spawn(async move { push(&queue, request("h", EventPriority::Normal)).await })
+        });
+        let results = futures::future::join_all(pushes).await;
+
+        let admitted = results
+            .iter()
+            .filter(|r| r.as_ref().unwrap().is_ok())
+            .count();
+        assert_eq!(admitted, 2);
+        assert_eq!(queue.herald_pending("h").await, 2);
+    }
+}
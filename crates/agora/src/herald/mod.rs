@@ -1,5 +1,11 @@
 //! Herald management.
 
+mod limits;
+mod status_events;
+mod store;
 mod types;
 
+pub use limits::{HeraldLimiter, LimitError};
+pub use status_events::{HeraldStatusEvents, SYSTEM_HERALD_ID, system_schemas};
+pub use store::SqliteHeraldStore;
 pub use types::*;
@@ -0,0 +1,337 @@
+//! Agora's own events on herald disconnects and reconnects.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::json;
+use time::OffsetDateTime;
+use tracing::{debug, error, info};
+
+use crate::config::HeraldStatusEventsConfig;
+use crate::event::{CreateEventRequest, EventPriority};
+use crate::herald::HeraldInfo;
+use crate::queue::EventQueue;
+
+/// Herald ID of the events Agora pushes itself. No herald may register with it.
+pub const SYSTEM_HERALD_ID: &str = "agora";
+
+/// Event type pushed when a herald misses its heartbeat timeout.
+pub const HERALD_DISCONNECTED: &str = "system.herald.disconnected";
+
+/// Event type pushed when a herald reported disconnected is heard from again.
+pub const HERALD_RECONNECTED: &str = "system.herald.reconnected";
+
+/// Pushes `system.herald.*` events into the queue, so consumers learn that a
+/// herald went quiet because it is down.
+///
+/// A reconnect is only reported for a herald whose disconnect was, so
+/// consumers always see the pair. A disconnect within the flap suppression
+/// window of the herald's last reported one is deferred: it is dropped if the
+/// herald comes back before the window ends, and reported by
+/// [`HeraldStatusEvents::report_deferred`] otherwise.
+pub struct HeraldStatusEvents {
+    queue: Arc<EventQueue>,
+    enabled: bool,
+    flap_suppression: Duration,
+    /// Heralds configured not to be reported.
+    suppressed: HashSet<String>,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    /// When each herald's disconnect was last reported.
+    last_reported: HashMap<String, OffsetDateTime>,
+    /// Heralds reported disconnected and not reconnected since.
+    down: HashSet<String>,
+    /// Disconnects held back by flap suppression.
+    deferred: HashMap<String, HeraldInfo>,
+}
+
+impl HeraldStatusEvents {
+    pub fn new(
+        queue: Arc<EventQueue>,
+        config: &HeraldStatusEventsConfig,
+        suppressed: HashSet<String>,
+    ) -> Self {
+        Self {
+            queue,
+            enabled: config.enabled,
+            flap_suppression: Duration::from_millis(config.flap_suppression_ms),
+            suppressed,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Report that a herald missed its heartbeat timeout.
+    pub async fn disconnected(&self, herald: &HeraldInfo) {
+        if !self.enabled || self.suppressed.contains(&herald.id) {
+            return;
+        }
+
+        let now = OffsetDateTime::now_utc();
+        {
+            let mut state = self.state.lock().unwrap();
+            if let Some(&last) = state.last_reported.get(&herald.id)
+                && now - last < self.flap_suppression
+            {
+                debug!(
+                    "Deferring disconnect of herald {}: last reported at {}",
+                    herald.id, last
+                );
+                state.deferred.insert(herald.id.clone(), herald.clone());
+                return;
+            }
+            state.last_reported.insert(herald.id.clone(), now);
+            state.down.insert(herald.id.clone());
+        }
+
+        self.push_disconnected(herald).await;
+    }
+
+    /// Report deferred disconnects whose flap suppression window has ended.
+    pub async fn report_deferred(&self) {
+        let now = OffsetDateTime::now_utc();
+        let due: Vec<HeraldInfo> = {
+            let mut state = self.state.lock().unwrap();
+            let State { last_reported, down, deferred } = &mut *state;
+            let ids: Vec<String> = deferred
+                .keys()
+                .filter(|id| {
+                    last_reported
+                        .get(*id)
+                        .is_none_or(|&last| now - last >= self.flap_suppression)
+                })
+                .cloned()
+                .collect();
+            ids.into_iter()
+                .filter_map(|id| {
+                    last_reported.insert(id.clone(), now);
+                    down.insert(id.clone());
+                    deferred.remove(&id)
+                })
+                .collect()
+        };
+
+        for herald in &due {
+            self.push_disconnected(herald).await;
+        }
+    }
+
+    /// Report that a herald is active again, if its disconnect was reported.
+    pub async fn active(&self, herald_id: &str) {
+        let disconnected_at = {
+            let mut state = self.state.lock().unwrap();
+            state.deferred.remove(herald_id);
+            if !state.down.remove(herald_id) {
+                return;
+            }
+            state.last_reported.get(herald_id).copied()
+        };
+
+        let payload = json!({
+            "herald_id": herald_id,
+            "disconnected_at": disconnected_at.and_then(format_time),
+        });
+        self.push(HERALD_RECONNECTED, EventPriority::Normal, payload)
+            .await;
+    }
+
+    async fn push_disconnected(&self, herald: &HeraldInfo) {
+        let payload = json!({
+            "herald_id": herald.id,
+            "description": herald.description,
+            "last_heartbeat": format_time(herald.last_heartbeat),
+        });
+        self.push(HERALD_DISCONNECTED, EventPriority::High, payload)
+            .await;
+    }
+
+    async fn push(&self, event_type: &str, priority: EventPriority, payload: serde_json::Value) {
+        let req = CreateEventRequest {
+            event_type: event_type.to_string(),
+            herald_id: SYSTEM_HERALD_ID.to_string(),
+            priority,
+            payload,
+            timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        };
+        match self.queue.push(req).await {
+            Ok(event) => info!("Pushed {} event {}", event_type, event.id),
+            Err(e) => error!("Failed to push {} event: {}", event_type, e),
+        }
+    }
+}
+
+/// Schemas of the events Agora pushes itself, keyed by event type.
+pub fn system_schemas() -> HashMap<String, serde_json::Value> {
+    let nullable_string = json!({ "type": ["string", "null"] });
+    HashMap::from([
+        (
+            HERALD_DISCONNECTED.to_string(),
+            json!({
+                "type": "object",
+                "required": ["herald_id", "description", "last_heartbeat"],
+                "properties": {
+                    "herald_id": { "type": "string" },
+                    "description": nullable_string,
+                    "last_heartbeat": nullable_string,
+                },
+            }),
+        ),
+        (
+            HERALD_RECONNECTED.to_string(),
+            json!({
+                "type": "object",
+                "required": ["herald_id", "disconnected_at"],
+                "properties": {
+                    "herald_id": { "type": "string" },
+                    "disconnected_at": nullable_string,
+                },
+            }),
+        ),
+    ])
+}
+
+fn format_time(at: OffsetDateTime) -> Option<String> {
+    at.format(&time::format_description::well_known::Rfc3339)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExpirePolicy, RetryConfig};
+    use crate::consumer::DEFAULT_CONSUMER;
+    use crate::herald::HeraldStatus;
+    use crate::queue::MemoryEventStore;
+
+    async fn queue() -> Arc<EventQueue> {
+        Arc::new(
+            EventQueue::new(
+                Box::new(MemoryEventStore::new()),
+                RetryConfig::default(),
+                ExpirePolicy::Drop,
+                None,
+            )
+            .await
+            .unwrap(),
+        )
+    }
+
+    fn herald(id: &str) -> HeraldInfo {
+        HeraldInfo {
+            id: id.to_string(),
+            description: None,
+            status: HeraldStatus::Disconnected,
+            registered_at: OffsetDateTime::now_utc(),
+            last_heartbeat: OffsetDateTime::now_utc(),
+            dead_letters: 0,
+            limits: None,
+        }
+    }
+
+    async fn event_types(queue: &EventQueue) -> Vec<String> {
+        queue
+            .fetch(DEFAULT_CONSUMER, 10)
+            .await
+            .into_iter()
+            .map(|e| {
+                assert_eq!(e.herald_id, SYSTEM_HERALD_ID);
+                e.event_type
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reports_disconnect_and_reconnect_once() {
+        let queue = queue().await;
+        let events = HeraldStatusEvents::new(
+            queue.clone(),
+            &HeraldStatusEventsConfig::default(),
+            HashSet::new(),
+        );
+
+        // Never reported down, so nothing to report
+        events.active("atrium-herald").await;
+        events.disconnected(&herald("atrium-herald")).await;
+        events.active("atrium-herald").await;
+        events.active("atrium-herald").await;
+        assert_eq!(
+            event_types(&queue).await,
+            vec![HERALD_DISCONNECTED, HERALD_RECONNECTED]
+        );
+
+        // Flapping within the suppression window goes unreported
+        events.disconnected(&herald("atrium-herald")).await;
+        events.active("atrium-herald").await;
+        events.report_deferred().await;
+        assert!(event_types(&queue).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deferred_disconnect_reported_after_window() {
+        let queue = queue().await;
+        let config = HeraldStatusEventsConfig { flap_suppression_ms: 50, ..Default::default() };
+        let events = HeraldStatusEvents::new(queue.clone(), &config, HashSet::new());
+
+        events.disconnected(&herald("atrium-herald")).await;
+        events.active("atrium-herald").await;
+        events.disconnected(&herald("atrium-herald")).await;
+        assert_eq!(
+            event_types(&queue).await,
+            vec![HERALD_DISCONNECTED, HERALD_RECONNECTED]
+        );
+
+        // Still down once the window ends
+        events.report_deferred().await;
+        assert!(event_types(&queue).await.is_empty());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        events.report_deferred().await;
+        events.report_deferred().await;
+        assert_eq!(event_types(&queue).await, vec![HERALD_DISCONNECTED]);
+    }
+
+    #[tokio::test]
+    async fn test_events_conform_to_system_schemas() {
+        let queue = queue().await;
+        let events = HeraldStatusEvents::new(
+            queue.clone(),
+            &HeraldStatusEventsConfig::default(),
+            HashSet::new(),
+        );
+        events.disconnected(&herald("atrium-herald")).await;
+        events.active("atrium-herald").await;
+
+        let schemas = system_schemas();
+        let pushed = queue.fetch(DEFAULT_CONSUMER, 10).await;
+        assert_eq!(pushed.len(), 2);
+        for event in pushed {
+            let validator = jsonschema::validator_for(&schemas[&event.event_type]).unwrap();
+            assert!(validator.is_valid(&event.payload), "{}", event.payload);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_suppressed_and_disabled() {
+        let queue = queue().await;
+        let events = HeraldStatusEvents::new(
+            queue.clone(),
+            &HeraldStatusEventsConfig::default(),
+            HashSet::from(["kairos-herald".to_string()]),
+        );
+        events.disconnected(&herald("kairos-herald")).await;
+        events.active("kairos-herald").await;
+
+        let disabled = HeraldStatusEventsConfig { enabled: false, ..Default::default() };
+        let off = HeraldStatusEvents::new(queue.clone(), &disabled, HashSet::new());
+        off.disconnected(&herald("atrium-herald")).await;
+        off.active("atrium-herald").await;
+
+        assert!(event_types(&queue).await.is_empty());
+    }
+}
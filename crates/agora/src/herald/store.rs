@@ -0,0 +1,181 @@
+//! SQLite persistence for registered heralds.
+
+use crate::herald::{HeraldInfo, HeraldStatus};
+use anyhow::Result;
+use sqlx::{Row, SqlitePool};
+use time::OffsetDateTime;
+
+/// SQLite-backed herald storage, so registrations and tokens survive restarts.
+#[derive(Clone)]
+pub struct SqliteHeraldStore {
+    pool: SqlitePool,
+}
+
+impl SqliteHeraldStore {
+    pub async fn new(database_path: &str) -> Result<Self> {
+        let db_url = format!("sqlite:{}?mode=rwc", database_path);
+        let pool = SqlitePool::connect(&db_url).await?;
+        Self::run_migrations(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// Closes the connection pool, on shutdown.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn run_migrations(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS heralds (
+                id TEXT PRIMARY KEY,
+                description TEXT,
+                token TEXT NOT NULL,
+                registered_at TEXT NOT NULL,
+                last_heartbeat TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Load all heralds with their tokens, for startup.
+    ///
+    /// Heralds start out Active; the heartbeat timeout check marks stale ones
+    /// Disconnected.
+    pub async fn load_all(&self) -> Result<Vec<(HeraldInfo, String)>> {
+        let rows = sqlx::query("SELECT * FROM heralds ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let token: String = row.get("token");
+                Ok((row_to_herald(row)?, token))
+            })
+            .collect()
+    }
+
+    /// Insert or replace a herald's registration.
+    pub async fn upsert(&self, info: &HeraldInfo, token: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO heralds (id, description, token, registered_at, last_heartbeat)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                description = excluded.description,
+                token = excluded.token,
+                registered_at = excluded.registered_at,
+                last_heartbeat = excluded.last_heartbeat
+            "#,
+        )
+        .bind(&info.id)
+        .bind(&info.description)
+        .bind(token)
+        .bind(format_time(info.registered_at)?)
+        .bind(format_time(info.last_heartbeat)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a heartbeat. Returns true if the herald exists.
+    pub async fn update_heartbeat(&self, id: &str, at: OffsetDateTime) -> Result<bool> {
+        let result = sqlx::query("UPDATE heralds SET last_heartbeat = ? WHERE id = ?")
+            .bind(format_time(at)?)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete a herald. Returns true if deleted.
+    pub async fn delete(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM heralds WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn format_time(at: OffsetDateTime) -> Result<String> {
+    Ok(at.format(&time::format_description::well_known::Rfc3339)?)
+}
+
+fn parse_time(s: &str) -> Result<OffsetDateTime> {
+    Ok(OffsetDateTime::parse(
+        s,
+        &time::format_description::well_known::Rfc3339,
+    )?)
+}
+
+fn row_to_herald(row: sqlx::sqlite::SqliteRow) -> Result<HeraldInfo> {
+    let registered_at: String = row.get("registered_at");
+    let last_heartbeat: String = row.get("last_heartbeat");
+
+    Ok(HeraldInfo {
+        id: row.get("id"),
+        description: row.get("description"),
+        status: HeraldStatus::Active,
+        registered_at: parse_time(&registered_at)?,
+        last_heartbeat: parse_time(&last_heartbeat)?,
+        dead_letters: 0,
+        limits: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_herald(id: &str) -> HeraldInfo {
+        let now = OffsetDateTime::now_utc();
+        HeraldInfo {
+            id: id.to_string(),
+            description: Some("test".to_string()),
+            status: HeraldStatus::Active,
+            registered_at: now,
+            last_heartbeat: now,
+            dead_letters: 0,
+            limits: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_load_and_delete() {
+        let store = SqliteHeraldStore::new(":memory:").await.unwrap();
+        store.upsert(&test_herald("a"), "token-a").await.unwrap();
+        store.upsert(&test_herald("a"), "token-a2").await.unwrap();
+        store.upsert(&test_herald("b"), "token-b").await.unwrap();
+
+        let loaded = store.load_all().await.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].0.id, "a");
+        assert_eq!(loaded[0].1, "token-a2");
+        assert_eq!(loaded[0].0.description.as_deref(), Some("test"));
+
+        assert!(
+            store
+                .update_heartbeat("a", OffsetDateTime::now_utc())
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .update_heartbeat("missing", OffsetDateTime::now_utc())
+                .await
+                .unwrap()
+        );
+
+        assert!(store.delete("a").await.unwrap());
+        assert!(!store.delete("a").await.unwrap());
+        assert_eq!(store.load_all().await.unwrap().len(), 1);
+    }
+}
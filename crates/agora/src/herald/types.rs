@@ -3,21 +3,89 @@ pub use agora_common::herald::*;
 use std::collections::HashMap;
 use time::OffsetDateTime;
 use tokio::sync::RwLock;
+use uuid::Uuid;
 
-/// In-memory herald registry.
-#[derive(Debug, Default)]
+use super::SqliteHeraldStore;
+use crate::metrics::Metrics;
+
+/// Herald registry errors.
+#[derive(Debug, thiserror::Error)]
+pub enum HeraldError {
+    /// The presented token does not match the herald's token.
+    #[error("invalid or missing token for herald '{0}'")]
+    Unauthorized(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone)]
+struct RegisteredHerald {
+    info: HeraldInfo,
+    token: String,
+}
+
+/// Herald registry, cached in memory and persisted in SQLite.
+///
+/// Every herald has a secret token: either preconfigured, or issued at its
+/// first registration. Re-registering, heartbeats and pushes must present it.
+/// A herald that lost its issued token can only be unregistered with the
+/// admin token, after which it registers afresh.
 pub struct HeraldRegistry {
-    heralds: RwLock<HashMap<String, HeraldInfo>>,
+    store: SqliteHeraldStore,
+    /// Tokens fixed in config; they take precedence over issued tokens.
+    configured_tokens: HashMap<String, String>,
+    admin_token: Option<String>,
+    heralds: RwLock<HashMap<String, RegisteredHerald>>,
 }
 
 impl HeraldRegistry {
-    /// Creates a new empty registry.
-    pub fn new() -> Self {
-        Self::default()
+    /// Creates a registry, loading previously registered heralds.
+    pub async fn new(
+        store: SqliteHeraldStore,
+        configured_tokens: HashMap<String, String>,
+    ) -> anyhow::Result<Self> {
+        let heralds = store
+            .load_all()
+            .await?
+            .into_iter()
+            .map(|(info, token)| (info.id.clone(), RegisteredHerald { info, token }))
+            .collect();
+
+        Ok(Self { store, configured_tokens, admin_token: None, heralds: RwLock::new(heralds) })
+    }
+
+    /// Sets the token that authorizes admin requests.
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token;
+        self
+    }
+
+    /// Closes the store, on shutdown.
+    pub async fn close(&self) {
+        self.store.close().await;
     }
 
-    /// Registers a new herald.
-    pub async fn register(&self, request: RegisterHeraldRequest) -> HeraldInfo {
+    /// Registers a herald, or re-registers a known one.
+    ///
+    /// A herald with a configured or previously issued token must present it;
+    /// otherwise a new token is issued. Returns the herald and its token.
+    pub async fn register(
+        &self,
+        request: RegisterHeraldRequest,
+        token: Option<&str>,
+    ) -> Result<(HeraldInfo, String), HeraldError> {
+        let mut heralds = self.heralds.write().await;
+
+        let expected = self
+            .configured_tokens
+            .get(&request.id)
+            .or_else(|| heralds.get(&request.id).map(|h| &h.token));
+        let token = match expected {
+            Some(expected) if token.is_some_and(|t| tokens_match(t, expected)) => expected.clone(),
+            Some(_) => return Err(HeraldError::Unauthorized(request.id)),
+            None => generate_token(),
+        };
+
         let now = OffsetDateTime::now_utc();
         let info = HeraldInfo {
             id: request.id.clone(),
@@ -25,52 +93,93 @@ impl HeraldRegistry {
             status: HeraldStatus::Active,
             registered_at: now,
             last_heartbeat: now,
+            dead_letters: 0,
+            limits: None,
         };
 
-        let mut heralds = self.heralds.write().await;
-        heralds.insert(request.id, info.clone());
-        info
+        self.store.upsert(&info, &token).await?;
+        heralds.insert(
+            request.id,
+            RegisteredHerald { info: info.clone(), token: token.clone() },
+        );
+        Ok((info, token))
+    }
+
+    /// Whether `token` is the token of herald `id`.
+    ///
+    /// Preconfigured heralds are accepted before they first register.
+    pub async fn authenticate(&self, id: &str, token: Option<&str>) -> bool {
+        let Some(token) = token else {
+            return false;
+        };
+        if let Some(expected) = self.configured_tokens.get(id) {
+            return tokens_match(token, expected);
+        }
+        let heralds = self.heralds.read().await;
+        heralds
+            .get(id)
+            .is_some_and(|h| tokens_match(token, &h.token))
+    }
+
+    /// Whether `token` is the admin token. Always false without one.
+    pub fn authenticate_admin(&self, token: Option<&str>) -> bool {
+        token
+            .zip(self.admin_token.as_deref())
+            .is_some_and(|(token, expected)| tokens_match(token, expected))
     }
 
     /// Gets a herald by ID.
     pub async fn get(&self, id: &str) -> Option<HeraldInfo> {
         let heralds = self.heralds.read().await;
-        heralds.get(id).cloned()
+        heralds.get(id).map(|h| h.info.clone())
     }
 
     /// Lists all heralds.
     pub async fn list(&self) -> Vec<HeraldInfo> {
         let heralds = self.heralds.read().await;
-        heralds.values().cloned().collect()
+        heralds.values().map(|h| h.info.clone()).collect()
     }
 
     /// Updates herald heartbeat.
-    pub async fn heartbeat(&self, id: &str) -> Option<HeartbeatResponse> {
+    pub async fn heartbeat(&self, id: &str) -> anyhow::Result<Option<HeartbeatResponse>> {
         let mut heralds = self.heralds.write().await;
-        if let Some(info) = heralds.get_mut(id) {
-            let now = OffsetDateTime::now_utc();
-            info.last_heartbeat = now;
-            info.status = HeraldStatus::Active;
-            Some(HeartbeatResponse { status: info.status, last_heartbeat: info.last_heartbeat })
-        } else {
-            None
-        }
+        let Some(herald) = heralds.get_mut(id) else {
+            return Ok(None);
+        };
+
+        let now = OffsetDateTime::now_utc();
+        self.store.update_heartbeat(id, now).await?;
+        let info = &mut herald.info;
+        info.last_heartbeat = now;
+        info.status = HeraldStatus::Active;
+        Ok(Some(HeartbeatResponse {
+            status: info.status,
+            last_heartbeat: info.last_heartbeat,
+        }))
     }
 
     /// Unregisters a herald.
-    pub async fn unregister(&self, id: &str) -> bool {
+    pub async fn unregister(&self, id: &str) -> anyhow::Result<bool> {
         let mut heralds = self.heralds.write().await;
-        heralds.remove(id).is_some()
+        self.store.delete(id).await?;
+        Ok(heralds.remove(id).is_some())
+    }
+
+    /// Refreshes the metrics' herald status gauges.
+    pub async fn record_metrics(&self, metrics: &Metrics) {
+        let heralds = self.heralds.read().await;
+        metrics.set_herald_statuses(heralds.values().map(|h| (h.info.id.clone(), h.info.status)));
     }
 
     /// Updates herald statuses based on heartbeat timeout.
-    /// Returns list of heralds that changed status to Disconnected.
-    pub async fn check_timeouts(&self, timeout_ms: i64) -> Vec<(String, HeraldStatus)> {
+    /// Returns the heralds that changed status to Disconnected.
+    pub async fn check_timeouts(&self, timeout_ms: i64) -> Vec<HeraldInfo> {
         let mut changed = Vec::new();
         let mut heralds = self.heralds.write().await;
         let now = OffsetDateTime::now_utc();
 
-        for (id, info) in heralds.iter_mut() {
+        for herald in heralds.values_mut() {
+            let info = &mut herald.info;
             // Skip already disconnected heralds
             if info.status == HeraldStatus::Disconnected {
                 continue;
@@ -80,10 +189,115 @@ impl HeraldRegistry {
 
             if elapsed_ms > timeout_ms {
                 info.status = HeraldStatus::Disconnected;
-                changed.push((id.clone(), HeraldStatus::Disconnected));
+                changed.push(info.clone());
             }
         }
 
         changed
     }
 }
+
+/// Random 256-bit token, hex encoded.
+fn generate_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Compares tokens in time independent of where they first differ.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str) -> RegisterHeraldRequest {
+        RegisterHeraldRequest { id: id.to_string(), description: None, schemas: HashMap::new() }
+    }
+
+    async fn registry(configured: &[(&str, &str)]) -> HeraldRegistry {
+        let store = SqliteHeraldStore::new(":memory:").await.unwrap();
+        let configured = configured
+            .iter()
+            .map(|(id, token)| (id.to_string(), token.to_string()))
+            .collect();
+        HeraldRegistry::new(store, configured).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_register_issues_token_required_afterwards() {
+        let registry = registry(&[]).await;
+        let (_, token) = registry.register(request("h"), None).await.unwrap();
+
+        assert!(registry.authenticate("h", Some(&token)).await);
+        assert!(!registry.authenticate("h", Some("wrong")).await);
+        assert!(!registry.authenticate("h", None).await);
+        assert!(!registry.authenticate("other", Some(&token)).await);
+
+        // Re-registering keeps the token, but only for its holder
+        assert!(matches!(
+            registry.register(request("h"), None).await,
+            Err(HeraldError::Unauthorized(_))
+        ));
+        let (_, again) = registry.register(request("h"), Some(&token)).await.unwrap();
+        assert_eq!(again, token);
+    }
+
+    #[tokio::test]
+    async fn test_configured_token_overrides_issued() {
+        let registry = registry(&[("kairos-herald", "secret")]).await;
+
+        // Accepted before registration
+        assert!(registry.authenticate("kairos-herald", Some("secret")).await);
+        assert!(
+            registry
+                .register(request("kairos-herald"), Some("guess"))
+                .await
+                .is_err()
+        );
+        let (_, token) = registry
+            .register(request("kairos-herald"), Some("secret"))
+            .await
+            .unwrap();
+        assert_eq!(token, "secret");
+    }
+
+    #[tokio::test]
+    async fn test_admin_token() {
+        let store = SqliteHeraldStore::new(":memory:").await.unwrap();
+        let admin = HeraldRegistry::new(store, HashMap::new())
+            .await
+            .unwrap()
+            .with_admin_token(Some("admin".to_string()));
+
+        assert!(admin.authenticate_admin(Some("admin")));
+        assert!(!admin.authenticate_admin(Some("guess")));
+        assert!(!admin.authenticate_admin(None));
+        // Not a herald token
+        assert!(!admin.authenticate("h", Some("admin")).await);
+
+        let unset = registry(&[]).await;
+        assert!(!unset.authenticate_admin(Some("admin")));
+    }
+
+    #[tokio::test]
+    async fn test_heralds_survive_restart() {
+        let store = SqliteHeraldStore::new(":memory:").await.unwrap();
+        let registry = HeraldRegistry::new(store.clone(), HashMap::new())
+            .await
+            .unwrap();
+        let (_, token) = registry.register(request("h"), None).await.unwrap();
+
+        let restarted = HeraldRegistry::new(store, HashMap::new()).await.unwrap();
+        assert!(restarted.get("h").await.is_some());
+        assert!(restarted.authenticate("h", Some(&token)).await);
+
+        assert!(restarted.unregister("h").await.unwrap());
+        assert!(restarted.get("h").await.is_none());
+    }
+}
@@ -4,10 +4,13 @@
 //! push events and consumers (like epha-ai) pull and acknowledge them.
 
 pub mod config;
+pub mod consumer;
 pub mod event;
 pub mod handlers;
 pub mod herald;
+pub mod metrics;
 pub mod queue;
+pub mod schema;
 pub mod server;
 
 pub use server::AppState;
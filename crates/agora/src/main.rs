@@ -1,8 +1,11 @@
 mod config;
+mod consumer;
 mod event;
 mod handlers;
 mod herald;
+mod metrics;
 mod queue;
+mod schema;
 mod server;
 
 use clap::Parser;
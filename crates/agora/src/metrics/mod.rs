@@ -0,0 +1,226 @@
+//! Prometheus metrics.
+
+use anyhow::Result;
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use crate::event::Event;
+use crate::herald::HeraldStatus;
+use crate::queue::QueueStats;
+use time::OffsetDateTime;
+
+/// Buckets for the number of redeliveries before an ack.
+const RETRY_BUCKETS: &[f64] = &[0.0, 1.0, 2.0, 3.0, 5.0, 10.0];
+
+/// Agora's metrics, exposed in the Prometheus text format at `/metrics`.
+///
+/// Counters are updated as events flow through the queue; gauges are
+/// refreshed from the queue and the herald registry on every scrape.
+pub struct Metrics {
+    registry: Registry,
+    events_pushed: IntCounterVec,
+    events_fetched: IntCounterVec,
+    events_acked: IntCounterVec,
+    ack_retries: HistogramVec,
+    events_pending: IntGaugeVec,
+    events_delivered: IntGaugeVec,
+    oldest_pending_age: IntGaugeVec,
+    dead_letters: IntGaugeVec,
+    herald_status: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let metrics = Self {
+            registry: Registry::new(),
+            events_pushed: IntCounterVec::new(
+                Opts::new("agora_events_pushed_total", "Events pushed by heralds"),
+                &["herald_id", "event_type"],
+            )?,
+            events_fetched: IntCounterVec::new(
+                Opts::new(
+                    "agora_events_fetched_total",
+                    "Event deliveries to consumers, including retries",
+                ),
+                &["consumer", "herald_id", "event_type"],
+            )?,
+            events_acked: IntCounterVec::new(
+                Opts::new("agora_events_acked_total", "Events acked by consumers"),
+                &["consumer", "herald_id", "event_type"],
+            )?,
+            ack_retries: HistogramVec::new(
+                HistogramOpts::new(
+                    "agora_event_retries",
+                    "Redeliveries an event needed before it was acked",
+                )
+                .buckets(RETRY_BUCKETS.to_vec()),
+                &["consumer"],
+            )?,
+            events_pending: IntGaugeVec::new(
+                Opts::new("agora_events_pending", "Events not yet delivered"),
+                &["consumer"],
+            )?,
+            events_delivered: IntGaugeVec::new(
+                Opts::new(
+                    "agora_events_delivered",
+                    "Events delivered and awaiting an ack",
+                ),
+                &["consumer"],
+            )?,
+            oldest_pending_age: IntGaugeVec::new(
+                Opts::new(
+                    "agora_oldest_pending_event_age_seconds",
+                    "How long the oldest undelivered event has been deliverable",
+                ),
+                &["consumer"],
+            )?,
+            dead_letters: IntGaugeVec::new(
+                Opts::new("agora_dead_letters", "Events in the dead-letter table"),
+                &["herald_id"],
+            )?,
+            herald_status: IntGaugeVec::new(
+                Opts::new(
+                    "agora_herald_status",
+                    "1 for the herald's current status, 0 otherwise",
+                ),
+                &["herald_id", "status"],
+            )?,
+        };
+
+        for collector in [
+            Box::new(metrics.events_pushed.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(metrics.events_fetched.clone()),
+            Box::new(metrics.events_acked.clone()),
+            Box::new(metrics.ack_retries.clone()),
+            Box::new(metrics.events_pending.clone()),
+            Box::new(metrics.events_delivered.clone()),
+            Box::new(metrics.oldest_pending_age.clone()),
+            Box::new(metrics.dead_letters.clone()),
+            Box::new(metrics.herald_status.clone()),
+        ] {
+            metrics.registry.register(collector)?;
+        }
+
+        Ok(metrics)
+    }
+
+    pub fn event_pushed(&self, event: &Event) {
+        self.events_pushed
+            .with_label_values(&[&event.herald_id, &event.event_type])
+            .inc();
+    }
+
+    pub fn event_fetched(&self, consumer: &str, event: &Event) {
+        self.events_fetched
+            .with_label_values(&[consumer, &event.herald_id, &event.event_type])
+            .inc();
+    }
+
+    pub fn event_acked(&self, consumer: &str, event: &Event, retry_count: u32) {
+        self.events_acked
+            .with_label_values(&[consumer, &event.herald_id, &event.event_type])
+            .inc();
+        self.ack_retries
+            .with_label_values(&[consumer])
+            .observe(retry_count as f64);
+    }
+
+    /// Replaces the queue gauges with each consumer's current figures.
+    pub fn set_queues(&self, stats: &[(String, QueueStats)], now: OffsetDateTime) {
+        self.events_pending.reset();
+        self.events_delivered.reset();
+        self.oldest_pending_age.reset();
+        for (consumer, stats) in stats {
+            let age = stats
+                .oldest_pending
+                .map_or(0, |since| (now - since).whole_seconds().max(0));
+            self.events_pending
+                .with_label_values(&[consumer])
+                .set(stats.pending as i64);
+            self.events_delivered
+                .with_label_values(&[consumer])
+                .set(stats.delivered as i64);
+            self.oldest_pending_age
+                .with_label_values(&[consumer])
+                .set(age);
+        }
+    }
+
+    /// Replaces the dead-letter gauges with per-herald counts.
+    pub fn set_dead_letters(&self, counts: impl IntoIterator<Item = (String, u64)>) {
+        self.dead_letters.reset();
+        for (herald_id, count) in counts {
+            self.dead_letters
+                .with_label_values(&[&herald_id])
+                .set(count as i64);
+        }
+    }
+
+    /// Replaces the herald status gauges.
+    pub fn set_herald_statuses(&self, heralds: impl IntoIterator<Item = (String, HeraldStatus)>) {
+        self.herald_status.reset();
+        for (herald_id, status) in heralds {
+            for candidate in [HeraldStatus::Active, HeraldStatus::Disconnected] {
+                self.herald_status
+                    .with_label_values(&[herald_id.as_str(), status_label(candidate)])
+                    .set((candidate == status) as i64);
+            }
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text format.
+    pub fn render(&self) -> Result<String> {
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+}
+
+fn status_label(status: HeraldStatus) -> &'static str {
+    match status {
+        HeraldStatus::Active => "active",
+        HeraldStatus::Disconnected => "disconnected",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{EventPriority, EventStatus};
+
+    #[test]
+    fn test_render_includes_labelled_series() {
+        let metrics = Metrics::new().unwrap();
+        let event = Event {
+            id: 1,
+            event_type: "chat.message".to_string(),
+            herald_id: "atrium".to_string(),
+            payload: serde_json::json!({}),
+            priority: EventPriority::Normal,
+            timestamp: OffsetDateTime::now_utc(),
+            status: EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
+        };
+        metrics.event_pushed(&event);
+        metrics.event_acked("default", &event, 2);
+        let now = OffsetDateTime::now_utc();
+        let stats = QueueStats {
+            pending: 3,
+            delivered: 1,
+            oldest_pending: Some(now - time::Duration::seconds(90)),
+        };
+        metrics.set_queues(&[("default".to_string(), stats)], now);
+        metrics.set_herald_statuses([("atrium".to_string(), HeraldStatus::Disconnected)]);
+
+        let text = metrics.render().unwrap();
+        assert!(text.contains(
+            r#"agora_events_pushed_total{event_type="chat.message",herald_id="atrium"} 1"#
+        ));
+        assert!(text.contains(r#"agora_event_retries_bucket{consumer="default",le="2"} 1"#));
+        assert!(text.contains(r#"agora_oldest_pending_event_age_seconds{consumer="default"} 90"#));
+        assert!(text.contains(r#"agora_herald_status{herald_id="atrium",status="active"} 0"#));
+        assert!(
+            text.contains(r#"agora_herald_status{herald_id="atrium",status="disconnected"} 1"#)
+        );
+    }
+}
@@ -0,0 +1,867 @@
+//! In-memory event storage, for tests and ephemeral deployments.
+
+use super::store::{
+    BatchAckOutcome, CoalesceTarget, DEFAULT_IDEMPOTENCY_WINDOW, EventStore, PendingAck,
+    PushOutcome,
+};
+use crate::consumer::{ConsumerInfo, DEFAULT_CONSUMER, UpsertConsumerRequest};
+use crate::event::{
+    ArchivedEvent, BatchAckMode, CreateEventRequest, DeadLetter, Event, EventHistoryQuery, EventId,
+    EventStatus,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Event storage that lives only as long as the process.
+///
+/// Behaves like [`SqliteEventStore`](super::SqliteEventStore), including the
+/// `default` consumer, idempotency windows and archive retention, but nothing
+/// survives a restart.
+pub struct MemoryEventStore {
+    inner: Mutex<Inner>,
+    idempotency_window: Duration,
+}
+
+#[derive(Default)]
+struct Inner {
+    last_id: EventId,
+    events: BTreeMap<EventId, QueuedEvent>,
+    /// Keyed by (herald_id, idempotency_key).
+    idempotency_keys: HashMap<(String, String), RememberedKey>,
+    /// Keyed by (event id, consumer).
+    archive: HashMap<(EventId, String), ArchivedEvent>,
+    /// Keyed by (consumer, event id).
+    dead_letters: HashMap<(String, EventId), DeadLettered>,
+    consumers: BTreeMap<String, ConsumerInfo>,
+}
+
+struct QueuedEvent {
+    event: Event,
+    coalesce_key: Option<String>,
+    /// Payloads merged into the event's array payload, see [`CoalesceTarget`].
+    coalesced: usize,
+    /// Delivery count per consumer that still holds the event.
+    deliveries: HashMap<String, u32>,
+}
+
+/// A dead letter, with the coalescing state its event is requeued with.
+struct DeadLettered {
+    dead_letter: DeadLetter,
+    coalesce_key: Option<String>,
+    coalesced: usize,
+}
+
+struct RememberedKey {
+    event: Event,
+    created_at: OffsetDateTime,
+}
+
+impl Default for MemoryEventStore {
+    fn default() -> Self {
+        let mut inner = Inner::default();
+        inner.consumers.insert(
+            DEFAULT_CONSUMER.to_string(),
+            ConsumerInfo {
+                name: DEFAULT_CONSUMER.to_string(),
+                event_types: Vec::new(),
+                herald_ids: Vec::new(),
+                created_at: OffsetDateTime::now_utc(),
+                pending: 0,
+                delivered: 0,
+            },
+        );
+
+        Self { inner: Mutex::new(inner), idempotency_window: DEFAULT_IDEMPOTENCY_WINDOW }
+    }
+}
+
+impl MemoryEventStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long an idempotency key is remembered after its first push.
+    pub fn with_idempotency_window(mut self, window: Duration) -> Self {
+        self.idempotency_window = window;
+        self
+    }
+
+    /// Records the request's idempotency key, if any, as belonging to
+    /// `event_id`, unless it already belongs to an event; that one is returned.
+    fn remember_idempotency_key(
+        &self,
+        inner: &mut Inner,
+        req: &CreateEventRequest,
+        event_id: EventId,
+    ) -> Option<Event> {
+        let key = req.idempotency_key.as_ref()?;
+
+        let now = OffsetDateTime::now_utc();
+        let cutoff = now - self.idempotency_window;
+        inner
+            .idempotency_keys
+            .retain(|_, remembered| remembered.created_at >= cutoff);
+        let slot = (req.herald_id.clone(), key.clone());
+        if let Some(remembered) = inner.idempotency_keys.get(&slot) {
+            return Some(inner.remembered_event(remembered));
+        }
+
+        let event = Event {
+            id: event_id,
+            event_type: req.event_type.clone(),
+            herald_id: req.herald_id.clone(),
+            payload: req.payload.clone(),
+            priority: req.priority,
+            timestamp: req.timestamp,
+            status: EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
+        };
+        inner
+            .idempotency_keys
+            .insert(slot, RememberedKey { event, created_at: now });
+        None
+    }
+}
+
+impl Inner {
+    /// The event an idempotency key was taken by: as it is now while still
+    /// queued, since merges may have changed it, and as first pushed after.
+    fn remembered_event(&self, remembered: &RememberedKey) -> Event {
+        self.events
+            .get(&remembered.event.id)
+            .map_or_else(|| remembered.event.clone(), |queued| queued.event.clone())
+    }
+
+    /// Drops one consumer's delivery, then the event if no other consumer needs it.
+    fn delete_delivery(&mut self, consumer: &str, id: EventId) -> bool {
+        let Some(queued) = self.events.get_mut(&id) else {
+            return false;
+        };
+        if queued.deliveries.remove(consumer).is_none() {
+            return false;
+        }
+        if queued.deliveries.is_empty() {
+            self.events.remove(&id);
+        }
+        true
+    }
+
+    /// Copies one consumer's delivery to the archive, then drops it.
+    fn archive_delivery(&mut self, consumer: &str, ack: &PendingAck, now: OffsetDateTime) -> bool {
+        let Some(event) = self
+            .events
+            .get(&ack.id)
+            .filter(|queued| queued.deliveries.contains_key(consumer))
+            .map(|queued| queued.event.clone())
+        else {
+            return false;
+        };
+
+        self.archive.insert(
+            (ack.id, consumer.to_string()),
+            ArchivedEvent {
+                event: Event { status: EventStatus::Acked, ..event },
+                consumer: consumer.to_string(),
+                delivered_at: ack.delivered_at,
+                acked_at: now,
+                retry_count: ack.retry_count,
+            },
+        );
+        self.delete_delivery(consumer, ack.id)
+    }
+
+    /// Drops archived events acked longer than `retention` ago.
+    fn prune_archive(&mut self, retention: Duration, now: OffsetDateTime) {
+        let cutoff = now - retention;
+        self.archive
+            .retain(|_, archived| archived.acked_at >= cutoff);
+    }
+}
+
+fn dead_letter_matches(
+    dead_letter: &DeadLetter,
+    consumer: Option<&str>,
+    herald_id: Option<&str>,
+    event_type: Option<&str>,
+) -> bool {
+    consumer.is_none_or(|c| dead_letter.consumer == c)
+        && herald_id.is_none_or(|h| dead_letter.event.herald_id == h)
+        && event_type.is_none_or(|t| dead_letter.event.event_type == t)
+}
+
+fn archive_matches(archived: &ArchivedEvent, query: &EventHistoryQuery) -> bool {
+    let event = &archived.event;
+    query
+        .herald_id
+        .as_ref()
+        .is_none_or(|h| &event.herald_id == h)
+        && query
+            .event_type
+            .as_ref()
+            .is_none_or(|t| &event.event_type == t)
+        && query.priority.is_none_or(|p| event.priority == p)
+        && query
+            .consumer
+            .as_ref()
+            .is_none_or(|c| &archived.consumer == c)
+        && query.since.is_none_or(|since| event.timestamp >= since)
+        && query.until.is_none_or(|until| event.timestamp < until)
+}
+
+#[async_trait]
+impl EventStore for MemoryEventStore {
+    async fn find_by_idempotency_key(
+        &self,
+        herald_id: &str,
+        idempotency_key: &str,
+    ) -> Result<Option<Event>> {
+        let inner = self.inner.lock().unwrap();
+        let cutoff = OffsetDateTime::now_utc() - self.idempotency_window;
+
+        Ok(inner
+            .idempotency_keys
+            .get(&(herald_id.to_string(), idempotency_key.to_string()))
+            .filter(|remembered| remembered.created_at >= cutoff)
+            .map(|remembered| inner.remembered_event(remembered)))
+    }
+
+    async fn insert(&self, req: CreateEventRequest, consumers: &[String]) -> Result<PushOutcome> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.last_id + 1;
+        if let Some(original) = self.remember_idempotency_key(&mut inner, &req, id) {
+            return Ok(PushOutcome::Duplicate(original));
+        }
+        inner.last_id = id;
+
+        let event = Event {
+            id,
+            event_type: req.event_type,
+            herald_id: req.herald_id,
+            payload: req.payload,
+            priority: req.priority,
+            timestamp: req.timestamp,
+            status: EventStatus::Pending,
+            deliver_after: req.deliver_after,
+            expires_at: req.expires_at,
+        };
+        // No consumer wants it: the event still gets an ID, but is not queued
+        if !consumers.is_empty() {
+            inner.events.insert(
+                id,
+                QueuedEvent {
+                    event: event.clone(),
+                    coalesce_key: req.coalesce_key,
+                    coalesced: 0,
+                    deliveries: consumers.iter().map(|c| (c.clone(), 0)).collect(),
+                },
+            );
+        }
+
+        Ok(PushOutcome::Stored(event))
+    }
+
+    async fn find_coalesce_target(
+        &self,
+        herald_id: &str,
+        event_type: &str,
+        coalesce_key: &str,
+    ) -> Result<Option<CoalesceTarget>> {
+        let inner = self.inner.lock().unwrap();
+
+        Ok(inner
+            .events
+            .values()
+            .rev()
+            .find(|queued| {
+                queued.event.herald_id == herald_id
+                    && queued.event.event_type == event_type
+                    && queued.coalesce_key.as_deref() == Some(coalesce_key)
+            })
+            .map(|queued| CoalesceTarget { id: queued.event.id, merged: queued.coalesced }))
+    }
+
+    async fn coalesce(
+        &self,
+        merged: &Event,
+        count: usize,
+        req: &CreateEventRequest,
+    ) -> Result<PushOutcome> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(original) = self.remember_idempotency_key(&mut inner, req, merged.id) {
+            return Ok(PushOutcome::Duplicate(original));
+        }
+        if let Some(queued) = inner.events.get_mut(&merged.id) {
+            queued.event.payload = merged.payload.clone();
+            queued.event.priority = merged.priority;
+            queued.coalesced = count;
+        }
+
+        Ok(PushOutcome::Stored(merged.clone()))
+    }
+
+    async fn load_all(&self) -> Result<Vec<(String, Event, u32)>> {
+        let inner = self.inner.lock().unwrap();
+        let mut deliveries: Vec<(String, Event, u32)> = inner
+            .events
+            .values()
+            .flat_map(|queued| {
+                queued
+                    .deliveries
+                    .iter()
+                    .map(|(consumer, &count)| (consumer.clone(), queued.event.clone(), count))
+            })
+            .collect();
+        deliveries.sort_by(|a, b| (&a.0, a.1.id).cmp(&(&b.0, b.1.id)));
+
+        Ok(deliveries)
+    }
+
+    async fn record_deliveries(&self, consumer: &str, ids: &[EventId]) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        for id in ids {
+            if let Some(count) = inner
+                .events
+                .get_mut(id)
+                .and_then(|queued| queued.deliveries.get_mut(consumer))
+            {
+                *count += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, consumer: &str, id: EventId) -> Result<bool> {
+        Ok(self.inner.lock().unwrap().delete_delivery(consumer, id))
+    }
+
+    async fn archive(
+        &self,
+        consumer: &str,
+        id: EventId,
+        delivered_at: Option<OffsetDateTime>,
+        retry_count: u32,
+        retention: Duration,
+    ) -> Result<bool> {
+        let now = OffsetDateTime::now_utc();
+        let mut inner = self.inner.lock().unwrap();
+        inner.prune_archive(retention, now);
+        let ack = PendingAck { id, delivered_at, retry_count };
+
+        Ok(inner.archive_delivery(consumer, &ack, now))
+    }
+
+    async fn ack_batch(
+        &self,
+        consumer: &str,
+        acks: &[PendingAck],
+        archive_retention: Option<Duration>,
+        mode: BatchAckMode,
+    ) -> Result<BatchAckOutcome> {
+        let now = OffsetDateTime::now_utc();
+        let mut inner = self.inner.lock().unwrap();
+        let (present, missing): (Vec<&PendingAck>, Vec<_>) = acks.iter().partition(|ack| {
+            inner
+                .events
+                .get(&ack.id)
+                .is_some_and(|queued| queued.deliveries.contains_key(consumer))
+        });
+        let mut outcome = BatchAckOutcome {
+            missing: missing.iter().map(|ack| ack.id).collect(),
+            ..Default::default()
+        };
+        if mode == BatchAckMode::AllOrNothing && !outcome.missing.is_empty() {
+            return Ok(outcome);
+        }
+
+        if let Some(retention) = archive_retention {
+            inner.prune_archive(retention, now);
+        }
+        for ack in present {
+            match archive_retention {
+                Some(_) => inner.archive_delivery(consumer, ack, now),
+                None => inner.delete_delivery(consumer, ack.id),
+            };
+            outcome.acked.push(ack.id);
+        }
+
+        Ok(outcome)
+    }
+
+    async fn list_archive(
+        &self,
+        query: &EventHistoryQuery,
+        limit: u32,
+    ) -> Result<(Vec<ArchivedEvent>, usize)> {
+        let inner = self.inner.lock().unwrap();
+        let mut matching: Vec<&ArchivedEvent> = inner
+            .archive
+            .values()
+            .filter(|archived| archive_matches(archived, query))
+            .collect();
+        matching.sort_by_key(|a| (Reverse(a.event.timestamp), Reverse(a.event.id), &a.consumer));
+
+        let total = matching.len();
+        let events = matching
+            .into_iter()
+            .skip(query.offset.unwrap_or(0) as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        Ok((events, total))
+    }
+
+    async fn dead_letter(
+        &self,
+        consumer: &str,
+        event: &Event,
+        delivery_count: u32,
+        reason: &str,
+    ) -> Result<DeadLetter> {
+        let mut inner = self.inner.lock().unwrap();
+        let dead_letter = DeadLetter {
+            event: Event { status: EventStatus::Delivered, ..event.clone() },
+            consumer: consumer.to_string(),
+            delivery_count,
+            reason: reason.to_string(),
+            dead_lettered_at: OffsetDateTime::now_utc(),
+        };
+        let (coalesce_key, coalesced) = inner.events.get(&event.id).map_or((None, 0), |queued| {
+            (queued.coalesce_key.clone(), queued.coalesced)
+        });
+        inner.dead_letters.insert(
+            (consumer.to_string(), event.id),
+            DeadLettered { dead_letter: dead_letter.clone(), coalesce_key, coalesced },
+        );
+        inner.delete_delivery(consumer, event.id);
+
+        Ok(dead_letter)
+    }
+
+    async fn list_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<DeadLetter>, usize)> {
+        let inner = self.inner.lock().unwrap();
+        let mut matching: Vec<&DeadLetter> = inner
+            .dead_letters
+            .values()
+            .map(|d| &d.dead_letter)
+            .filter(|d| dead_letter_matches(d, consumer, herald_id, event_type))
+            .collect();
+        matching.sort_by_key(|d| (Reverse(d.dead_lettered_at), Reverse(d.event.id)));
+
+        let total = matching.len();
+        let dead_letters = matching.into_iter().take(limit as usize).cloned().collect();
+        Ok((dead_letters, total))
+    }
+
+    async fn get_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<DeadLetter>> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner
+            .dead_letters
+            .get(&(consumer.to_string(), id))
+            .map(|d| d.dead_letter.clone()))
+    }
+
+    async fn requeue_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<Event>> {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(dead_lettered) = inner.dead_letters.remove(&(consumer.to_string(), id)) else {
+            return Ok(None);
+        };
+        let event = Event { status: EventStatus::Pending, ..dead_lettered.dead_letter.event };
+
+        // Other consumers may still hold the event
+        inner
+            .events
+            .entry(id)
+            .or_insert_with(|| QueuedEvent {
+                event: event.clone(),
+                coalesce_key: dead_lettered.coalesce_key,
+                coalesced: dead_lettered.coalesced,
+                deliveries: HashMap::new(),
+            })
+            .deliveries
+            .insert(consumer.to_string(), 0);
+
+        Ok(Some(event))
+    }
+
+    async fn delete_dead_letter(&self, consumer: &str, id: EventId) -> Result<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        Ok(inner
+            .dead_letters
+            .remove(&(consumer.to_string(), id))
+            .is_some())
+    }
+
+    async fn purge_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+    ) -> Result<u64> {
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.dead_letters.len();
+        inner
+            .dead_letters
+            .retain(|_, d| !dead_letter_matches(&d.dead_letter, consumer, herald_id, event_type));
+
+        Ok((before - inner.dead_letters.len()) as u64)
+    }
+
+    async fn dead_letter_counts(&self) -> Result<HashMap<String, u64>> {
+        let inner = self.inner.lock().unwrap();
+        let mut counts = HashMap::new();
+        for dead_lettered in inner.dead_letters.values() {
+            *counts
+                .entry(dead_lettered.dead_letter.event.herald_id.clone())
+                .or_default() += 1;
+        }
+
+        Ok(counts)
+    }
+
+    async fn load_consumers(&self) -> Result<Vec<ConsumerInfo>> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.consumers.values().cloned().collect())
+    }
+
+    async fn upsert_consumer(
+        &self,
+        name: &str,
+        request: &UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo> {
+        let mut inner = self.inner.lock().unwrap();
+        let consumer = inner
+            .consumers
+            .entry(name.to_string())
+            .or_insert_with(|| ConsumerInfo {
+                name: name.to_string(),
+                event_types: Vec::new(),
+                herald_ids: Vec::new(),
+                created_at: OffsetDateTime::now_utc(),
+                pending: 0,
+                delivered: 0,
+            });
+        consumer.event_types = request.event_types.clone();
+        consumer.herald_ids = request.herald_ids.clone();
+
+        Ok(consumer.clone())
+    }
+
+    async fn delete_consumer(&self, name: &str) -> Result<bool> {
+        let mut inner = self.inner.lock().unwrap();
+        let deleted = inner.consumers.remove(name).is_some();
+
+        inner.events.retain(|_, queued| {
+            queued.deliveries.remove(name);
+            !queued.deliveries.is_empty()
+        });
+        inner
+            .dead_letters
+            .retain(|(consumer, _), _| consumer != name);
+
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventPriority;
+
+    fn test_request(herald_id: &str, event_type: &str) -> CreateEventRequest {
+        CreateEventRequest {
+            event_type: event_type.to_string(),
+            herald_id: herald_id.to_string(),
+            priority: EventPriority::Normal,
+            payload: serde_json::json!({}),
+            timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        }
+    }
+
+    fn both_consumers() -> Vec<String> {
+        vec![DEFAULT_CONSUMER.to_string(), "observer".to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_event_deleted_once_every_consumer_acks() {
+        let store = MemoryEventStore::new();
+        let event = store
+            .insert(test_request("h", "t"), &both_consumers())
+            .await
+            .unwrap()
+            .into_event();
+        store
+            .record_deliveries(DEFAULT_CONSUMER, &[event.id])
+            .await
+            .unwrap();
+
+        let loaded = store.load_all().await.unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].0, DEFAULT_CONSUMER);
+        assert_eq!(loaded[0].2, 1);
+
+        assert!(store.delete(DEFAULT_CONSUMER, event.id).await.unwrap());
+        assert!(!store.delete(DEFAULT_CONSUMER, event.id).await.unwrap());
+        assert_eq!(store.load_all().await.unwrap().len(), 1);
+        assert!(store.delete("observer", event.id).await.unwrap());
+        assert!(store.load_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_requeue_and_purge() {
+        let store = MemoryEventStore::new();
+        let consumers = vec![DEFAULT_CONSUMER.to_string()];
+        let expires_at = OffsetDateTime::now_utc() + time::Duration::hours(1);
+        let event = store
+            .insert(
+                CreateEventRequest {
+                    expires_at: Some(expires_at),
+                    coalesce_key: Some("alice".to_string()),
+                    ..test_request("atrium", "chat.message")
+                },
+                &consumers,
+            )
+            .await
+            .unwrap()
+            .into_event();
+        let other = store
+            .insert(test_request("kairos", "kairos.trigger"), &consumers)
+            .await
+            .unwrap()
+            .into_event();
+
+        store
+            .dead_letter(DEFAULT_CONSUMER, &event, 3, "max_retries")
+            .await
+            .unwrap();
+        store
+            .dead_letter(DEFAULT_CONSUMER, &other, 3, "max_retries")
+            .await
+            .unwrap();
+        assert!(store.load_all().await.unwrap().is_empty());
+        assert_eq!(store.dead_letter_counts().await.unwrap()["atrium"], 1);
+
+        let (dead_letters, total) = store
+            .list_dead_letters(None, Some("atrium"), None, 10)
+            .await
+            .unwrap();
+        assert_eq!((dead_letters.len(), total), (1, 1));
+
+        let requeued = store
+            .requeue_dead_letter(DEFAULT_CONSUMER, event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(requeued.id, event.id);
+        // Requeued with its expiry and coalescing
+        assert_eq!(requeued.expires_at, Some(expires_at));
+        assert_eq!(store.load_all().await.unwrap()[0].2, 0);
+        let target = store
+            .find_coalesce_target("atrium", "chat.message", "alice")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(target.id, event.id);
+
+        assert_eq!(
+            store
+                .purge_dead_letters(Some(DEFAULT_CONSUMER), None, None)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(store.dead_letter_counts().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_archive_and_history() {
+        let store = MemoryEventStore::new();
+        let consumers = vec![DEFAULT_CONSUMER.to_string()];
+        let chat = store
+            .insert(test_request("atrium", "chat.message"), &consumers)
+            .await
+            .unwrap()
+            .into_event();
+        let timer = store
+            .insert(test_request("kairos", "kairos.trigger"), &consumers)
+            .await
+            .unwrap()
+            .into_event();
+        let retention = Duration::from_secs(3600);
+
+        for id in [chat.id, timer.id] {
+            assert!(
+                store
+                    .archive(DEFAULT_CONSUMER, id, None, 1, retention)
+                    .await
+                    .unwrap()
+            );
+        }
+        assert!(
+            !store
+                .archive(DEFAULT_CONSUMER, chat.id, None, 1, retention)
+                .await
+                .unwrap()
+        );
+
+        let query =
+            EventHistoryQuery { herald_id: Some("kairos".to_string()), ..Default::default() };
+        let (events, total) = store.list_archive(&query, 10).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].event.id, timer.id);
+        assert_eq!(events[0].event.status, EventStatus::Acked);
+
+        let page = EventHistoryQuery { offset: Some(1), ..Default::default() };
+        let (events, total) = store.list_archive(&page, 10).await.unwrap();
+        assert_eq!((events.len(), total), (1, 2));
+        assert_eq!(events[0].event.id, chat.id);
+    }
+
+    #[tokio::test]
+    async fn test_ack_batch_modes() {
+        let store = MemoryEventStore::new();
+        let consumers = vec![DEFAULT_CONSUMER.to_string()];
+        let a = store
+            .insert(test_request("h", "t"), &consumers)
+            .await
+            .unwrap()
+            .into_event();
+        let b = store
+            .insert(test_request("h", "t"), &consumers)
+            .await
+            .unwrap()
+            .into_event();
+        let acks = |ids: &[EventId]| -> Vec<PendingAck> {
+            ids.iter()
+                .map(|&id| PendingAck { id, delivered_at: None, retry_count: 0 })
+                .collect()
+        };
+
+        let outcome = store
+            .ack_batch(
+                DEFAULT_CONSUMER,
+                &acks(&[a.id, 999]),
+                None,
+                BatchAckMode::AllOrNothing,
+            )
+            .await
+            .unwrap();
+        assert!(outcome.acked.is_empty());
+        assert_eq!(outcome.missing, vec![999]);
+        assert_eq!(store.load_all().await.unwrap().len(), 2);
+
+        let outcome = store
+            .ack_batch(
+                DEFAULT_CONSUMER,
+                &acks(&[a.id, 999]),
+                None,
+                BatchAckMode::BestEffort,
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.acked, vec![a.id]);
+        assert_eq!(outcome.missing, vec![999]);
+        assert_eq!(store.load_all().await.unwrap()[0].1.id, b.id);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_and_coalescing() {
+        let store = MemoryEventStore::new().with_idempotency_window(Duration::from_secs(60));
+        let keyed = CreateEventRequest {
+            idempotency_key: Some("msg-1".to_string()),
+            coalesce_key: Some("alice".to_string()),
+            ..test_request("atrium", "chat.message")
+        };
+        let event = store
+            .insert(keyed.clone(), &[DEFAULT_CONSUMER.to_string()])
+            .await
+            .unwrap()
+            .into_event();
+
+        let original = store
+            .find_by_idempotency_key("atrium", "msg-1")
+            .await
+            .unwrap();
+        assert_eq!(original.map(|e| e.id), Some(event.id));
+        assert!(
+            store
+                .find_by_idempotency_key("kairos", "msg-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let target = store
+            .find_coalesce_target("atrium", "chat.message", "alice")
+            .await
+            .unwrap();
+        assert_eq!(target, Some(CoalesceTarget { id: event.id, merged: 0 }));
+        let merged = Event { payload: serde_json::json!([1, 2]), ..event };
+        // The key is taken by the event itself, so nothing is merged
+        let outcome = store.coalesce(&merged, 2, &keyed).await.unwrap();
+        assert!(matches!(outcome, PushOutcome::Duplicate(e) if e.id == event.id));
+        assert_eq!(
+            store.load_all().await.unwrap()[0].1.payload,
+            serde_json::json!({})
+        );
+
+        let next = CreateEventRequest { idempotency_key: Some("msg-2".to_string()), ..keyed };
+        store.coalesce(&merged, 2, &next).await.unwrap();
+        assert_eq!(
+            store.load_all().await.unwrap()[0].1.payload,
+            serde_json::json!([1, 2])
+        );
+        let target = store
+            .find_coalesce_target("atrium", "chat.message", "alice")
+            .await
+            .unwrap();
+        assert_eq!(target.map(|t| t.merged), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_consumers() {
+        let store = MemoryEventStore::new();
+        assert_eq!(
+            store.load_consumers().await.unwrap()[0].name,
+            DEFAULT_CONSUMER
+        );
+
+        let request = UpsertConsumerRequest {
+            event_types: vec!["chat.*".to_string()],
+            herald_ids: Vec::new(),
+        };
+        let created = store.upsert_consumer("observer", &request).await.unwrap();
+        let updated = store
+            .upsert_consumer("observer", &UpsertConsumerRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(created.created_at, updated.created_at);
+        assert!(updated.event_types.is_empty());
+
+        store
+            .insert(test_request("h", "t"), &["observer".to_string()])
+            .await
+            .unwrap()
+            .into_event();
+        assert!(store.delete_consumer("observer").await.unwrap());
+        assert!(!store.delete_consumer("observer").await.unwrap());
+        assert!(store.load_all().await.unwrap().is_empty());
+        assert_eq!(store.load_consumers().await.unwrap().len(), 1);
+    }
+}
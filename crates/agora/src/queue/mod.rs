@@ -1,77 +1,616 @@
 //! Event queue implementations.
 
+mod memory;
 mod sqlite;
 mod state;
+mod store;
 
+pub use memory::MemoryEventStore;
 pub use sqlite::SqliteEventStore;
-pub use state::DeliveryCache;
+pub use state::{DeliveryCache, QueueStats};
+pub use store::{BatchAckOutcome, EventStore, PushOutcome};
 
-use crate::config::RetryConfig;
-use crate::event::{CreateEventRequest, Event, EventId, EventStatus};
+use crate::config::{CoalesceRule, ExpirePolicy, RetryConfig};
+use crate::consumer::{ConsumerInfo, UpsertConsumerRequest, consumer_matches};
+use crate::event::{
+    ArchivedEvent, BatchAckMode, CreateEventRequest, DeadLetter, Event, EventHistoryQuery, EventId,
+    EventPriority, EventStatus, QueuedEvent, QueuedEventsQuery,
+};
+use crate::herald::{HeraldLimiter, LimitError};
+use crate::metrics::Metrics;
 use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use store::PendingAck;
+use time::OffsetDateTime;
+use tokio::sync::{Notify, RwLock, broadcast};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+/// Pushed events buffered per watcher before a slow one starts missing them.
+const WATCH_BUFFER: usize = 256;
 
 /// Combined event queue with persistence and delivery tracking.
+///
+/// Each pushed event is queued for every consumer whose filters match it,
+/// and each consumer fetches and acks its copy independently.
 pub struct EventQueue {
-    store: SqliteEventStore,
+    store: Box<dyn EventStore>,
     cache: DeliveryCache,
     retry_config: RetryConfig,
+    on_expire: ExpirePolicy,
+    /// Registered consumers by name.
+    consumers: RwLock<HashMap<String, ConsumerInfo>>,
+    /// Wakes waiting fetches when new events become pending.
+    available: Notify,
+    /// Every stored or merged event, for watchers that queue nothing.
+    pushed: broadcast::Sender<Event>,
+    /// How long acked events are kept in the archive; None disables archiving.
+    archive_retention: Option<Duration>,
+    /// Coalescing rules by event type.
+    coalesce_rules: HashMap<String, CoalesceRule>,
+    /// Limits on each herald's pushes; None admits everything.
+    limiter: Option<Arc<HeraldLimiter>>,
+    metrics: Arc<Metrics>,
 }
 
 impl EventQueue {
-    pub async fn new(database_path: &str, retry_config: RetryConfig) -> Result<Self> {
-        let store = SqliteEventStore::new(database_path).await?;
+    pub async fn new(
+        store: Box<dyn EventStore>,
+        retry_config: RetryConfig,
+        on_expire: ExpirePolicy,
+        archive_retention: Option<Duration>,
+    ) -> Result<Self> {
         let cache = DeliveryCache::new();
 
+        let mut consumers = HashMap::new();
+        for consumer in store.load_consumers().await? {
+            cache.add_consumer(&consumer.name).await;
+            consumers.insert(consumer.name.clone(), consumer);
+        }
+
         // Load pending events from SQLite
         let pending = store.load_all().await?;
         cache.load_pending(pending).await;
 
-        Ok(Self { store, cache, retry_config })
+        Ok(Self {
+            store,
+            cache,
+            retry_config,
+            on_expire,
+            consumers: RwLock::new(consumers),
+            available: Notify::new(),
+            pushed: broadcast::channel(WATCH_BUFFER).0,
+            archive_retention,
+            coalesce_rules: HashMap::new(),
+            limiter: None,
+            metrics: Arc::new(Metrics::new()?),
+        })
+    }
+
+    /// Sets the coalescing rules, keyed by event type.
+    pub fn with_coalesce_rules(mut self, rules: HashMap<String, CoalesceRule>) -> Self {
+        self.coalesce_rules = rules;
+        self
+    }
+
+    /// Holds pushes to each herald's limits.
+    pub fn with_limiter(mut self, limiter: Arc<HeraldLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
     }
 
-    /// Create new event.
-    pub async fn push(&self, req: CreateEventRequest) -> Result<Event> {
-        let event = self.store.insert(req).await?;
-        self.cache.add_pending(event.clone()).await;
+    /// Records queue activity in the given metrics instead of private ones.
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Closes the store, on shutdown, once nothing else will use the queue.
+    pub async fn close(&self) {
+        self.store.close().await;
+    }
+
+    /// Create new event, queued for every consumer whose filters match it.
+    ///
+    /// If the herald already pushed an event with the same idempotency key
+    /// within the window, nothing is queued and the original event is returned.
+    ///
+    /// If the event type has a coalescing rule and the request a
+    /// `coalesce_key`, the payload is merged into the herald's latest event
+    /// with the same type and key instead, if no consumer has been delivered
+    /// that one yet. The merged event is returned; its payload becomes an
+    /// array of the merged payloads. An event nothing merged into keeps its
+    /// payload as pushed.
+    ///
+    /// Pushes other than duplicates are held to the herald's limits (see
+    /// [`EventQueue::with_limiter`]): merges count towards its payload size
+    /// and rate limits like new events, while `max_pending` only applies to
+    /// pushes that add an event. Fails with the limit that refused the push,
+    /// or [`LimitError::Storage`].
+    pub async fn push(&self, req: CreateEventRequest) -> Result<Event, LimitError> {
+        if let Some(key) = &req.idempotency_key
+            && let Some(original) = self
+                .store
+                .find_by_idempotency_key(&req.herald_id, key)
+                .await?
+        {
+            return Ok(Self::duplicate(&req.herald_id, Some(key), original));
+        }
+
+        // Held until the push is queued or merged, so the next push counts it
+        let mut admission = match &self.limiter {
+            Some(limiter) => Some(limiter.admit(&req).await?),
+            None => None,
+        };
+
+        // Held across the insert so a consumer cannot be deleted halfway
+        let consumers = self.consumers.read().await;
+        let targets: Vec<String> = consumers
+            .values()
+            .filter(|c| consumer_matches(c, &req.event_type, &req.herald_id))
+            .map(|c| c.name.clone())
+            .collect();
+
+        let coalesce = req
+            .coalesce_key
+            .clone()
+            .zip(self.coalesce_rules.get(&req.event_type));
+        if let Some((key, rule)) = coalesce {
+            match self.coalesce(&req, &key, rule, &targets).await? {
+                Some(PushOutcome::Stored(merged)) => {
+                    if let Some(admission) = admission {
+                        admission.accept();
+                    }
+                    drop(consumers);
+                    self.metrics.event_pushed(&merged);
+                    self.available.notify_waiters();
+                    let _ = self.pushed.send(merged.clone());
+                    return Ok(merged);
+                }
+                Some(PushOutcome::Duplicate(original)) => {
+                    let key = req.idempotency_key.as_deref();
+                    return Ok(Self::duplicate(&req.herald_id, key, original));
+                }
+                None => {}
+            }
+        }
+
+        if let Some(admission) = &mut admission {
+            admission.make_room(self, &req).await?;
+        }
+
+        let (herald_id, key) = (req.herald_id.clone(), req.idempotency_key.clone());
+        let event = match self.store.insert(req, &targets).await? {
+            PushOutcome::Stored(event) => event,
+            PushOutcome::Duplicate(original) => {
+                return Ok(Self::duplicate(&herald_id, key.as_deref(), original));
+            }
+        };
+        if targets.is_empty() {
+            debug!(
+                "No consumer matches event {} ({})",
+                event.id, event.event_type
+            );
+        }
+        for consumer in &targets {
+            self.cache.add_pending(consumer, event.clone()).await;
+        }
+        if let Some(admission) = admission {
+            admission.accept();
+        }
+        drop(consumers);
+
+        self.metrics.event_pushed(&event);
+        self.available.notify_waiters();
+        // No watcher is not an error
+        let _ = self.pushed.send(event.clone());
         Ok(event)
     }
 
-    /// Get events for delivery (pending + retries).
-    pub async fn fetch(&self, limit: u32) -> Vec<Event> {
-        self.cache.get_deliverable(limit, &self.retry_config).await
+    /// Receive every event pushed from now on, whichever consumers it is
+    /// queued for, without queueing anything for the receiver: nothing is
+    /// delivered or needs an ack. A merged event is received again after each
+    /// merge. A receiver more than `WATCH_BUFFER` events behind skips ahead.
+    pub fn watch(&self) -> broadcast::Receiver<Event> {
+        self.pushed.subscribe()
     }
 
-    /// Acknowledge event. Returns error if SQLite delete fails.
-    pub async fn ack(&self, id: EventId) -> Result<Option<Event>> {
-        // 1. Delete from SQLite first (must succeed)
-        let deleted = self.store.delete(id).await?;
+    /// Log a push whose idempotency key is taken and hand back the original.
+    fn duplicate(herald_id: &str, key: Option<&str>, original: Event) -> Event {
+        debug!(
+            "Duplicate push from {} (key {:?}), returning event {}",
+            herald_id, key, original.id
+        );
+        original
+    }
+
+    /// Merge `req` into the latest event sharing its coalesce key, if that is
+    /// still undelivered everywhere and not full.
+    async fn coalesce(
+        &self,
+        req: &CreateEventRequest,
+        key: &str,
+        rule: &CoalesceRule,
+        targets: &[String],
+    ) -> Result<Option<PushOutcome>> {
+        if targets.is_empty() {
+            return Ok(None);
+        }
+        let Some(target) = self
+            .store
+            .find_coalesce_target(&req.herald_id, &req.event_type, key)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let id = target.id;
+        // Taken out of the cache while it is rewritten, so it cannot be fetched meanwhile
+        let Some(original) = self.cache.take_undelivered(id, targets).await else {
+            return Ok(None);
+        };
+
+        // Wrapped in an array only once a second payload joins it
+        let mut members = match (&original.payload, target.merged) {
+            (serde_json::Value::Array(members), merged) if merged > 0 => members.clone(),
+            (payload, _) => vec![payload.clone()],
+        };
+        if members.len() >= rule.max_events {
+            // Full: the request starts a new event
+            self.requeue_pending(targets, &original).await;
+            return Ok(None);
+        }
+        members.push(req.payload.clone());
+        let count = members.len();
+        let merged = Event {
+            payload: serde_json::Value::Array(members),
+            priority: original.priority.max(req.priority),
+            ..original.clone()
+        };
+
+        let outcome = match self.store.coalesce(&merged, count, req).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                self.requeue_pending(targets, &original).await;
+                return Err(e);
+            }
+        };
+        if let PushOutcome::Duplicate(_) = &outcome {
+            self.requeue_pending(targets, &original).await;
+            return Ok(Some(outcome));
+        }
+        self.requeue_pending(targets, &merged).await;
+        debug!(
+            "Coalesced event from {} into event {} (key {})",
+            req.herald_id, merged.id, key
+        );
+        Ok(Some(outcome))
+    }
+
+    async fn requeue_pending(&self, consumers: &[String], event: &Event) {
+        for consumer in consumers {
+            self.cache.add_pending(consumer, event.clone()).await;
+        }
+    }
+
+    /// Get a consumer's events for delivery (pending + retries).
+    ///
+    /// Events that exceeded `max_retries` are moved to the dead-letter table
+    /// instead of being returned, and expired events are handled per
+    /// `on_expire`.
+    pub async fn fetch(&self, consumer: &str, limit: u32) -> Vec<Event> {
+        let events = self
+            .cache
+            .get_deliverable(consumer, limit, &self.retry_config)
+            .await;
+        self.dead_letter_exhausted().await;
+        self.discard_expired().await;
+        for event in &events {
+            self.metrics.event_fetched(consumer, event);
+        }
+
+        // Best effort: a failed update only means fewer retries are counted after a restart
+        let ids: Vec<EventId> = events.iter().map(|e| e.id).collect();
+        if let Err(e) = self.store.record_deliveries(consumer, &ids).await {
+            warn!(
+                "Failed to record deliveries to {} for {:?}: {}",
+                consumer, ids, e
+            );
+        }
+
+        events
+    }
+
+    /// Like [`EventQueue::fetch`], but waits up to `timeout` for events to
+    /// become deliverable when none are ready yet.
+    ///
+    /// Wakes as soon as an event is pushed or a retry falls due. Returns an
+    /// empty list if the timeout elapses or `shutdown` is cancelled first.
+    pub async fn fetch_wait(
+        &self,
+        consumer: &str,
+        limit: u32,
+        timeout: Duration,
+        shutdown: &CancellationToken,
+    ) -> Vec<Event> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            // Register before fetching so a push in between is not missed
+            let notified = self.available.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let events = self.fetch(consumer, limit).await;
+            let now = Instant::now();
+            if !events.is_empty() || now >= deadline {
+                return events;
+            }
+
+            let mut wake_at = deadline;
+            if let Some(retry_at) = self.cache.next_retry_at(consumer).await {
+                let retry_in = (retry_at - OffsetDateTime::now_utc())
+                    .try_into()
+                    .unwrap_or_default();
+                wake_at = wake_at.min(now + retry_in);
+            }
+
+            tokio::select! {
+                _ = notified => {}
+                _ = tokio::time::sleep_until(wake_at) => {}
+                _ = shutdown.cancelled() => return Vec::new(),
+            }
+        }
+    }
+
+    /// Drop or dead-letter every event past its `expires_at`, per
+    /// `on_expire`, including ones no fetch would reach: held back, awaiting
+    /// a retry, or queued for a consumer that stopped fetching.
+    pub async fn expire_stale(&self) {
+        self.cache.sweep_expired().await;
+        self.discard_expired().await;
+    }
+
+    /// Move events that ran out of retries to the dead-letter table.
+    /// Events that cannot be moved are kept in the cache and retried on the next fetch.
+    async fn dead_letter_exhausted(&self) {
+        for state in self.cache.take_exhausted().await {
+            let reason = format!(
+                "max_retries ({}) exceeded after {} deliveries",
+                self.retry_config.max_retries.unwrap_or_default(),
+                state.delivery_count
+            );
+            match self
+                .store
+                .dead_letter(&state.consumer, &state.event, state.delivery_count, &reason)
+                .await
+            {
+                Ok(_) => warn!(
+                    "Dead-lettered event {} for {}: {}",
+                    state.event.id, state.consumer, reason
+                ),
+                Err(e) => {
+                    error!(
+                        "Failed to dead-letter event {} for {}: {}",
+                        state.event.id, state.consumer, e
+                    );
+                    self.cache.restore(state).await;
+                }
+            }
+        }
+    }
+
+    /// Drop or dead-letter events that passed their `expires_at`.
+    /// Events that cannot be removed are kept in the cache and retried on the next fetch.
+    async fn discard_expired(&self) {
+        for state in self.cache.take_expired().await {
+            let result = match self.on_expire {
+                ExpirePolicy::Drop => self
+                    .store
+                    .delete(&state.consumer, state.event.id)
+                    .await
+                    .map(|_| ()),
+                ExpirePolicy::DeadLetter => self
+                    .store
+                    .dead_letter(
+                        &state.consumer,
+                        &state.event,
+                        state.delivery_count,
+                        "expired before it was acked",
+                    )
+                    .await
+                    .map(|_| ()),
+            };
+            match result {
+                Ok(()) => info!(
+                    "Expired event {} for {} ({:?})",
+                    state.event.id, state.consumer, self.on_expire
+                ),
+                Err(e) => {
+                    error!(
+                        "Failed to expire event {} for {}: {}",
+                        state.event.id, state.consumer, e
+                    );
+                    self.cache.restore(state).await;
+                }
+            }
+        }
+    }
+
+    /// Acknowledge a consumer's event. Returns error if SQLite delete fails.
+    pub async fn ack(&self, consumer: &str, id: EventId) -> Result<Option<Event>> {
+        let (delivered_at, retry_count) = self.delivery_info(consumer, id).await;
+
+        // 1. Delete (or archive) in SQLite first (must succeed)
+        let deleted = match self.archive_retention {
+            Some(retention) => {
+                self.store
+                    .archive(consumer, id, delivered_at, retry_count, retention)
+                    .await?
+            }
+            None => self.store.delete(consumer, id).await?,
+        };
         if !deleted {
             return Ok(None);
         }
 
         // 2. Remove from memory immediately
-        Ok(self.cache.remove(id).await)
+        let event = self.cache.remove(consumer, id).await;
+        if let Some(event) = &event {
+            self.metrics.event_acked(consumer, event, retry_count);
+        }
+        Ok(event)
+    }
+
+    /// A consumer's queued events matching the filter, in delivery order,
+    /// and how many match in total. Nothing is marked delivered.
+    pub async fn list_queued(
+        &self,
+        consumer: &str,
+        query: &QueuedEventsQuery,
+        limit: u32,
+    ) -> (Vec<QueuedEvent>, usize) {
+        let matching: Vec<QueuedEvent> = self
+            .cache
+            .list(consumer)
+            .await
+            .into_iter()
+            .map(|state| {
+                let status = match state.delivered_at {
+                    Some(_) => EventStatus::Delivered,
+                    None => EventStatus::Pending,
+                };
+                QueuedEvent {
+                    event: Event { status, ..state.event },
+                    consumer: state.consumer,
+                    delivered_at: state.delivered_at,
+                    delivery_count: state.delivery_count,
+                    next_delivery_at: state.next_retry_at,
+                }
+            })
+            .filter(|queued| {
+                let event = &queued.event;
+                query.status.is_none_or(|status| event.status == status)
+                    && query
+                        .herald_id
+                        .as_ref()
+                        .is_none_or(|id| &event.herald_id == id)
+                    && query
+                        .event_type
+                        .as_ref()
+                        .is_none_or(|t| &event.event_type == t)
+            })
+            .collect();
+
+        let total = matching.len();
+        (matching.into_iter().take(limit as usize).collect(), total)
+    }
+
+    /// Make a consumer's queued event deliverable again right away, e.g. to
+    /// redeliver a delivered event without waiting for its retry. Returns
+    /// None if the event is not queued for the consumer.
+    pub async fn requeue(&self, consumer: &str, id: EventId) -> Option<Event> {
+        let event = self.cache.requeue(consumer, id).await;
+        if event.is_some() {
+            self.available.notify_waiters();
+        }
+        event
     }
 
-    /// Batch acknowledge.
+    /// Number of a herald's events still queued for some consumer.
+    pub async fn herald_pending(&self, herald_id: &str) -> usize {
+        self.cache.herald_count(herald_id).await
+    }
+
+    /// Drop the herald's least important event no consumer has been
+    /// delivered yet, to make room for a new one of `priority`: the lowest
+    /// priority not above it, oldest first. Returns the dropped event, or
+    /// None if there is no such event.
+    pub async fn drop_lowest(
+        &self,
+        herald_id: &str,
+        priority: EventPriority,
+    ) -> Result<Option<Event>> {
+        let Some((event, holders)) = self.cache.take_droppable(herald_id, priority).await else {
+            return Ok(None);
+        };
+        for (i, consumer) in holders.iter().enumerate() {
+            if let Err(e) = self.store.delete(consumer, event.id).await {
+                self.requeue_pending(&holders[i..], &event).await;
+                return Err(e);
+            }
+        }
+        info!(
+            "Dropped {} event {} from {} to make room",
+            event.priority, event.id, herald_id
+        );
+        Ok(Some(event))
+    }
+
+    /// When the consumer was last delivered an event, and how often it was redelivered.
+    async fn delivery_info(&self, consumer: &str, id: EventId) -> (Option<OffsetDateTime>, u32) {
+        self.cache
+            .get(consumer, id)
+            .await
+            .map(|s| (s.delivered_at, s.retry_count))
+            .unwrap_or_default()
+    }
+
+    /// Acked events from the archive matching the query, most recent first,
+    /// with the total number of matches.
+    pub async fn event_history(
+        &self,
+        query: &EventHistoryQuery,
+        limit: u32,
+    ) -> Result<(Vec<ArchivedEvent>, usize)> {
+        self.store.list_archive(query, limit).await
+    }
+
+    /// Batch acknowledge in a single storage transaction.
     ///
-    /// **Non-atomic**: If ack #3 of 5 fails, the first 2 are still acked.
-    /// Callers receive list of successfully acked event IDs, not transaction rollback.
-    pub async fn batch_ack(&self, ids: Vec<EventId>) -> Result<Vec<EventId>> {
-        let mut acked_ids = Vec::with_capacity(ids.len());
+    /// Repeated IDs are acked once. In [`BatchAckMode::AllOrNothing`] mode a
+    /// missing or failed ID leaves every event queued.
+    pub async fn batch_ack(
+        &self,
+        consumer: &str,
+        ids: Vec<EventId>,
+        mode: BatchAckMode,
+    ) -> Result<BatchAckOutcome> {
+        let mut seen = HashSet::with_capacity(ids.len());
+        let mut acks = Vec::with_capacity(ids.len());
         for id in ids {
-            if self.ack(id).await?.is_some() {
-                acked_ids.push(id);
+            if !seen.insert(id) {
+                continue;
+            }
+            let (delivered_at, retry_count) = self.delivery_info(consumer, id).await;
+            acks.push(PendingAck { id, delivered_at, retry_count });
+        }
+
+        let outcome = self
+            .store
+            .ack_batch(consumer, &acks, self.archive_retention, mode)
+            .await?;
+        let acked: HashSet<EventId> = outcome.acked.iter().copied().collect();
+        for ack in acks.iter().filter(|ack| acked.contains(&ack.id)) {
+            if let Some(event) = self.cache.remove(consumer, ack.id).await {
+                self.metrics.event_acked(consumer, &event, ack.retry_count);
             }
         }
-        Ok(acked_ids)
+
+        Ok(outcome)
     }
 
     /// Update event status (for compatibility).
     /// Only supports Acked status, which triggers ack().
-    pub async fn update_status(&self, id: EventId, status: EventStatus) -> Result<Option<Event>> {
+    pub async fn update_status(
+        &self,
+        consumer: &str,
+        id: EventId,
+        status: EventStatus,
+    ) -> Result<Option<Event>> {
         if status == EventStatus::Acked {
-            self.ack(id).await
+            self.ack(consumer, id).await
         } else {
             // Delivered status is handled internally by fetch()
             Ok(None)
@@ -82,9 +621,800 @@ impl EventQueue {
     /// Only supports Acked status.
     pub async fn batch_update_status(
         &self,
+        consumer: &str,
         ids: Vec<EventId>,
         status: EventStatus,
-    ) -> Result<Vec<EventId>> {
-        if status == EventStatus::Acked { self.batch_ack(ids).await } else { Ok(Vec::new()) }
+        mode: BatchAckMode,
+    ) -> Result<BatchAckOutcome> {
+        if status == EventStatus::Acked {
+            self.batch_ack(consumer, ids, mode).await
+        } else {
+            Ok(BatchAckOutcome::default())
+        }
+    }
+
+    /// List dead-lettered events, newest first, with the total matching count.
+    pub async fn list_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<DeadLetter>, usize)> {
+        self.store
+            .list_dead_letters(consumer, herald_id, event_type, limit)
+            .await
+    }
+
+    /// Get a consumer's dead-lettered event by ID.
+    pub async fn get_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<DeadLetter>> {
+        self.store.get_dead_letter(consumer, id).await
+    }
+
+    /// Move a consumer's dead-lettered event back into its queue as pending.
+    pub async fn requeue_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<Event>> {
+        let event = self.store.requeue_dead_letter(consumer, id).await?;
+        if let Some(event) = &event {
+            self.cache.add_pending(consumer, event.clone()).await;
+            self.available.notify_waiters();
+        }
+        Ok(event)
+    }
+
+    /// Delete a consumer's dead-lettered event. Returns true if deleted.
+    pub async fn delete_dead_letter(&self, consumer: &str, id: EventId) -> Result<bool> {
+        self.store.delete_dead_letter(consumer, id).await
+    }
+
+    /// Delete dead-lettered events matching the filter. Returns the number deleted.
+    pub async fn purge_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+    ) -> Result<u64> {
+        self.store
+            .purge_dead_letters(consumer, herald_id, event_type)
+            .await
+    }
+
+    /// Refresh the metrics' queue and dead-letter gauges.
+    pub async fn record_metrics(&self) -> Result<()> {
+        self.metrics
+            .set_queues(&self.cache.stats().await, OffsetDateTime::now_utc());
+        self.metrics
+            .set_dead_letters(self.store.dead_letter_counts().await?);
+        Ok(())
+    }
+
+    /// Count dead-lettered events per herald.
+    pub async fn dead_letter_counts(&self) -> Result<HashMap<String, u64>> {
+        self.store.dead_letter_counts().await
+    }
+
+    /// List consumers with their current delivery counts, sorted by name.
+    pub async fn list_consumers(&self) -> Vec<ConsumerInfo> {
+        let consumers: Vec<ConsumerInfo> = self.consumers.read().await.values().cloned().collect();
+        let mut result = Vec::with_capacity(consumers.len());
+        for consumer in consumers {
+            result.push(self.with_counts(consumer).await);
+        }
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        result
+    }
+
+    /// Get a consumer with its current delivery counts.
+    pub async fn get_consumer(&self, name: &str) -> Option<ConsumerInfo> {
+        let consumer = self.consumers.read().await.get(name).cloned()?;
+        Some(self.with_counts(consumer).await)
+    }
+
+    /// Create a consumer, or replace the filters of an existing one.
+    ///
+    /// New consumers only receive events pushed after they are created, and
+    /// changed filters only apply to events pushed after the change.
+    pub async fn upsert_consumer(
+        &self,
+        name: &str,
+        request: &UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo> {
+        let mut consumers = self.consumers.write().await;
+        let consumer = self.store.upsert_consumer(name, request).await?;
+        self.cache.add_consumer(name).await;
+        consumers.insert(name.to_string(), consumer.clone());
+        drop(consumers);
+
+        Ok(self.with_counts(consumer).await)
+    }
+
+    /// Delete a consumer, dropping its undelivered events and dead letters.
+    /// Returns true if deleted.
+    pub async fn delete_consumer(&self, name: &str) -> Result<bool> {
+        let mut consumers = self.consumers.write().await;
+        let deleted = self.store.delete_consumer(name).await?;
+        self.cache.remove_consumer(name).await;
+        consumers.remove(name);
+        Ok(deleted)
+    }
+
+    async fn with_counts(&self, consumer: ConsumerInfo) -> ConsumerInfo {
+        let (pending, delivered) = self.cache.counts(&consumer.name).await;
+        ConsumerInfo { pending, delivered, ..consumer }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consumer::DEFAULT_CONSUMER;
+    use crate::event::EventPriority;
+    use time::OffsetDateTime;
+
+    fn test_request() -> CreateEventRequest {
+        typed_request("test")
+    }
+
+    fn typed_request(event_type: &str) -> CreateEventRequest {
+        CreateEventRequest {
+            event_type: event_type.to_string(),
+            herald_id: "test-herald".to_string(),
+            priority: EventPriority::Normal,
+            payload: serde_json::json!({}),
+            timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_dead_letters_after_max_retries_and_requeues() {
+        // Zero interval makes every delivered event immediately retry-ready
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: Some(1),
+        };
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            config,
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let event = queue.push(test_request()).await.unwrap();
+
+        // Initial delivery + 1 retry
+        assert_eq!(queue.fetch(DEFAULT_CONSUMER, 10).await.len(), 1);
+        assert_eq!(queue.fetch(DEFAULT_CONSUMER, 10).await.len(), 1);
+
+        // Next fetch dead-letters instead of delivering
+        assert!(queue.fetch(DEFAULT_CONSUMER, 10).await.is_empty());
+        let dead = queue
+            .get_dead_letter(DEFAULT_CONSUMER, event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(dead.delivery_count, 2);
+        assert!(
+            queue
+                .ack(DEFAULT_CONSUMER, event.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Requeued events are delivered again
+        queue
+            .requeue_dead_letter(DEFAULT_CONSUMER, event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let events = queue.fetch(DEFAULT_CONSUMER, 10).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, event.id);
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_lists_and_requeues_queued_events() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let first = queue.push(typed_request("chat.message")).await.unwrap();
+        let second = queue.push(typed_request("kairos.trigger")).await.unwrap();
+        assert_eq!(queue.fetch(DEFAULT_CONSUMER, 1).await[0].id, first.id);
+
+        // Listing does not deliver anything
+        let all = QueuedEventsQuery::default();
+        let (events, total) = queue.list_queued(DEFAULT_CONSUMER, &all, 10).await;
+        assert_eq!(total, 2);
+        assert_eq!(events[0].event.status, EventStatus::Delivered);
+        assert_eq!(events[0].delivery_count, 1);
+        assert!(events[0].next_delivery_at.is_some());
+        assert_eq!(events[1].event.status, EventStatus::Pending);
+
+        let pending =
+            QueuedEventsQuery { status: Some(EventStatus::Pending), ..Default::default() };
+        let (events, _) = queue.list_queued(DEFAULT_CONSUMER, &pending, 10).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event.id, second.id);
+        let by_type = QueuedEventsQuery {
+            event_type: Some("chat.message".to_string()),
+            ..Default::default()
+        };
+        let (events, total) = queue.list_queued(DEFAULT_CONSUMER, &by_type, 0).await;
+        assert!(events.is_empty());
+        assert_eq!(total, 1);
+
+        // A requeued event is redelivered without waiting for its retry
+        assert_eq!(queue.fetch(DEFAULT_CONSUMER, 10).await[0].id, second.id);
+        assert!(queue.fetch(DEFAULT_CONSUMER, 10).await.is_empty());
+        assert!(queue.requeue(DEFAULT_CONSUMER, first.id).await.is_some());
+        assert!(queue.requeue(DEFAULT_CONSUMER, 999).await.is_none());
+        let events = queue.fetch(DEFAULT_CONSUMER, 10).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, first.id);
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_fetch_wait_wakes_on_push() {
+        let queue = std::sync::Arc::new(
+            EventQueue::new(
+                Box::new(MemoryEventStore::new()),
+                RetryConfig::default(),
+                ExpirePolicy::Drop,
+                None,
+            )
+            .await
+            .unwrap(),
+        );
+
+        let waiter = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .fetch_wait(
+                        DEFAULT_CONSUMER,
+                        10,
+                        Duration::from_secs(10),
+                        &CancellationToken::new(),
+                    )
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let event = queue.push(test_request()).await.unwrap();
+
+        let events = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, event.id);
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_fetch_wait_times_out_empty() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            queue
+                .fetch_wait(
+                    DEFAULT_CONSUMER,
+                    10,
+                    Duration::from_millis(20),
+                    &CancellationToken::new()
+                )
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_fetch_wait_ends_on_shutdown() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let shutdown = CancellationToken::new();
+
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            shutdown.cancel();
+        };
+        let (events, ()) = tokio::time::timeout(
+            Duration::from_secs(1),
+            futures::future::join(
+                queue.fetch_wait(DEFAULT_CONSUMER, 10, Duration::from_secs(60), &shutdown),
+                cancel,
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_fetch_wait_wakes_on_due_retry() {
+        let config = RetryConfig {
+            base_interval_ms: 50,
+            multiplier: 2,
+            max_interval_ms: 50,
+            max_retries: None,
+        };
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            config,
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let event = queue.push(test_request()).await.unwrap();
+        assert_eq!(queue.fetch(DEFAULT_CONSUMER, 10).await.len(), 1);
+
+        let events = queue
+            .fetch_wait(
+                DEFAULT_CONSUMER,
+                10,
+                Duration::from_secs(10),
+                &CancellationToken::new(),
+            )
+            .await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, event.id);
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_consumers_receive_matching_events_independently() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let filters =
+            UpsertConsumerRequest { event_types: vec!["chat.*".to_string()], herald_ids: vec![] };
+        queue.upsert_consumer("observer", &filters).await.unwrap();
+
+        let chat = queue.push(typed_request("chat.message")).await.unwrap();
+        let timer = queue.push(typed_request("kairos.trigger")).await.unwrap();
+
+        // The observer only sees chat events, and fetching them does not steal them
+        let observed = queue.fetch("observer", 10).await;
+        assert_eq!(
+            observed.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![chat.id]
+        );
+        let delivered = queue.fetch(DEFAULT_CONSUMER, 10).await;
+        assert_eq!(
+            delivered.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![chat.id, timer.id]
+        );
+
+        // Acks are per consumer
+        assert!(queue.ack("observer", chat.id).await.unwrap().is_some());
+        assert!(queue.ack("observer", timer.id).await.unwrap().is_none());
+        assert!(
+            queue
+                .ack(DEFAULT_CONSUMER, chat.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        let observer = queue.get_consumer("observer").await.unwrap();
+        assert_eq!((observer.pending, observer.delivered), (0, 0));
+        let default = queue.get_consumer(DEFAULT_CONSUMER).await.unwrap();
+        assert_eq!((default.pending, default.delivered), (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_deleted_consumer_stops_receiving() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        queue
+            .upsert_consumer("observer", &UpsertConsumerRequest::default())
+            .await
+            .unwrap();
+        queue.push(test_request()).await.unwrap();
+
+        assert!(queue.delete_consumer("observer").await.unwrap());
+        assert!(queue.get_consumer("observer").await.is_none());
+        queue.push(test_request()).await.unwrap();
+        assert!(queue.fetch("observer", 10).await.is_empty());
+        assert_eq!(queue.fetch(DEFAULT_CONSUMER, 10).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_duplicate_push_returns_original() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut request = test_request();
+        request.idempotency_key = Some("schedule-1".to_string());
+
+        let original = queue.push(request.clone()).await.unwrap();
+        let duplicate = queue.push(request.clone()).await.unwrap();
+        assert_eq!(duplicate.id, original.id);
+        assert_eq!(queue.fetch(DEFAULT_CONSUMER, 10).await.len(), 1);
+
+        // Still a duplicate after the original was acked
+        queue.ack(DEFAULT_CONSUMER, original.id).await.unwrap();
+        assert_eq!(queue.push(request).await.unwrap().id, original.id);
+        assert!(queue.fetch(DEFAULT_CONSUMER, 10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_ack_archives_when_enabled() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            Some(Duration::from_secs(3600)),
+        )
+        .await
+        .unwrap();
+        let event = queue.push(test_request()).await.unwrap();
+        queue.fetch(DEFAULT_CONSUMER, 10).await;
+
+        assert!(
+            queue
+                .ack(DEFAULT_CONSUMER, event.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(queue.fetch(DEFAULT_CONSUMER, 10).await.is_empty());
+
+        let (history, total) = queue
+            .event_history(&EventHistoryQuery::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(history[0].event.id, event.id);
+        assert_eq!(history[0].consumer, DEFAULT_CONSUMER);
+        assert!(history[0].delivered_at.is_some());
+        assert_eq!(history[0].retry_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_batch_ack_modes() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let a = queue.push(test_request()).await.unwrap();
+        let b = queue.push(test_request()).await.unwrap();
+
+        let outcome = queue
+            .batch_ack(
+                DEFAULT_CONSUMER,
+                vec![a.id, 999],
+                BatchAckMode::AllOrNothing,
+            )
+            .await
+            .unwrap();
+        assert!(outcome.acked.is_empty());
+        assert_eq!(outcome.missing, vec![999]);
+        assert!(queue.cache.get(DEFAULT_CONSUMER, a.id).await.is_some());
+
+        // Repeated IDs are acked once rather than reported missing
+        let outcome = queue
+            .batch_ack(
+                DEFAULT_CONSUMER,
+                vec![a.id, a.id, b.id],
+                BatchAckMode::AllOrNothing,
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.acked, vec![a.id, b.id]);
+        assert!(outcome.missing.is_empty());
+        assert!(queue.fetch(DEFAULT_CONSUMER, 10).await.is_empty());
+        assert!(queue.cache.get(DEFAULT_CONSUMER, a.id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_records_metrics() {
+        let metrics = Arc::new(Metrics::new().unwrap());
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone());
+        let event = queue.push(test_request()).await.unwrap();
+        queue.push(test_request()).await.unwrap();
+        queue.fetch(DEFAULT_CONSUMER, 1).await;
+        queue.ack(DEFAULT_CONSUMER, event.id).await.unwrap();
+        queue.record_metrics().await.unwrap();
+
+        let text = metrics.render().unwrap();
+        for series in [
+            r#"agora_events_pushed_total{event_type="test",herald_id="test-herald"} 2"#,
+            r#"agora_events_fetched_total{consumer="default",event_type="test",herald_id="test-herald"} 1"#,
+            r#"agora_events_acked_total{consumer="default",event_type="test",herald_id="test-herald"} 1"#,
+            r#"agora_events_pending{consumer="default"} 1"#,
+            r#"agora_events_delivered{consumer="default"} 0"#,
+        ] {
+            assert!(text.contains(series), "missing {series} in:\n{text}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_dead_letters_expired_events() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::DeadLetter,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut request = test_request();
+        request.expires_at = Some(OffsetDateTime::now_utc() - time::Duration::seconds(1));
+        let expired = queue.push(request).await.unwrap();
+        let mut request = test_request();
+        request.deliver_after = Some(OffsetDateTime::now_utc() + time::Duration::hours(1));
+        queue.push(request).await.unwrap();
+
+        assert!(queue.fetch(DEFAULT_CONSUMER, 10).await.is_empty());
+
+        let (dead_letters, _) = queue.list_dead_letters(None, None, None, 10).await.unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].event.id, expired.id);
+        assert_eq!(dead_letters[0].delivery_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_watch_queues_nothing() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut watcher = queue.watch();
+
+        let pushed = queue.push(test_request()).await.unwrap();
+        assert_eq!(watcher.recv().await.unwrap().id, pushed.id);
+
+        // The event is still the default consumer's to fetch, and no one else's
+        assert_eq!(queue.list_consumers().await.len(), 1);
+        let events = queue.fetch(DEFAULT_CONSUMER, 10).await;
+        assert_eq!(
+            events.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![pushed.id]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_expires_events_no_fetch_reaches() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::DeadLetter,
+            None,
+        )
+        .await
+        .unwrap();
+        let mut request = test_request();
+        request.deliver_after = Some(OffsetDateTime::now_utc() + time::Duration::hours(1));
+        request.expires_at = Some(OffsetDateTime::now_utc() + time::Duration::milliseconds(50));
+        let held_back = queue.push(request).await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        queue.expire_stale().await;
+
+        let (dead_letters, _) = queue.list_dead_letters(None, None, None, 10).await.unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].event.id, held_back.id);
+        assert_eq!(queue.cache.counts(DEFAULT_CONSUMER).await, (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_duplicate_returns_merged_event() {
+        let stores: [Box<dyn EventStore>; 2] = [
+            Box::new(MemoryEventStore::new()),
+            Box::new(SqliteEventStore::new(":memory:").await.unwrap()),
+        ];
+        for store in stores {
+            let rules =
+                HashMap::from([("chat.message".to_string(), CoalesceRule { max_events: 3 })]);
+            let queue = EventQueue::new(store, RetryConfig::default(), ExpirePolicy::Drop, None)
+                .await
+                .unwrap()
+                .with_coalesce_rules(rules);
+            let chat = |n: u32| CreateEventRequest {
+                payload: serde_json::json!({ "n": n }),
+                idempotency_key: Some(format!("msg-{}", n)),
+                coalesce_key: Some("alice".to_string()),
+                ..typed_request("chat.message")
+            };
+
+            let first = queue.push(chat(1)).await.unwrap();
+            let merged = queue
+                .push(CreateEventRequest { priority: EventPriority::High, ..chat(2) })
+                .await
+                .unwrap();
+            assert_eq!(merged.id, first.id);
+
+            // A retry of the first push sees the event as merged since
+            let retried = queue.push(chat(1)).await.unwrap();
+            assert_eq!(retried.id, first.id);
+            assert_eq!(retried.payload, merged.payload);
+            assert_eq!(retried.priority, EventPriority::High);
+            assert_eq!(queue.fetch(DEFAULT_CONSUMER, 10).await.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_coalesces_undelivered_events() {
+        let rules = HashMap::from([("chat.message".to_string(), CoalesceRule { max_events: 3 })]);
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap()
+        .with_coalesce_rules(rules);
+        let chat = |n: u32, key: &str| CreateEventRequest {
+            payload: serde_json::json!({ "n": n }),
+            coalesce_key: Some(key.to_string()),
+            ..typed_request("chat.message")
+        };
+
+        let first = queue.push(chat(1, "alice")).await.unwrap();
+        assert_eq!(first.payload, serde_json::json!({ "n": 1 }));
+        let merged = queue
+            .push(CreateEventRequest { priority: EventPriority::High, ..chat(2, "alice") })
+            .await
+            .unwrap();
+        assert_eq!(merged.id, first.id);
+        assert_eq!(merged.priority, EventPriority::High);
+        queue.push(chat(3, "alice")).await.unwrap();
+        // Full, and a different key: both start new events
+        let overflow = queue.push(chat(4, "alice")).await.unwrap();
+        let bob = queue.push(chat(5, "bob")).await.unwrap();
+        // No rule for the type: key ignored
+        let other = queue
+            .push(CreateEventRequest { coalesce_key: Some("alice".to_string()), ..test_request() })
+            .await
+            .unwrap();
+        assert_eq!(other.payload, serde_json::json!({}));
+
+        let events = queue.fetch(DEFAULT_CONSUMER, 10).await;
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].id, first.id);
+        assert_eq!(
+            events[0].payload,
+            serde_json::json!([{ "n": 1 }, { "n": 2 }, { "n": 3 }])
+        );
+        assert!(events.iter().any(|e| e.id == overflow.id));
+
+        // Delivered events are not merged into
+        let late = queue.push(chat(6, "bob")).await.unwrap();
+        assert_ne!(late.id, bob.id);
+        assert_eq!(late.payload, serde_json::json!({ "n": 6 }));
+        assert!(
+            events
+                .iter()
+                .any(|e| e.id == bob.id && e.payload == serde_json::json!({ "n": 5 }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_coalescing_keeps_array_payloads_whole() {
+        let rules = HashMap::from([("chat.message".to_string(), CoalesceRule { max_events: 3 })]);
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap()
+        .with_coalesce_rules(rules);
+        let chat = |payload| CreateEventRequest {
+            payload,
+            coalesce_key: Some("alice".to_string()),
+            ..typed_request("chat.message")
+        };
+
+        let first = queue.push(chat(serde_json::json!([1, 2]))).await.unwrap();
+        assert_eq!(first.payload, serde_json::json!([1, 2]));
+        let merged = queue.push(chat(serde_json::json!([3]))).await.unwrap();
+        assert_eq!(merged.id, first.id);
+        assert_eq!(merged.payload, serde_json::json!([[1, 2], [3]]));
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_drops_lowest_undelivered_herald_event() {
+        let queue = EventQueue::new(
+            Box::new(MemoryEventStore::new()),
+            RetryConfig::default(),
+            ExpirePolicy::Drop,
+            None,
+        )
+        .await
+        .unwrap();
+        let push = |priority| {
+            let queue = &queue;
+            async move {
+                let req = CreateEventRequest { priority, ..test_request() };
+                queue.push(req).await.unwrap()
+            }
+        };
+        let high = push(EventPriority::High).await;
+        let low = push(EventPriority::Low).await;
+        let normal = push(EventPriority::Normal).await;
+        assert_eq!(queue.herald_pending("test-herald").await, 3);
+
+        // Lowest priority first
+        let dropped = queue
+            .drop_lowest("test-herald", EventPriority::Normal)
+            .await
+            .unwrap();
+        assert_eq!(dropped.map(|e| e.id), Some(low.id));
+
+        // Delivered events and those above the new event's priority are kept
+        let delivered = queue.fetch(DEFAULT_CONSUMER, 1).await;
+        assert_eq!(delivered[0].id, high.id);
+        assert!(
+            queue
+                .drop_lowest("test-herald", EventPriority::Low)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let dropped = queue
+            .drop_lowest("test-herald", EventPriority::Urgent)
+            .await
+            .unwrap();
+        assert_eq!(dropped.map(|e| e.id), Some(normal.id));
+        assert_eq!(queue.herald_pending("test-herald").await, 1);
+        assert_eq!(queue.herald_pending("other-herald").await, 0);
     }
 }
@@ -1,14 +1,31 @@
-//! SQLite persistence for pending events.
+//! SQLite persistence for pending and dead-lettered events and their consumers.
 
-use crate::event::{CreateEventRequest, Event, EventId, EventPriority, EventStatus};
+use super::store::{
+    BatchAckOutcome, CoalesceTarget, DEFAULT_IDEMPOTENCY_WINDOW, EventStore, PendingAck,
+    PushOutcome,
+};
+use crate::consumer::{ConsumerInfo, DEFAULT_CONSUMER, UpsertConsumerRequest};
+use crate::event::{
+    ArchivedEvent, BatchAckMode, CreateEventRequest, DeadLetter, Event, EventHistoryQuery, EventId,
+    EventPriority, EventStatus,
+};
 use anyhow::Result;
-use sqlx::{Row, SqlitePool};
+use async_trait::async_trait;
+use sqlx::{Acquire, QueryBuilder, Row, Sqlite, SqliteConnection, SqlitePool};
+use std::collections::HashMap;
+use std::time::Duration;
 use time::OffsetDateTime;
+use tracing::warn;
 
 /// SQLite-backed pending event storage.
+///
+/// An event stays in `events` while at least one consumer still has a row
+/// for it in `deliveries`. Idempotency keys outlive their events (a duplicate
+/// may arrive after the original was acked), so they keep their own copy.
 #[derive(Clone)]
 pub struct SqliteEventStore {
     pool: SqlitePool,
+    idempotency_window: Duration,
 }
 
 impl SqliteEventStore {
@@ -16,7 +33,13 @@ impl SqliteEventStore {
         let db_url = format!("sqlite:{}?mode=rwc", database_path);
         let pool = SqlitePool::connect(&db_url).await?;
         Self::run_migrations(&pool).await?;
-        Ok(Self { pool })
+        Ok(Self { pool, idempotency_window: DEFAULT_IDEMPOTENCY_WINDOW })
+    }
+
+    /// Sets how long an idempotency key is remembered after its first push.
+    pub fn with_idempotency_window(mut self, window: Duration) -> Self {
+        self.idempotency_window = window;
+        self
     }
 
     async fn run_migrations(pool: &SqlitePool) -> Result<()> {
@@ -39,19 +62,350 @@ impl SqliteEventStore {
             .execute(pool)
             .await?;
 
+        // Single-consumer databases tracked delivery counts on the event itself;
+        // the column is only read below to carry them over to `deliveries`
+        let has_delivery_count: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('events') WHERE name = 'delivery_count'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_delivery_count {
+            sqlx::query("ALTER TABLE events ADD COLUMN delivery_count INTEGER NOT NULL DEFAULT 0")
+                .execute(pool)
+                .await?;
+        }
+
+        for column in ["deliver_after", "expires_at", "coalesce_key"] {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('events') WHERE name = ?",
+            )
+            .bind(column)
+            .fetch_one(pool)
+            .await?;
+            if !exists {
+                sqlx::query(&format!("ALTER TABLE events ADD COLUMN {} TEXT", column))
+                    .execute(pool)
+                    .await?;
+            }
+        }
+
+        let has_coalesced: bool = sqlx::query_scalar(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('events') WHERE name = 'coalesced'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if !has_coalesced {
+            sqlx::query("ALTER TABLE events ADD COLUMN coalesced INTEGER NOT NULL DEFAULT 0")
+                .execute(pool)
+                .await?;
+            // Payloads were wrapped in an array as soon as they had a coalesce key
+            sqlx::query(
+                r#"
+                UPDATE events SET coalesced = json_array_length(payload)
+                WHERE coalesce_key IS NOT NULL AND json_type(payload) = 'array'
+                "#,
+            )
+            .execute(pool)
+            .await?;
+        }
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_events_coalesce ON events(herald_id, event_type, coalesce_key)",
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS consumers (
+                name TEXT PRIMARY KEY,
+                event_types TEXT NOT NULL DEFAULT '[]',
+                herald_ids TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query("INSERT OR IGNORE INTO consumers (name, created_at) VALUES (?, ?)")
+            .bind(DEFAULT_CONSUMER)
+            .bind(format_time(OffsetDateTime::now_utc())?)
+            .execute(pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS deliveries (
+                event_id INTEGER NOT NULL,
+                consumer TEXT NOT NULL,
+                delivery_count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (consumer, event_id)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_deliveries_event_id ON deliveries(event_id)")
+            .execute(pool)
+            .await?;
+
+        // Events queued before consumers existed belong to the default consumer
+        sqlx::query(
+            r#"
+            INSERT INTO deliveries (event_id, consumer, delivery_count)
+            SELECT id, ?, delivery_count FROM events
+            WHERE id NOT IN (SELECT event_id FROM deliveries)
+            "#,
+        )
+        .bind(DEFAULT_CONSUMER)
+        .execute(pool)
+        .await?;
+
+        // Dead letters gained a consumer column and a composite key; SQLite
+        // cannot change a primary key in place, so older tables are rebuilt
+        let legacy_dead_letters: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'dead_letters')
+               AND NOT EXISTS (SELECT 1 FROM pragma_table_info('dead_letters') WHERE name = 'consumer')
+            "#,
+        )
+        .fetch_one(pool)
+        .await?;
+        if legacy_dead_letters {
+            sqlx::query("ALTER TABLE dead_letters RENAME TO dead_letters_legacy")
+                .execute(pool)
+                .await?;
+        }
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS dead_letters (
+                id INTEGER NOT NULL,
+                consumer TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                herald_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                priority TEXT NOT NULL DEFAULT 'normal',
+                timestamp TEXT NOT NULL,
+                delivery_count INTEGER NOT NULL,
+                reason TEXT NOT NULL,
+                dead_lettered_at TEXT NOT NULL,
+                PRIMARY KEY (id, consumer)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        if legacy_dead_letters {
+            sqlx::query(
+                r#"
+                INSERT INTO dead_letters (
+                    id, consumer, event_type, herald_id, payload, priority, timestamp,
+                    delivery_count, reason, dead_lettered_at
+                )
+                SELECT id, ?, event_type, herald_id, payload, priority, timestamp,
+                       delivery_count, reason, dead_lettered_at
+                FROM dead_letters_legacy
+                "#,
+            )
+            .bind(DEFAULT_CONSUMER)
+            .execute(pool)
+            .await?;
+            sqlx::query("DROP TABLE dead_letters_legacy")
+                .execute(pool)
+                .await?;
+        }
+
+        // Kept so a requeued event comes back with its expiry and coalescing
+        for (column, definition) in [
+            ("deliver_after", "TEXT"),
+            ("expires_at", "TEXT"),
+            ("coalesce_key", "TEXT"),
+            ("coalesced", "INTEGER NOT NULL DEFAULT 0"),
+        ] {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('dead_letters') WHERE name = ?",
+            )
+            .bind(column)
+            .fetch_one(pool)
+            .await?;
+            if !exists {
+                sqlx::query(&format!(
+                    "ALTER TABLE dead_letters ADD COLUMN {} {}",
+                    column, definition
+                ))
+                .execute(pool)
+                .await?;
+            }
+        }
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_dead_letters_herald_id ON dead_letters(herald_id)",
+        )
+        .execute(pool)
+        .await?;
+
+        // Keys are scoped per herald; `created_at_ms` is unix milliseconds so
+        // the window check is a plain integer comparison
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS idempotency_keys (
+                herald_id TEXT NOT NULL,
+                idempotency_key TEXT NOT NULL,
+                event_id INTEGER NOT NULL,
+                event_type TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                created_at_ms INTEGER NOT NULL,
+                PRIMARY KEY (herald_id, idempotency_key)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys(created_at_ms)",
+        )
+        .execute(pool)
+        .await?;
+
+        // Times used in filters are also kept as unix milliseconds, since
+        // RFC 3339 strings with offsets do not sort chronologically
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS events_archive (
+                id INTEGER NOT NULL,
+                consumer TEXT NOT NULL,
+                event_type TEXT NOT NULL,
+                herald_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                priority TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                timestamp_ms INTEGER NOT NULL,
+                delivered_at TEXT,
+                acked_at TEXT NOT NULL,
+                acked_at_ms INTEGER NOT NULL,
+                retry_count INTEGER NOT NULL,
+                PRIMARY KEY (id, consumer)
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        for index in [
+            "CREATE INDEX IF NOT EXISTS idx_events_archive_timestamp ON events_archive(timestamp_ms)",
+            "CREATE INDEX IF NOT EXISTS idx_events_archive_acked_at ON events_archive(acked_at_ms)",
+        ] {
+            sqlx::query(index).execute(pool).await?;
+        }
+
         Ok(())
     }
 
-    /// Insert new event. Returns the created event with ID.
-    pub async fn insert(&self, req: CreateEventRequest) -> Result<Event> {
-        let timestamp = req
-            .timestamp
-            .format(&time::format_description::well_known::Rfc3339)?;
+    /// Oldest `created_at_ms` still inside the idempotency window.
+    fn idempotency_cutoff(&self, now_ms: i64) -> i64 {
+        now_ms.saturating_sub(self.idempotency_window.as_millis() as i64)
+    }
+
+    /// Records the request's idempotency key, if any, as belonging to `event_id`.
+    ///
+    /// Returns the event the key already belongs to if another push took it
+    /// first; the caller must then roll back.
+    async fn remember_idempotency_key(
+        &self,
+        conn: &mut SqliteConnection,
+        req: &CreateEventRequest,
+        event_id: EventId,
+    ) -> Result<Option<Event>> {
+        let Some(key) = &req.idempotency_key else {
+            return Ok(None);
+        };
+
+        // Expired keys are pruned here rather than by a background task
+        let now_ms = unix_ms(OffsetDateTime::now_utc());
+        sqlx::query("DELETE FROM idempotency_keys WHERE created_at_ms < ?")
+            .bind(self.idempotency_cutoff(now_ms))
+            .execute(&mut *conn)
+            .await?;
 
         let result = sqlx::query(
             r#"
-            INSERT INTO events (event_type, herald_id, payload, priority, timestamp)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO idempotency_keys
+                (herald_id, idempotency_key, event_id, event_type, payload, priority, timestamp, created_at_ms)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (herald_id, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(&req.herald_id)
+        .bind(key)
+        .bind(event_id as i64)
+        .bind(&req.event_type)
+        .bind(req.payload.to_string())
+        .bind(req.priority.to_string())
+        .bind(format_time(req.timestamp)?)
+        .bind(now_ms)
+        .execute(&mut *conn)
+        .await?;
+        if result.rows_affected() > 0 {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(&format!(
+            "{} WHERE k.herald_id = ? AND k.idempotency_key = ?",
+            REMEMBERED_EVENT
+        ))
+        .bind(&req.herald_id)
+        .bind(key)
+        .fetch_one(&mut *conn)
+        .await?;
+        row_to_event(row).map(Some)
+    }
+}
+
+/// Selects the event an idempotency key was taken by: as it is now while
+/// still queued, since merges may have changed it, and as first pushed after.
+const REMEMBERED_EVENT: &str = r#"
+    SELECT k.event_id AS id, k.event_type, k.herald_id,
+           COALESCE(e.payload, k.payload) AS payload,
+           COALESCE(e.priority, k.priority) AS priority, k.timestamp
+    FROM idempotency_keys k
+    LEFT JOIN events e ON e.id = k.event_id
+"#;
+
+#[async_trait]
+impl EventStore for SqliteEventStore {
+    async fn find_by_idempotency_key(
+        &self,
+        herald_id: &str,
+        idempotency_key: &str,
+    ) -> Result<Option<Event>> {
+        let row = sqlx::query(&format!(
+            "{} WHERE k.herald_id = ? AND k.idempotency_key = ? AND k.created_at_ms >= ?",
+            REMEMBERED_EVENT
+        ))
+        .bind(herald_id)
+        .bind(idempotency_key)
+        .bind(self.idempotency_cutoff(unix_ms(OffsetDateTime::now_utc())))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(row_to_event).transpose()
+    }
+
+    async fn insert(&self, req: CreateEventRequest, consumers: &[String]) -> Result<PushOutcome> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO events
+                (event_type, herald_id, payload, priority, timestamp, deliver_after, expires_at, coalesce_key)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING id
             "#,
         )
@@ -59,13 +413,38 @@ impl SqliteEventStore {
         .bind(&req.herald_id)
         .bind(req.payload.to_string())
         .bind(req.priority.to_string())
-        .bind(&timestamp)
-        .fetch_one(&self.pool)
+        .bind(format_time(req.timestamp)?)
+        .bind(req.deliver_after.map(format_time).transpose()?)
+        .bind(req.expires_at.map(format_time).transpose()?)
+        .bind(&req.coalesce_key)
+        .fetch_one(&mut *tx)
         .await?;
 
         let id: i64 = result.get("id");
 
-        Ok(Event {
+        for consumer in consumers {
+            sqlx::query("INSERT INTO deliveries (event_id, consumer) VALUES (?, ?)")
+                .bind(id)
+                .bind(consumer)
+                .execute(&mut *tx)
+                .await?;
+        }
+        if consumers.is_empty() {
+            // No consumer wants it: the event still gets an ID, but is not queued
+            delete_orphaned_events(&mut tx).await?;
+        }
+
+        if let Some(original) = self
+            .remember_idempotency_key(&mut tx, &req, id as EventId)
+            .await?
+        {
+            tx.rollback().await?;
+            return Ok(PushOutcome::Duplicate(original));
+        }
+
+        tx.commit().await?;
+
+        Ok(PushOutcome::Stored(Event {
             id: id as u64,
             event_type: req.event_type,
             herald_id: req.herald_id,
@@ -73,27 +452,676 @@ impl SqliteEventStore {
             priority: req.priority,
             timestamp: req.timestamp,
             status: EventStatus::Pending,
+            deliver_after: req.deliver_after,
+            expires_at: req.expires_at,
+        }))
+    }
+
+    async fn find_coalesce_target(
+        &self,
+        herald_id: &str,
+        event_type: &str,
+        coalesce_key: &str,
+    ) -> Result<Option<CoalesceTarget>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, coalesced FROM events
+            WHERE herald_id = ? AND event_type = ? AND coalesce_key = ?
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(herald_id)
+        .bind(event_type)
+        .bind(coalesce_key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| CoalesceTarget {
+            id: row.get::<i64, _>("id") as EventId,
+            merged: row.get::<i64, _>("coalesced") as usize,
+        }))
+    }
+
+    async fn coalesce(
+        &self,
+        merged: &Event,
+        count: usize,
+        req: &CreateEventRequest,
+    ) -> Result<PushOutcome> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE events SET payload = ?, priority = ?, coalesced = ? WHERE id = ?")
+            .bind(merged.payload.to_string())
+            .bind(merged.priority.to_string())
+            .bind(count as i64)
+            .bind(merged.id as i64)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(original) = self
+            .remember_idempotency_key(&mut tx, req, merged.id)
+            .await?
+        {
+            tx.rollback().await?;
+            return Ok(PushOutcome::Duplicate(original));
+        }
+
+        tx.commit().await?;
+        Ok(PushOutcome::Stored(merged.clone()))
+    }
+
+    async fn load_all(&self) -> Result<Vec<(String, Event, u32)>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT e.id, e.event_type, e.herald_id, e.payload, e.priority, e.timestamp,
+                   e.deliver_after, e.expires_at, d.consumer, d.delivery_count
+            FROM deliveries d
+            JOIN events e ON e.id = d.event_id
+            ORDER BY d.consumer, e.id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let consumer: String = row.get("consumer");
+                let delivery_count = row.get::<i64, _>("delivery_count") as u32;
+                let deliver_after: Option<String> = row.get("deliver_after");
+                let expires_at: Option<String> = row.get("expires_at");
+                let event = Event {
+                    deliver_after: deliver_after.as_deref().map(parse_time).transpose()?,
+                    expires_at: expires_at.as_deref().map(parse_time).transpose()?,
+                    ..row_to_event(row)?
+                };
+                Ok((consumer, event, delivery_count))
+            })
+            .collect()
+    }
+
+    async fn record_deliveries(&self, consumer: &str, ids: &[EventId]) -> Result<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let query = format!(
+            "UPDATE deliveries SET delivery_count = delivery_count + 1 WHERE consumer = ? AND event_id IN ({})",
+            placeholders
+        );
+
+        let mut q = sqlx::query(&query).bind(consumer);
+        for id in ids {
+            q = q.bind(*id as i64);
+        }
+        q.execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, consumer: &str, id: EventId) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let deleted = delete_delivery(&mut tx, consumer, id).await?;
+        tx.commit().await?;
+
+        Ok(deleted)
+    }
+
+    async fn archive(
+        &self,
+        consumer: &str,
+        id: EventId,
+        delivered_at: Option<OffsetDateTime>,
+        retry_count: u32,
+        retention: Duration,
+    ) -> Result<bool> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin().await?;
+        prune_archive(&mut tx, retention, now).await?;
+        let ack = PendingAck { id, delivered_at, retry_count };
+        let archived = archive_delivery(&mut tx, consumer, &ack, now).await?;
+        tx.commit().await?;
+
+        Ok(archived)
+    }
+
+    async fn ack_batch(
+        &self,
+        consumer: &str,
+        acks: &[PendingAck],
+        archive_retention: Option<Duration>,
+        mode: BatchAckMode,
+    ) -> Result<BatchAckOutcome> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin().await?;
+        if let Some(retention) = archive_retention {
+            prune_archive(&mut tx, retention, now).await?;
+        }
+
+        let mut outcome = BatchAckOutcome::default();
+        for ack in acks {
+            // A savepoint per event, so a failed statement leaves no half-acked event.
+            let mut savepoint = tx.begin().await?;
+            let result = match archive_retention {
+                Some(_) => archive_delivery(&mut savepoint, consumer, ack, now).await,
+                None => delete_delivery(&mut savepoint, consumer, ack.id).await,
+            };
+            match result {
+                Ok(true) => {
+                    savepoint.commit().await?;
+                    outcome.acked.push(ack.id);
+                }
+                Ok(false) => outcome.missing.push(ack.id),
+                Err(e) => {
+                    warn!("Failed to ack event {} for {}: {}", ack.id, consumer, e);
+                    savepoint.rollback().await?;
+                    outcome.failed.push(ack.id);
+                }
+            }
+        }
+
+        if mode == BatchAckMode::AllOrNothing
+            && (!outcome.missing.is_empty() || !outcome.failed.is_empty())
+        {
+            tx.rollback().await?;
+            outcome.acked.clear();
+            return Ok(outcome);
+        }
+        tx.commit().await?;
+
+        Ok(outcome)
+    }
+
+    async fn list_archive(
+        &self,
+        query: &EventHistoryQuery,
+        limit: u32,
+    ) -> Result<(Vec<ArchivedEvent>, usize)> {
+        let mut count_q = QueryBuilder::new("SELECT COUNT(*) FROM events_archive");
+        push_archive_filter(&mut count_q, query);
+        let total: i64 = count_q.build_query_scalar().fetch_one(&self.pool).await?;
+
+        let mut list_q = QueryBuilder::new("SELECT * FROM events_archive");
+        push_archive_filter(&mut list_q, query);
+        list_q
+            .push(" ORDER BY timestamp_ms DESC, id DESC, consumer LIMIT ")
+            .push_bind(limit as i64)
+            .push(" OFFSET ")
+            .push_bind(query.offset.unwrap_or(0) as i64);
+        let rows = list_q.build().fetch_all(&self.pool).await?;
+
+        let events = rows
+            .into_iter()
+            .map(row_to_archived_event)
+            .collect::<Result<_>>()?;
+        Ok((events, total as usize))
+    }
+
+    async fn dead_letter(
+        &self,
+        consumer: &str,
+        event: &Event,
+        delivery_count: u32,
+        reason: &str,
+    ) -> Result<DeadLetter> {
+        let now = OffsetDateTime::now_utc();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO dead_letters (
+                id, consumer, event_type, herald_id, payload, priority, timestamp,
+                deliver_after, expires_at, coalesce_key, coalesced,
+                delivery_count, reason, dead_lettered_at
+            )
+            SELECT ?, ?, ?, ?, ?, ?, ?, ?, ?, e.coalesce_key, COALESCE(e.coalesced, 0), ?, ?, ?
+            FROM (SELECT ? AS id) AS d
+            LEFT JOIN events e ON e.id = d.id
+            "#,
+        )
+        .bind(event.id as i64)
+        .bind(consumer)
+        .bind(&event.event_type)
+        .bind(&event.herald_id)
+        .bind(event.payload.to_string())
+        .bind(event.priority.to_string())
+        .bind(format_time(event.timestamp)?)
+        .bind(event.deliver_after.map(format_time).transpose()?)
+        .bind(event.expires_at.map(format_time).transpose()?)
+        .bind(delivery_count as i64)
+        .bind(reason)
+        .bind(format_time(now)?)
+        .bind(event.id as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        delete_delivery(&mut tx, consumer, event.id).await?;
+
+        tx.commit().await?;
+
+        Ok(DeadLetter {
+            event: Event { status: EventStatus::Delivered, ..event.clone() },
+            consumer: consumer.to_string(),
+            delivery_count,
+            reason: reason.to_string(),
+            dead_lettered_at: now,
         })
     }
 
-    /// Load all pending events (for startup).
-    pub async fn load_all(&self) -> Result<Vec<Event>> {
-        let rows = sqlx::query("SELECT * FROM events ORDER BY id")
-            .fetch_all(&self.pool)
+    async fn list_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<DeadLetter>, usize)> {
+        let (filter, values) = dead_letter_filter(consumer, herald_id, event_type);
+
+        let count_query = format!("SELECT COUNT(*) FROM dead_letters WHERE {}", filter);
+        let mut count_q = sqlx::query_scalar::<_, i64>(&count_query);
+        for value in &values {
+            count_q = count_q.bind(*value);
+        }
+        let total = count_q.fetch_one(&self.pool).await?;
+
+        let list_query = format!(
+            "SELECT * FROM dead_letters WHERE {} ORDER BY dead_lettered_at DESC, id DESC LIMIT ?",
+            filter
+        );
+        let mut list_q = sqlx::query(&list_query);
+        for value in &values {
+            list_q = list_q.bind(*value);
+        }
+        let rows = list_q.bind(limit as i64).fetch_all(&self.pool).await?;
+
+        let dead_letters = rows
+            .into_iter()
+            .map(row_to_dead_letter)
+            .collect::<Result<_>>()?;
+        Ok((dead_letters, total as usize))
+    }
+
+    async fn get_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<DeadLetter>> {
+        let row = sqlx::query("SELECT * FROM dead_letters WHERE id = ? AND consumer = ?")
+            .bind(id as i64)
+            .bind(consumer)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.map(row_to_dead_letter).transpose()
+    }
+
+    async fn requeue_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<Event>> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query("SELECT * FROM dead_letters WHERE id = ? AND consumer = ?")
+            .bind(id as i64)
+            .bind(consumer)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let coalesce_key: Option<String> = row.get("coalesce_key");
+        let coalesced: i64 = row.get("coalesced");
+        let event = Event { status: EventStatus::Pending, ..row_to_dead_letter(row)?.event };
+
+        // Other consumers may still hold the event
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO events (
+                id, event_type, herald_id, payload, priority, timestamp,
+                deliver_after, expires_at, coalesce_key, coalesced
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(event.id as i64)
+        .bind(&event.event_type)
+        .bind(&event.herald_id)
+        .bind(event.payload.to_string())
+        .bind(event.priority.to_string())
+        .bind(format_time(event.timestamp)?)
+        .bind(event.deliver_after.map(format_time).transpose()?)
+        .bind(event.expires_at.map(format_time).transpose()?)
+        .bind(coalesce_key)
+        .bind(coalesced)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO deliveries (event_id, consumer) VALUES (?, ?)")
+            .bind(event.id as i64)
+            .bind(consumer)
+            .execute(&mut *tx)
             .await?;
 
-        rows.into_iter().map(row_to_event).collect()
+        sqlx::query("DELETE FROM dead_letters WHERE id = ? AND consumer = ?")
+            .bind(id as i64)
+            .bind(consumer)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some(event))
     }
 
-    /// Delete event by ID. Returns true if deleted.
-    pub async fn delete(&self, id: EventId) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM events WHERE id = ?")
+    async fn delete_dead_letter(&self, consumer: &str, id: EventId) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM dead_letters WHERE id = ? AND consumer = ?")
             .bind(id as i64)
+            .bind(consumer)
             .execute(&self.pool)
             .await?;
 
         Ok(result.rows_affected() > 0)
     }
+
+    async fn purge_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+    ) -> Result<u64> {
+        let (filter, values) = dead_letter_filter(consumer, herald_id, event_type);
+        let query = format!("DELETE FROM dead_letters WHERE {}", filter);
+        let mut q = sqlx::query(&query);
+        for value in &values {
+            q = q.bind(*value);
+        }
+
+        Ok(q.execute(&self.pool).await?.rows_affected())
+    }
+
+    async fn dead_letter_counts(&self) -> Result<HashMap<String, u64>> {
+        let rows =
+            sqlx::query("SELECT herald_id, COUNT(*) AS count FROM dead_letters GROUP BY herald_id")
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.get("herald_id"), row.get::<i64, _>("count") as u64))
+            .collect())
+    }
+
+    async fn load_consumers(&self) -> Result<Vec<ConsumerInfo>> {
+        let rows = sqlx::query("SELECT * FROM consumers ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(row_to_consumer).collect()
+    }
+
+    async fn upsert_consumer(
+        &self,
+        name: &str,
+        request: &UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO consumers (name, event_types, herald_ids, created_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET
+                event_types = excluded.event_types,
+                herald_ids = excluded.herald_ids
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(serde_json::to_string(&request.event_types)?)
+        .bind(serde_json::to_string(&request.herald_ids)?)
+        .bind(format_time(OffsetDateTime::now_utc())?)
+        .fetch_one(&self.pool)
+        .await?;
+
+        row_to_consumer(row)
+    }
+
+    async fn delete_consumer(&self, name: &str) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query("DELETE FROM consumers WHERE name = ?")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM deliveries WHERE consumer = ?")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM dead_letters WHERE consumer = ?")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        delete_orphaned_events(&mut tx).await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+}
+
+/// Deletes one consumer's delivery, then the event if no other consumer needs it.
+async fn delete_delivery(conn: &mut SqliteConnection, consumer: &str, id: EventId) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM deliveries WHERE consumer = ? AND event_id = ?")
+        .bind(consumer)
+        .bind(id as i64)
+        .execute(&mut *conn)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "DELETE FROM events WHERE id = ? AND NOT EXISTS (SELECT 1 FROM deliveries WHERE event_id = ?)",
+    )
+    .bind(id as i64)
+    .bind(id as i64)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(true)
+}
+
+/// Copies the consumer's delivery of an event to the archive and deletes it.
+/// Returns false if the consumer holds no delivery for the event.
+async fn archive_delivery(
+    conn: &mut SqliteConnection,
+    consumer: &str,
+    ack: &PendingAck,
+    now: OffsetDateTime,
+) -> Result<bool> {
+    let Some(row) = sqlx::query(
+        r#"
+        SELECT e.* FROM events e
+        JOIN deliveries d ON d.event_id = e.id
+        WHERE e.id = ? AND d.consumer = ?
+        "#,
+    )
+    .bind(ack.id as i64)
+    .bind(consumer)
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(false);
+    };
+    let event = row_to_event(row)?;
+
+    sqlx::query(
+        r#"
+        INSERT OR REPLACE INTO events_archive
+            (id, consumer, event_type, herald_id, payload, priority, timestamp, timestamp_ms,
+             delivered_at, acked_at, acked_at_ms, retry_count)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(ack.id as i64)
+    .bind(consumer)
+    .bind(&event.event_type)
+    .bind(&event.herald_id)
+    .bind(event.payload.to_string())
+    .bind(event.priority.to_string())
+    .bind(format_time(event.timestamp)?)
+    .bind(unix_ms(event.timestamp))
+    .bind(ack.delivered_at.map(format_time).transpose()?)
+    .bind(format_time(now)?)
+    .bind(unix_ms(now))
+    .bind(ack.retry_count as i64)
+    .execute(&mut *conn)
+    .await?;
+
+    delete_delivery(conn, consumer, ack.id).await
+}
+
+/// Deletes archived events acked longer than `retention` ago.
+async fn prune_archive(
+    conn: &mut SqliteConnection,
+    retention: Duration,
+    now: OffsetDateTime,
+) -> Result<()> {
+    sqlx::query("DELETE FROM events_archive WHERE acked_at_ms < ?")
+        .bind(unix_ms(now).saturating_sub(retention.as_millis() as i64))
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes events that no consumer holds a delivery for.
+async fn delete_orphaned_events(conn: &mut SqliteConnection) -> Result<()> {
+    sqlx::query(
+        "DELETE FROM events WHERE NOT EXISTS (SELECT 1 FROM deliveries WHERE event_id = events.id)",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Builds the WHERE clause for dead-letter filters, with the values to bind in order.
+fn dead_letter_filter<'a>(
+    consumer: Option<&'a str>,
+    herald_id: Option<&'a str>,
+    event_type: Option<&'a str>,
+) -> (String, Vec<&'a str>) {
+    let mut filter = String::from("1=1");
+    let mut values = Vec::new();
+    for (column, value) in
+        [("consumer", consumer), ("herald_id", herald_id), ("event_type", event_type)]
+    {
+        if let Some(value) = value {
+            filter.push_str(&format!(" AND {} = ?", column));
+            values.push(value);
+        }
+    }
+    (filter, values)
+}
+
+/// Appends the WHERE clause for an event history query.
+fn push_archive_filter<'a>(builder: &mut QueryBuilder<'a, Sqlite>, query: &'a EventHistoryQuery) {
+    builder.push(" WHERE 1=1");
+    for (column, value) in [
+        ("herald_id", query.herald_id.as_deref()),
+        ("event_type", query.event_type.as_deref()),
+        ("consumer", query.consumer.as_deref()),
+    ] {
+        if let Some(value) = value {
+            builder.push(format!(" AND {} = ", column)).push_bind(value);
+        }
+    }
+    if let Some(priority) = query.priority {
+        builder
+            .push(" AND priority = ")
+            .push_bind(priority.to_string());
+    }
+    if let Some(since) = query.since {
+        builder
+            .push(" AND timestamp_ms >= ")
+            .push_bind(unix_ms(since));
+    }
+    if let Some(until) = query.until {
+        builder
+            .push(" AND timestamp_ms < ")
+            .push_bind(unix_ms(until));
+    }
+}
+
+fn format_time(at: OffsetDateTime) -> Result<String> {
+    Ok(at.format(&time::format_description::well_known::Rfc3339)?)
+}
+
+fn parse_time(s: &str) -> Result<OffsetDateTime> {
+    Ok(OffsetDateTime::parse(
+        s,
+        &time::format_description::well_known::Rfc3339,
+    )?)
+}
+
+fn unix_ms(at: OffsetDateTime) -> i64 {
+    (at.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+fn row_to_consumer(row: sqlx::sqlite::SqliteRow) -> Result<ConsumerInfo> {
+    let created_at_str: String = row.get("created_at");
+    let created_at = OffsetDateTime::parse(
+        &created_at_str,
+        &time::format_description::well_known::Rfc3339,
+    )?;
+    let event_types: String = row.get("event_types");
+    let herald_ids: String = row.get("herald_ids");
+
+    Ok(ConsumerInfo {
+        name: row.get("name"),
+        event_types: serde_json::from_str(&event_types)?,
+        herald_ids: serde_json::from_str(&herald_ids)?,
+        created_at,
+        pending: 0,
+        delivered: 0,
+    })
+}
+
+fn row_to_dead_letter(row: sqlx::sqlite::SqliteRow) -> Result<DeadLetter> {
+    let dead_lettered_at_str: String = row.get("dead_lettered_at");
+    let dead_lettered_at = OffsetDateTime::parse(
+        &dead_lettered_at_str,
+        &time::format_description::well_known::Rfc3339,
+    )?;
+    let consumer: String = row.get("consumer");
+    let delivery_count = row.get::<i64, _>("delivery_count") as u32;
+    let reason: String = row.get("reason");
+    let deliver_after: Option<String> = row.get("deliver_after");
+    let expires_at: Option<String> = row.get("expires_at");
+
+    Ok(DeadLetter {
+        event: Event {
+            status: EventStatus::Delivered,
+            deliver_after: deliver_after.as_deref().map(parse_time).transpose()?,
+            expires_at: expires_at.as_deref().map(parse_time).transpose()?,
+            ..row_to_event(row)?
+        },
+        consumer,
+        delivery_count,
+        reason,
+        dead_lettered_at,
+    })
+}
+
+fn row_to_archived_event(row: sqlx::sqlite::SqliteRow) -> Result<ArchivedEvent> {
+    let parse = |s: &str| OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339);
+    let delivered_at: Option<String> = row.get("delivered_at");
+    let acked_at: String = row.get("acked_at");
+
+    Ok(ArchivedEvent {
+        consumer: row.get("consumer"),
+        delivered_at: delivered_at.as_deref().map(parse).transpose()?,
+        acked_at: parse(&acked_at)?,
+        retry_count: row.get::<i64, _>("retry_count") as u32,
+        event: Event { status: EventStatus::Acked, ..row_to_event(row)? },
+    })
 }
 
 fn row_to_event(row: sqlx::sqlite::SqliteRow) -> Result<Event> {
@@ -117,6 +1145,9 @@ fn row_to_event(row: sqlx::sqlite::SqliteRow) -> Result<Event> {
         priority,
         timestamp,
         status: EventStatus::Pending,
+        // Only kept for queued events; see `load_all`
+        deliver_after: None,
+        expires_at: None,
     })
 }
 
@@ -128,3 +1159,727 @@ fn parse_priority(s: &str) -> EventPriority {
         _ => EventPriority::Normal,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_request(herald_id: &str, event_type: &str) -> CreateEventRequest {
+        CreateEventRequest {
+            event_type: event_type.to_string(),
+            herald_id: herald_id.to_string(),
+            priority: EventPriority::High,
+            payload: serde_json::json!({ "n": 1 }),
+            timestamp: OffsetDateTime::now_utc(),
+            idempotency_key: None,
+            deliver_after: None,
+            expires_at: None,
+            coalesce_key: None,
+        }
+    }
+
+    fn default_consumer() -> Vec<String> {
+        vec![DEFAULT_CONSUMER.to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_record_deliveries_persists_count() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let a = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let b = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        store
+            .record_deliveries(DEFAULT_CONSUMER, &[a.id, b.id])
+            .await
+            .unwrap();
+        store
+            .record_deliveries(DEFAULT_CONSUMER, &[a.id])
+            .await
+            .unwrap();
+
+        let loaded = store.load_all().await.unwrap();
+        let counts: Vec<(EventId, u32)> = loaded.iter().map(|(_, e, c)| (e.id, *c)).collect();
+        assert_eq!(counts, vec![(a.id, 2), (b.id, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_moves_event() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let event = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        let dead = store
+            .dead_letter(DEFAULT_CONSUMER, &event, 4, "too many")
+            .await
+            .unwrap();
+        assert_eq!(dead.delivery_count, 4);
+
+        assert!(store.load_all().await.unwrap().is_empty());
+        let fetched = store
+            .get_dead_letter(DEFAULT_CONSUMER, event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.event.id, event.id);
+        assert_eq!(fetched.event.priority, EventPriority::High);
+        assert_eq!(fetched.event.payload, serde_json::json!({ "n": 1 }));
+        assert_eq!(fetched.consumer, DEFAULT_CONSUMER);
+        assert_eq!(fetched.reason, "too many");
+    }
+
+    #[tokio::test]
+    async fn test_requeue_dead_letter_keeps_id_and_resets_count() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let event = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        store
+            .record_deliveries(DEFAULT_CONSUMER, &[event.id])
+            .await
+            .unwrap();
+        store
+            .dead_letter(DEFAULT_CONSUMER, &event, 1, "too many")
+            .await
+            .unwrap();
+
+        let requeued = store
+            .requeue_dead_letter(DEFAULT_CONSUMER, event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(requeued.id, event.id);
+        assert_eq!(requeued.status, EventStatus::Pending);
+
+        assert!(
+            store
+                .get_dead_letter(DEFAULT_CONSUMER, event.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let loaded = store.load_all().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].2, 0);
+
+        assert!(
+            store
+                .requeue_dead_letter(DEFAULT_CONSUMER, event.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requeue_dead_letter_keeps_expiry_and_coalescing() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let expires_at =
+            OffsetDateTime::now_utc().replace_nanosecond(0).unwrap() + time::Duration::hours(1);
+        let req = CreateEventRequest {
+            expires_at: Some(expires_at),
+            coalesce_key: Some("alice".to_string()),
+            ..test_request("h", "chat")
+        };
+        let event = store
+            .insert(req.clone(), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let merged = Event { payload: serde_json::json!([{ "n": 1 }, { "n": 2 }]), ..event };
+        store.coalesce(&merged, 2, &req).await.unwrap();
+        store
+            .dead_letter(DEFAULT_CONSUMER, &merged, 3, "max_retries")
+            .await
+            .unwrap();
+        assert!(store.load_all().await.unwrap().is_empty());
+
+        let dead_letter = store
+            .get_dead_letter(DEFAULT_CONSUMER, merged.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(dead_letter.event.expires_at, Some(expires_at));
+
+        let requeued = store
+            .requeue_dead_letter(DEFAULT_CONSUMER, merged.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(requeued.expires_at, Some(expires_at));
+        assert_eq!(
+            store.load_all().await.unwrap()[0].1.expires_at,
+            Some(expires_at)
+        );
+        let target = store
+            .find_coalesce_target("h", "chat", "alice")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!((target.id, target.merged), (merged.id, 2));
+    }
+
+    #[tokio::test]
+    async fn test_list_purge_and_count_dead_letters_by_filter() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        for (herald, event_type) in [("a", "chat"), ("a", "timer"), ("b", "chat")] {
+            let event = store
+                .insert(test_request(herald, event_type), &default_consumer())
+                .await
+                .unwrap()
+                .into_event();
+            store
+                .dead_letter(DEFAULT_CONSUMER, &event, 1, "too many")
+                .await
+                .unwrap();
+        }
+
+        let (all, total) = store
+            .list_dead_letters(None, None, None, 100)
+            .await
+            .unwrap();
+        assert_eq!((all.len(), total), (3, 3));
+
+        let (page, total) = store
+            .list_dead_letters(None, Some("a"), None, 1)
+            .await
+            .unwrap();
+        assert_eq!((page.len(), total), (1, 2));
+
+        let (chat, _) = store
+            .list_dead_letters(None, None, Some("chat"), 100)
+            .await
+            .unwrap();
+        assert_eq!(chat.len(), 2);
+
+        let (other, _) = store
+            .list_dead_letters(Some("other"), None, None, 100)
+            .await
+            .unwrap();
+        assert!(other.is_empty());
+
+        let counts = store.dead_letter_counts().await.unwrap();
+        assert_eq!(counts.get("a"), Some(&2));
+        assert_eq!(counts.get("b"), Some(&1));
+
+        assert_eq!(
+            store
+                .purge_dead_letters(None, Some("a"), Some("chat"))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(store.purge_dead_letters(None, None, None).await.unwrap(), 2);
+        assert!(store.dead_letter_counts().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_event_deleted_once_every_consumer_acks() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let consumers = vec!["agent".to_string(), "observer".to_string()];
+        let event = store
+            .insert(test_request("h", "t"), &consumers)
+            .await
+            .unwrap()
+            .into_event();
+        assert_eq!(store.load_all().await.unwrap().len(), 2);
+
+        assert!(store.delete("agent", event.id).await.unwrap());
+        assert!(!store.delete("agent", event.id).await.unwrap());
+        let loaded = store.load_all().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].0, "observer");
+
+        // Dead-lettering the last delivery also removes the event
+        store
+            .dead_letter("observer", &event, 1, "too many")
+            .await
+            .unwrap();
+        assert!(store.load_all().await.unwrap().is_empty());
+
+        // Requeueing brings back only that consumer's delivery
+        store
+            .requeue_dead_letter("observer", event.id)
+            .await
+            .unwrap()
+            .unwrap();
+        let loaded = store.load_all().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].0, "observer");
+    }
+
+    #[tokio::test]
+    async fn test_insert_without_consumers_is_not_queued() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let event = store
+            .insert(test_request("h", "t"), &[])
+            .await
+            .unwrap()
+            .into_event();
+        assert!(event.id > 0);
+        assert!(store.load_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_upsert_and_delete_consumer() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let names = |consumers: Vec<ConsumerInfo>| -> Vec<String> {
+            consumers.into_iter().map(|c| c.name).collect()
+        };
+        assert_eq!(
+            names(store.load_consumers().await.unwrap()),
+            vec![DEFAULT_CONSUMER]
+        );
+
+        let request =
+            UpsertConsumerRequest { event_types: vec!["chat.*".to_string()], herald_ids: vec![] };
+        let created = store.upsert_consumer("observer", &request).await.unwrap();
+        assert_eq!(created.event_types, vec!["chat.*"]);
+
+        // Updating replaces filters but keeps the creation time
+        let request =
+            UpsertConsumerRequest { event_types: vec![], herald_ids: vec!["atrium".to_string()] };
+        let updated = store.upsert_consumer("observer", &request).await.unwrap();
+        assert!(updated.event_types.is_empty());
+        assert_eq!(updated.herald_ids, vec!["atrium"]);
+        assert_eq!(updated.created_at, created.created_at);
+
+        let consumers = vec![DEFAULT_CONSUMER.to_string(), "observer".to_string()];
+        let event = store
+            .insert(test_request("h", "t"), &consumers)
+            .await
+            .unwrap()
+            .into_event();
+        store
+            .dead_letter("observer", &event, 1, "too many")
+            .await
+            .unwrap();
+
+        assert!(store.delete_consumer("observer").await.unwrap());
+        assert!(!store.delete_consumer("observer").await.unwrap());
+        assert_eq!(
+            names(store.load_consumers().await.unwrap()),
+            vec![DEFAULT_CONSUMER]
+        );
+        assert_eq!(store.load_all().await.unwrap().len(), 1);
+        assert!(
+            store
+                .list_dead_letters(None, None, None, 100)
+                .await
+                .unwrap()
+                .0
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrates_single_consumer_database() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let now = format_time(OffsetDateTime::now_utc()).unwrap();
+
+        // Schema from before consumers existed
+        sqlx::query(
+            r#"
+            CREATE TABLE events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                event_type TEXT NOT NULL,
+                herald_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                priority TEXT NOT NULL DEFAULT 'normal',
+                timestamp TEXT NOT NULL,
+                delivery_count INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE dead_letters (
+                id INTEGER PRIMARY KEY,
+                event_type TEXT NOT NULL,
+                herald_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                priority TEXT NOT NULL DEFAULT 'normal',
+                timestamp TEXT NOT NULL,
+                delivery_count INTEGER NOT NULL,
+                reason TEXT NOT NULL,
+                dead_lettered_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO events (id, event_type, herald_id, payload, timestamp, delivery_count) VALUES (1, 't', 'h', '{}', ?, 2)",
+        )
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO dead_letters VALUES (2, 't', 'h', '{}', 'normal', ?, 3, 'too many', ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Migrations must be safe to run on every startup
+        SqliteEventStore::run_migrations(&pool).await.unwrap();
+        SqliteEventStore::run_migrations(&pool).await.unwrap();
+        let store = SqliteEventStore { pool, idempotency_window: DEFAULT_IDEMPOTENCY_WINDOW };
+
+        let loaded = store.load_all().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(
+            (loaded[0].0.as_str(), loaded[0].1.id, loaded[0].2),
+            (DEFAULT_CONSUMER, 1, 2)
+        );
+
+        let dead = store
+            .get_dead_letter(DEFAULT_CONSUMER, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(dead.delivery_count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_outlives_acked_event() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let mut request = test_request("h", "t");
+        request.idempotency_key = Some("k1".to_string());
+        let event = store
+            .insert(request, &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        assert!(store.delete(DEFAULT_CONSUMER, event.id).await.unwrap());
+
+        let found = store
+            .find_by_idempotency_key("h", "k1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, event.id);
+        assert_eq!(found.payload, event.payload);
+        // Keys are scoped per herald
+        assert!(
+            store
+                .find_by_idempotency_key("other", "k1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_with_taken_idempotency_key_writes_nothing() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let mut request = test_request("h", "t");
+        request.idempotency_key = Some("k1".to_string());
+        let first = store
+            .insert(request.clone(), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        // A concurrent push that missed the lookup must not claim the key again
+        let outcome = store.insert(request, &default_consumer()).await.unwrap();
+        assert!(matches!(outcome, PushOutcome::Duplicate(e) if e.id == first.id));
+
+        let loaded = store.load_all().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].1.id, first.id);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_expires_after_window() {
+        let store = SqliteEventStore::new(":memory:")
+            .await
+            .unwrap()
+            .with_idempotency_window(Duration::ZERO);
+        let mut request = test_request("h", "t");
+        request.idempotency_key = Some("k1".to_string());
+        store
+            .insert(request, &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(
+            store
+                .find_by_idempotency_key("h", "k1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_archive_moves_acked_event_and_filters_history() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let retention = Duration::from_secs(3600);
+        let chat = store
+            .insert(test_request("atrium", "chat.message"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let timer = store
+            .insert(
+                test_request("kairos", "kairos.trigger"),
+                &default_consumer(),
+            )
+            .await
+            .unwrap()
+            .into_event();
+
+        let delivered_at = OffsetDateTime::now_utc();
+        assert!(
+            store
+                .archive(DEFAULT_CONSUMER, chat.id, Some(delivered_at), 2, retention)
+                .await
+                .unwrap()
+        );
+        assert!(
+            store
+                .archive(DEFAULT_CONSUMER, timer.id, None, 0, retention)
+                .await
+                .unwrap()
+        );
+        // Already acked
+        assert!(
+            !store
+                .archive(DEFAULT_CONSUMER, chat.id, None, 0, retention)
+                .await
+                .unwrap()
+        );
+        assert!(store.load_all().await.unwrap().is_empty());
+
+        let (all, total) = store
+            .list_archive(&EventHistoryQuery::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        assert!(all.iter().all(|a| a.event.status == EventStatus::Acked));
+
+        let query = EventHistoryQuery {
+            herald_id: Some("atrium".to_string()),
+            priority: Some(EventPriority::High),
+            since: Some(chat.timestamp - time::Duration::seconds(1)),
+            ..Default::default()
+        };
+        let (events, total) = store.list_archive(&query, 10).await.unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(events[0].event.id, chat.id);
+        assert_eq!(events[0].retry_count, 2);
+        assert!(events[0].delivered_at.is_some());
+
+        let future = EventHistoryQuery {
+            since: Some(OffsetDateTime::now_utc() + time::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert_eq!(store.list_archive(&future, 10).await.unwrap().1, 0);
+
+        // Pagination keeps the total
+        let page = EventHistoryQuery { offset: Some(1), ..Default::default() };
+        let (events, total) = store.list_archive(&page, 10).await.unwrap();
+        assert_eq!((events.len(), total), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_archive_prunes_past_retention() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let a = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let b = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        store
+            .archive(DEFAULT_CONSUMER, a.id, None, 0, Duration::ZERO)
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        store
+            .archive(DEFAULT_CONSUMER, b.id, None, 0, Duration::ZERO)
+            .await
+            .unwrap();
+
+        let (events, _) = store
+            .list_archive(&EventHistoryQuery::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            events.iter().map(|e| e.event.id).collect::<Vec<_>>(),
+            vec![b.id]
+        );
+    }
+
+    fn pending(id: EventId) -> PendingAck {
+        PendingAck { id, delivered_at: None, retry_count: 0 }
+    }
+
+    #[tokio::test]
+    async fn test_ack_batch_best_effort_reports_missing() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let a = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let b = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        let outcome = store
+            .ack_batch(
+                DEFAULT_CONSUMER,
+                &[pending(a.id), pending(999), pending(b.id)],
+                Some(Duration::from_secs(3600)),
+                BatchAckMode::BestEffort,
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.acked, vec![a.id, b.id]);
+        assert_eq!(outcome.missing, vec![999]);
+        assert!(outcome.failed.is_empty());
+
+        assert!(store.load_all().await.unwrap().is_empty());
+        let (_, total) = store
+            .list_archive(&EventHistoryQuery::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_ack_batch_all_or_nothing_rolls_back() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let a = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let b = store
+            .insert(test_request("h", "t"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let retention = Some(Duration::from_secs(3600));
+
+        let outcome = store
+            .ack_batch(
+                DEFAULT_CONSUMER,
+                &[pending(a.id), pending(999)],
+                retention,
+                BatchAckMode::AllOrNothing,
+            )
+            .await
+            .unwrap();
+        assert!(outcome.acked.is_empty());
+        assert_eq!(outcome.missing, vec![999]);
+        assert_eq!(store.load_all().await.unwrap().len(), 2);
+        let (_, total) = store
+            .list_archive(&EventHistoryQuery::default(), 10)
+            .await
+            .unwrap();
+        assert_eq!(total, 0);
+
+        let outcome = store
+            .ack_batch(
+                DEFAULT_CONSUMER,
+                &[pending(a.id), pending(b.id)],
+                retention,
+                BatchAckMode::AllOrNothing,
+            )
+            .await
+            .unwrap();
+        assert_eq!(outcome.acked, vec![a.id, b.id]);
+        assert!(store.load_all().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_rewrites_queued_event() {
+        let store = SqliteEventStore::new(":memory:").await.unwrap();
+        let keyed = |key: &str| CreateEventRequest {
+            coalesce_key: Some(key.to_string()),
+            ..test_request("atrium", "chat.message")
+        };
+        let first = store
+            .insert(keyed("alice"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        let latest = store
+            .insert(keyed("alice"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+        store
+            .insert(keyed("bob"), &default_consumer())
+            .await
+            .unwrap()
+            .into_event();
+
+        let target = store
+            .find_coalesce_target("atrium", "chat.message", "alice")
+            .await
+            .unwrap();
+        assert_eq!(target, Some(CoalesceTarget { id: latest.id, merged: 0 }));
+        assert!(
+            store
+                .find_coalesce_target("kairos", "chat.message", "alice")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let merged = Event {
+            payload: serde_json::json!([1, 2]),
+            priority: EventPriority::Urgent,
+            ..latest.clone()
+        };
+        store.coalesce(&merged, 2, &keyed("alice")).await.unwrap();
+
+        let loaded = store.load_all().await.unwrap();
+        let (_, event, _) = loaded.iter().find(|(_, e, _)| e.id == latest.id).unwrap();
+        assert_eq!(event.payload, serde_json::json!([1, 2]));
+        assert_eq!(event.priority, EventPriority::Urgent);
+        assert_ne!(first.id, latest.id);
+        let target = store
+            .find_coalesce_target("atrium", "chat.message", "alice")
+            .await
+            .unwrap();
+        assert_eq!(target.map(|t| t.merged), Some(2));
+    }
+}
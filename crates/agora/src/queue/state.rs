@@ -1,8 +1,9 @@
-//! Delivery state tracking for events.
+//! Delivery state tracking for events, per consumer.
 
 use crate::config::RetryConfig;
-use crate::event::{Event, EventId};
-use std::collections::HashMap;
+use crate::event::{Event, EventId, EventPriority};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use time::OffsetDateTime;
 use tokio::sync::RwLock;
 
@@ -12,17 +13,162 @@ use tokio::sync::RwLock;
 /// - Some(t) = delivered at time t (waiting for ack)
 #[derive(Debug, Clone)]
 pub struct DeliveryState {
+    /// Consumer this state belongs to.
+    pub consumer: String,
     pub event: Event,
     /// None = pending, Some = delivered
     pub delivered_at: Option<OffsetDateTime>,
     pub retry_count: u32,
     pub next_retry_at: Option<OffsetDateTime>,
+    /// Total deliveries so far, including those before the last restart.
+    pub delivery_count: u32,
+}
+
+/// A consumer's queue at a point in time, for metrics.
+#[derive(Debug, Clone, Default)]
+pub struct QueueStats {
+    /// Events not yet delivered, including held-back ones.
+    pub pending: usize,
+    /// Events delivered and awaiting an ack.
+    pub delivered: usize,
+    /// Since when the oldest deliverable, undelivered event has been waiting:
+    /// its `deliver_after`, or else its timestamp.
+    pub oldest_pending: Option<OffsetDateTime>,
+}
+
+/// Delivery order: highest priority first, then oldest timestamp, then lowest id.
+type DeliveryKey = (Reverse<EventPriority>, OffsetDateTime, EventId);
+
+fn delivery_key(event: &Event) -> DeliveryKey {
+    (Reverse(event.priority), event.timestamp, event.id)
+}
+
+/// Event states plus the ordered indexes used for delivery.
+///
+/// Every event lives in exactly one index:
+/// - `ready`: pending or retry-due events, in delivery order
+/// - `waiting`: delivered events, keyed by `next_retry_at`
+///
+/// Pending events with a future `deliver_after` wait in `waiting` too.
+///
+/// Events that run out of retries leave both and wait in `exhausted`
+/// until the queue moves them to the dead-letter table; events past their
+/// `expires_at` likewise wait in `expired`.
+#[derive(Debug, Default)]
+struct CacheInner {
+    states: HashMap<EventId, DeliveryState>,
+    ready: BTreeSet<DeliveryKey>,
+    waiting: BTreeSet<(OffsetDateTime, EventId)>,
+    exhausted: Vec<DeliveryState>,
+    expired: Vec<DeliveryState>,
+}
+
+impl CacheInner {
+    fn insert_pending(&mut self, consumer: &str, event: Event, delivery_count: u32) {
+        let deliver_after = event
+            .deliver_after
+            .filter(|&at| at > OffsetDateTime::now_utc());
+        let state = DeliveryState {
+            consumer: consumer.to_string(),
+            event,
+            delivered_at: None,
+            retry_count: 0,
+            next_retry_at: deliver_after,
+            delivery_count,
+        };
+
+        match deliver_after {
+            // Held back until due, like a delivered event awaiting retry
+            Some(at) => {
+                self.remove(state.event.id);
+                self.waiting.insert((at, state.event.id));
+                self.states.insert(state.event.id, state);
+            }
+            None => self.insert_ready(state),
+        }
+    }
+
+    fn insert_ready(&mut self, state: DeliveryState) {
+        // Replacing an existing entry must not leave stale index keys behind
+        self.remove(state.event.id);
+
+        self.ready.insert(delivery_key(&state.event));
+        self.states.insert(state.event.id, state);
+    }
+
+    /// Moves delivered events whose retry time has passed, and held-back
+    /// events that are now due, into `ready`.
+    fn promote_due_retries(&mut self, now: OffsetDateTime) {
+        while let Some(&(retry_at, id)) = self.waiting.first() {
+            if retry_at > now {
+                break;
+            }
+            self.waiting.pop_first();
+            if let Some(state) = self.states.get(&id) {
+                self.ready.insert(delivery_key(&state.event));
+            }
+        }
+    }
+
+    fn remove(&mut self, id: EventId) -> Option<DeliveryState> {
+        let state = self.states.remove(&id)?;
+        self.ready.remove(&delivery_key(&state.event));
+        if let Some(retry_at) = state.next_retry_at {
+            self.waiting.remove(&(retry_at, id));
+        }
+        Some(state)
+    }
+
+    /// Moves every event past its `expires_at` into `expired`, whichever
+    /// index holds it.
+    fn sweep_expired(&mut self, now: OffsetDateTime) {
+        let ids: Vec<EventId> = self
+            .states
+            .values()
+            .filter(|s| s.event.expires_at.is_some_and(|at| at <= now))
+            .map(|s| s.event.id)
+            .collect();
+        for id in ids {
+            if let Some(state) = self.remove(id) {
+                self.expired.push(state);
+            }
+        }
+    }
+
+    /// Pending and delivered-awaiting-ack counts.
+    fn counts(&self) -> (usize, usize) {
+        let delivered = self
+            .states
+            .values()
+            .filter(|s| s.delivered_at.is_some())
+            .count();
+        (self.states.len() - delivered, delivered)
+    }
+
+    fn stats(&self, now: OffsetDateTime) -> QueueStats {
+        let (pending, delivered) = self.counts();
+        let oldest_pending = self
+            .states
+            .values()
+            .filter(|s| s.delivered_at.is_none())
+            .map(|s| {
+                s.event
+                    .deliver_after
+                    .map_or(s.event.timestamp, |at| at.max(s.event.timestamp))
+            })
+            .filter(|&since| since <= now)
+            .min();
+        QueueStats { pending, delivered, oldest_pending }
+    }
 }
 
 /// In-memory delivery state cache.
+///
+/// Each consumer has independent state: delivering or acking an event for
+/// one consumer does not affect any other.
 #[derive(Debug, Default)]
 pub struct DeliveryCache {
-    events: RwLock<HashMap<EventId, DeliveryState>>,
+    consumers: RwLock<HashMap<String, CacheInner>>,
 }
 
 impl DeliveryCache {
@@ -30,58 +176,298 @@ impl DeliveryCache {
         Self::default()
     }
 
-    /// Add event as pending (delivered_at = None).
-    pub async fn add_pending(&self, event: Event) {
-        let state =
-            DeliveryState { event, delivered_at: None, retry_count: 0, next_retry_at: None };
-        self.events.write().await.insert(state.event.id, state);
+    /// Start tracking a consumer (no-op if already tracked).
+    pub async fn add_consumer(&self, consumer: &str) {
+        self.consumers
+            .write()
+            .await
+            .entry(consumer.to_string())
+            .or_default();
     }
 
-    /// Load events from SQLite on startup.
-    pub async fn load_pending(&self, events: Vec<Event>) {
-        let mut cache = self.events.write().await;
-        for event in events {
-            let state =
-                DeliveryState { event, delivered_at: None, retry_count: 0, next_retry_at: None };
-            cache.insert(state.event.id, state);
+    /// Stop tracking a consumer, dropping all of its delivery state.
+    pub async fn remove_consumer(&self, consumer: &str) {
+        self.consumers.write().await.remove(consumer);
+    }
+
+    /// Add event as pending (delivered_at = None) for a consumer.
+    pub async fn add_pending(&self, consumer: &str, event: Event) {
+        let mut consumers = self.consumers.write().await;
+        let inner = consumers.entry(consumer.to_string()).or_default();
+        inner.insert_pending(consumer, event, 0);
+    }
+
+    /// Remove an event so it can be rewritten, but only if exactly the given
+    /// consumers hold it and none has ever been delivered it. Otherwise
+    /// nothing changes and None is returned.
+    ///
+    /// Checked and removed under one lock, so a concurrent fetch cannot
+    /// deliver the event in between; re-add it with [`DeliveryCache::add_pending`].
+    pub async fn take_undelivered(&self, id: EventId, consumers: &[String]) -> Option<Event> {
+        let mut all = self.consumers.write().await;
+        let undelivered = |s: &DeliveryState| s.delivered_at.is_none() && s.delivery_count == 0;
+        let holders = all
+            .values()
+            .filter(|inner| inner.states.contains_key(&id))
+            .count();
+        let eligible = holders == consumers.len()
+            && consumers.iter().all(|name| {
+                all.get(name)
+                    .and_then(|inner| inner.states.get(&id))
+                    .is_some_and(undelivered)
+            });
+        if !eligible {
+            return None;
+        }
+
+        let mut event = None;
+        for name in consumers {
+            if let Some(state) = all.get_mut(name).and_then(|inner| inner.remove(id)) {
+                event = Some(state.event);
+            }
+        }
+        event
+    }
+
+    /// Load events from SQLite on startup, with their consumers and persisted
+    /// delivery counts.
+    ///
+    /// Previously delivered events become pending again, but still count
+    /// towards `max_retries`.
+    pub async fn load_pending(&self, deliveries: Vec<(String, Event, u32)>) {
+        let mut consumers = self.consumers.write().await;
+        for (consumer, event, delivery_count) in deliveries {
+            let inner = consumers.entry(consumer.clone()).or_default();
+            inner.insert_pending(&consumer, event, delivery_count);
         }
     }
 
     /// Get events ready for delivery (pending + retries).
     /// Marks them as delivered and returns them.
-    pub async fn get_deliverable(&self, limit: u32, config: &RetryConfig) -> Vec<Event> {
-        let mut cache = self.events.write().await;
+    ///
+    /// Events are returned in delivery order: priority (urgent first),
+    /// then timestamp, then id. Pending events and due retries share
+    /// the same ordering, so `limit` always cuts off the least important ones.
+    ///
+    /// Events whose next delivery would exceed `config.max_retries` are not
+    /// returned; they are set aside for [`DeliveryCache::take_exhausted`].
+    /// Likewise, events past their `expires_at` are set aside for
+    /// [`DeliveryCache::take_expired`].
+    pub async fn get_deliverable(
+        &self,
+        consumer: &str,
+        limit: u32,
+        config: &RetryConfig,
+    ) -> Vec<Event> {
+        let mut consumers = self.consumers.write().await;
+        let Some(inner) = consumers.get_mut(consumer) else {
+            return Vec::new();
+        };
         let now = OffsetDateTime::now_utc();
-        let mut result = Vec::new();
+        inner.promote_due_retries(now);
 
-        for state in cache.values_mut() {
-            if result.len() >= limit as usize {
+        let mut result = Vec::new();
+        while result.len() < limit as usize {
+            let Some((_, _, id)) = inner.ready.pop_first() else {
                 break;
+            };
+            let Some(state) = inner.states.get_mut(&id) else {
+                continue;
+            };
+
+            if state.event.expires_at.is_some_and(|at| at <= now) {
+                if let Some(state) = inner.states.remove(&id) {
+                    inner.expired.push(state);
+                }
+                continue;
             }
 
-            let is_pending = state.delivered_at.is_none();
-            let is_retry_ready = state.next_retry_at.map(|t| t <= now).unwrap_or(false);
+            if config
+                .max_retries
+                .is_some_and(|max| state.delivery_count > max)
+            {
+                // Out of retries → set aside for the dead-letter table
+                if let Some(state) = inner.states.remove(&id) {
+                    inner.exhausted.push(state);
+                }
+                continue;
+            }
 
-            if is_pending {
-                // Pending → Delivered
+            if state.delivered_at.is_none() {
+                // Pending → Delivered (deliveries before a restart still count)
                 state.delivered_at = Some(now);
-                state.retry_count = 0;
-                state.next_retry_at = Some(now + retry_interval(0, config));
-                result.push(state.event.clone());
-            } else if is_retry_ready {
+                state.retry_count = state.delivery_count;
+            } else {
                 // Delivered + retry ready → fetch again
                 state.retry_count += 1;
-                state.next_retry_at = Some(now + retry_interval(state.retry_count, config));
-                result.push(state.event.clone());
             }
+            state.delivery_count += 1;
+
+            let retry_at = now + retry_interval(state.retry_count, config);
+            state.next_retry_at = Some(retry_at);
+            result.push(state.event.clone());
+            inner.waiting.insert((retry_at, id));
         }
 
         result
     }
 
-    /// Remove event (called on ack after SQLite delete succeeds).
-    pub async fn remove(&self, id: EventId) -> Option<Event> {
-        self.events.write().await.remove(&id).map(|s| s.event)
+    /// Current delivery state of a consumer's event, without changing it.
+    pub async fn get(&self, consumer: &str, id: EventId) -> Option<DeliveryState> {
+        let consumers = self.consumers.read().await;
+        consumers.get(consumer)?.states.get(&id).cloned()
+    }
+
+    /// All of a consumer's queued events in delivery order, without changing them.
+    pub async fn list(&self, consumer: &str) -> Vec<DeliveryState> {
+        let consumers = self.consumers.read().await;
+        let Some(inner) = consumers.get(consumer) else {
+            return Vec::new();
+        };
+        let mut states: Vec<DeliveryState> = inner.states.values().cloned().collect();
+        states.sort_by_key(|s| delivery_key(&s.event));
+        states
+    }
+
+    /// Make a consumer's queued event deliverable right away, as if it had
+    /// never been delivered. Earlier deliveries still count towards
+    /// `max_retries`.
+    pub async fn requeue(&self, consumer: &str, id: EventId) -> Option<Event> {
+        let mut consumers = self.consumers.write().await;
+        let inner = consumers.get_mut(consumer)?;
+        let mut state = inner.remove(id)?;
+        state.delivered_at = None;
+        state.next_retry_at = None;
+        let event = state.event.clone();
+        inner.insert_ready(state);
+        Some(event)
+    }
+
+    /// Remove a consumer's event (called on ack after SQLite delete succeeds).
+    pub async fn remove(&self, consumer: &str, id: EventId) -> Option<Event> {
+        let mut consumers = self.consumers.write().await;
+        consumers.get_mut(consumer)?.remove(id).map(|s| s.event)
+    }
+
+    /// Earliest time one of the consumer's delivered-but-unacked events
+    /// becomes ready for retry.
+    pub async fn next_retry_at(&self, consumer: &str) -> Option<OffsetDateTime> {
+        let consumers = self.consumers.read().await;
+        consumers
+            .get(consumer)?
+            .waiting
+            .first()
+            .map(|&(retry_at, _)| retry_at)
+    }
+
+    /// Pending and delivered-awaiting-ack counts for a consumer.
+    pub async fn counts(&self, consumer: &str) -> (usize, usize) {
+        let consumers = self.consumers.read().await;
+        consumers
+            .get(consumer)
+            .map(CacheInner::counts)
+            .unwrap_or_default()
+    }
+
+    /// Number of a herald's events queued for at least one consumer.
+    pub async fn herald_count(&self, herald_id: &str) -> usize {
+        let consumers = self.consumers.read().await;
+        consumers
+            .values()
+            .flat_map(|inner| inner.states.values())
+            .filter(|s| s.event.herald_id == herald_id)
+            .map(|s| s.event.id)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Remove a herald's least important event that no consumer has been
+    /// delivered yet: lowest priority first, then oldest. Events above
+    /// `max_priority` are never taken.
+    ///
+    /// Returns the event with the consumers that held it.
+    pub async fn take_droppable(
+        &self,
+        herald_id: &str,
+        max_priority: EventPriority,
+    ) -> Option<(Event, Vec<String>)> {
+        let mut all = self.consumers.write().await;
+
+        let mut delivered = HashSet::new();
+        let mut candidates = BTreeSet::new();
+        for state in all.values().flat_map(|inner| inner.states.values()) {
+            if state.event.herald_id != herald_id {
+                continue;
+            }
+            if state.delivered_at.is_some() || state.delivery_count > 0 {
+                delivered.insert(state.event.id);
+            } else if state.event.priority <= max_priority {
+                candidates.insert((state.event.priority, state.event.timestamp, state.event.id));
+            }
+        }
+        let (_, _, id) = candidates
+            .into_iter()
+            .find(|(_, _, id)| !delivered.contains(id))?;
+
+        let mut event = None;
+        let mut holders = Vec::new();
+        for (name, inner) in all.iter_mut() {
+            if let Some(state) = inner.remove(id) {
+                event = Some(state.event);
+                holders.push(name.clone());
+            }
+        }
+        Some((event?, holders))
+    }
+
+    /// Queue figures of every tracked consumer.
+    pub async fn stats(&self) -> Vec<(String, QueueStats)> {
+        let now = OffsetDateTime::now_utc();
+        let consumers = self.consumers.read().await;
+        consumers
+            .iter()
+            .map(|(name, inner)| (name.clone(), inner.stats(now)))
+            .collect()
+    }
+
+    /// Take events that exceeded `max_retries` during previous fetches, for all consumers.
+    pub async fn take_exhausted(&self) -> Vec<DeliveryState> {
+        let mut consumers = self.consumers.write().await;
+        consumers
+            .values_mut()
+            .flat_map(|inner| std::mem::take(&mut inner.exhausted))
+            .collect()
+    }
+
+    /// Take events found past their `expires_at` during previous fetches, for all consumers.
+    pub async fn take_expired(&self) -> Vec<DeliveryState> {
+        let mut consumers = self.consumers.write().await;
+        consumers
+            .values_mut()
+            .flat_map(|inner| std::mem::take(&mut inner.expired))
+            .collect()
+    }
+
+    /// Set aside every event past its `expires_at` for
+    /// [`DeliveryCache::take_expired`], for all consumers.
+    ///
+    /// Unlike [`DeliveryCache::get_deliverable`], this also catches events
+    /// held back or awaiting a retry, and those of consumers that never fetch.
+    pub async fn sweep_expired(&self) {
+        let now = OffsetDateTime::now_utc();
+        let mut consumers = self.consumers.write().await;
+        for inner in consumers.values_mut() {
+            inner.sweep_expired(now);
+        }
+    }
+
+    /// Put an exhausted or expired event back, e.g. when dead-lettering it failed.
+    /// It is retried on the next fetch.
+    pub async fn restore(&self, state: DeliveryState) {
+        let mut consumers = self.consumers.write().await;
+        if let Some(inner) = consumers.get_mut(&state.consumer) {
+            inner.insert_ready(state);
+        }
     }
 }
 
@@ -115,10 +501,17 @@ fn retry_interval(retry_count: u32, config: &RetryConfig) -> time::Duration {
 mod tests {
     use super::*;
     use crate::config::RetryConfig;
-    use crate::event::{EventPriority, EventStatus};
+    use crate::consumer::DEFAULT_CONSUMER;
+    use crate::event::EventStatus;
+    use time::macros::datetime;
 
     fn default_config() -> RetryConfig {
-        RetryConfig { base_interval_ms: 5000, multiplier: 2, max_interval_ms: 300000 }
+        RetryConfig {
+            base_interval_ms: 5000,
+            multiplier: 2,
+            max_interval_ms: 300000,
+            max_retries: None,
+        }
     }
 
     fn test_event(id: u64) -> Event {
@@ -130,9 +523,15 @@ mod tests {
             priority: EventPriority::Normal,
             timestamp: OffsetDateTime::now_utc(),
             status: EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
         }
     }
 
+    fn prioritized_event(id: u64, priority: EventPriority, timestamp: OffsetDateTime) -> Event {
+        Event { priority, timestamp, ..test_event(id) }
+    }
+
     // ============== retry_interval tests ==============
 
     #[test]
@@ -195,7 +594,12 @@ mod tests {
 
     #[test]
     fn test_retry_interval_multiplier_one() {
-        let config = RetryConfig { base_interval_ms: 5000, multiplier: 1, max_interval_ms: 300000 };
+        let config = RetryConfig {
+            base_interval_ms: 5000,
+            multiplier: 1,
+            max_interval_ms: 300000,
+            max_retries: None,
+        };
         // With multiplier = 1, no exponential growth
         assert_eq!(
             retry_interval(0, &config),
@@ -209,7 +613,12 @@ mod tests {
 
     #[test]
     fn test_retry_interval_multiplier_zero() {
-        let config = RetryConfig { base_interval_ms: 5000, multiplier: 0, max_interval_ms: 300000 };
+        let config = RetryConfig {
+            base_interval_ms: 5000,
+            multiplier: 0,
+            max_interval_ms: 300000,
+            max_retries: None,
+        };
         // With multiplier = 0, should return min(base, max)
         assert_eq!(
             retry_interval(0, &config),
@@ -223,7 +632,12 @@ mod tests {
 
     #[test]
     fn test_retry_interval_large_multiplier() {
-        let config = RetryConfig { base_interval_ms: 1000, multiplier: 10, max_interval_ms: 60000 };
+        let config = RetryConfig {
+            base_interval_ms: 1000,
+            multiplier: 10,
+            max_interval_ms: 60000,
+            max_retries: None,
+        };
         // 1000 * 10^0 = 1000
         assert_eq!(
             retry_interval(0, &config),
@@ -252,9 +666,11 @@ mod tests {
     async fn test_delivery_cache_add_pending() {
         let cache = DeliveryCache::new();
         let event = test_event(1);
-        cache.add_pending(event.clone()).await;
+        cache.add_pending(DEFAULT_CONSUMER, event.clone()).await;
 
-        let deliverable = cache.get_deliverable(10, &default_config()).await;
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
         assert_eq!(deliverable.len(), 1);
         assert_eq!(deliverable[0].id, 1);
     }
@@ -262,26 +678,32 @@ mod tests {
     #[tokio::test]
     async fn test_delivery_cache_fetch_marks_as_delivered() {
         let cache = DeliveryCache::new();
-        cache.add_pending(test_event(1)).await;
+        cache.add_pending(DEFAULT_CONSUMER, test_event(1)).await;
 
         // First fetch
-        let events = cache.get_deliverable(10, &default_config()).await;
+        let events = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
         assert_eq!(events.len(), 1);
 
         // Immediately fetch again - should not return (not retry-ready yet)
-        let events2 = cache.get_deliverable(10, &default_config()).await;
+        let events2 = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
         assert_eq!(events2.len(), 0);
     }
 
     #[tokio::test]
     async fn test_delivery_cache_remove() {
         let cache = DeliveryCache::new();
-        cache.add_pending(test_event(1)).await;
+        cache.add_pending(DEFAULT_CONSUMER, test_event(1)).await;
 
-        let removed = cache.remove(1).await;
+        let removed = cache.remove(DEFAULT_CONSUMER, 1).await;
         assert!(removed.is_some());
 
-        let deliverable = cache.get_deliverable(10, &default_config()).await;
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
         assert_eq!(deliverable.len(), 0);
     }
 
@@ -289,7 +711,7 @@ mod tests {
     async fn test_delivery_cache_remove_nonexistent() {
         let cache = DeliveryCache::new();
 
-        let removed = cache.remove(999).await;
+        let removed = cache.remove(DEFAULT_CONSUMER, 999).await;
         assert!(removed.is_none());
     }
 
@@ -297,10 +719,12 @@ mod tests {
     async fn test_delivery_cache_limit_respected() {
         let cache = DeliveryCache::new();
         for i in 1..=5 {
-            cache.add_pending(test_event(i)).await;
+            cache.add_pending(DEFAULT_CONSUMER, test_event(i)).await;
         }
 
-        let deliverable = cache.get_deliverable(3, &default_config()).await;
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 3, &default_config())
+            .await;
         assert_eq!(deliverable.len(), 3);
     }
 
@@ -308,17 +732,573 @@ mod tests {
     async fn test_delivery_cache_empty() {
         let cache = DeliveryCache::new();
 
-        let deliverable = cache.get_deliverable(10, &default_config()).await;
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
         assert_eq!(deliverable.len(), 0);
     }
 
     #[tokio::test]
     async fn test_delivery_cache_load_pending() {
         let cache = DeliveryCache::new();
-        let events = vec![test_event(1), test_event(2), test_event(3)];
+        let events = (1..=3)
+            .map(|id| (DEFAULT_CONSUMER.to_string(), test_event(id), 0))
+            .collect();
         cache.load_pending(events).await;
 
-        let deliverable = cache.get_deliverable(10, &default_config()).await;
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
         assert_eq!(deliverable.len(), 3);
     }
+
+    // ============== Delivery order tests ==============
+
+    fn ids(events: &[Event]) -> Vec<EventId> {
+        events.iter().map(|e| e.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_orders_by_priority() {
+        let cache = DeliveryCache::new();
+        let ts = datetime!(2025-03-12 09:00 UTC);
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(1, EventPriority::Low, ts),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(2, EventPriority::Normal, ts),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(3, EventPriority::Urgent, ts),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(4, EventPriority::High, ts),
+            )
+            .await;
+
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
+        assert_eq!(ids(&deliverable), vec![3, 4, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_same_priority_orders_by_timestamp_then_id() {
+        let cache = DeliveryCache::new();
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(1, EventPriority::Normal, datetime!(2025-03-12 09:05 UTC)),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(3, EventPriority::Normal, datetime!(2025-03-12 09:00 UTC)),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(2, EventPriority::Normal, datetime!(2025-03-12 09:00 UTC)),
+            )
+            .await;
+
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
+        assert_eq!(ids(&deliverable), vec![2, 3, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_limit_keeps_most_important() {
+        let cache = DeliveryCache::new();
+        let ts = datetime!(2025-03-12 09:00 UTC);
+        for i in 1..=50 {
+            cache
+                .add_pending(
+                    DEFAULT_CONSUMER,
+                    prioritized_event(i, EventPriority::Low, ts),
+                )
+                .await;
+        }
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(100, EventPriority::Urgent, ts),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(101, EventPriority::High, ts),
+            )
+            .await;
+
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 3, &default_config())
+            .await;
+        assert_eq!(ids(&deliverable), vec![100, 101, 1]);
+
+        // Remaining events continue in order
+        let deliverable = cache
+            .get_deliverable(DEFAULT_CONSUMER, 2, &default_config())
+            .await;
+        assert_eq!(ids(&deliverable), vec![2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_retries_follow_priority_order() {
+        // Zero interval makes every delivered event immediately retry-ready
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: None,
+        };
+        let cache = DeliveryCache::new();
+        let ts = datetime!(2025-03-12 09:00 UTC);
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(1, EventPriority::Low, ts),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(2, EventPriority::Urgent, ts),
+            )
+            .await;
+
+        let first = cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await;
+        assert_eq!(ids(&first), vec![2, 1]);
+
+        // A new normal event competes with the retries by priority
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(3, EventPriority::Normal, ts),
+            )
+            .await;
+        let second = cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await;
+        assert_eq!(ids(&second), vec![2, 3, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_remove_delivered_event() {
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: None,
+        };
+        let cache = DeliveryCache::new();
+        cache.add_pending(DEFAULT_CONSUMER, test_event(1)).await;
+        cache.add_pending(DEFAULT_CONSUMER, test_event(2)).await;
+
+        assert_eq!(
+            cache
+                .get_deliverable(DEFAULT_CONSUMER, 10, &config)
+                .await
+                .len(),
+            2
+        );
+
+        // Acked events must not come back as retries
+        assert!(cache.remove(DEFAULT_CONSUMER, 1).await.is_some());
+        assert_eq!(
+            ids(&cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await),
+            vec![2]
+        );
+    }
+
+    // ============== Max retries tests ==============
+
+    #[tokio::test]
+    async fn test_delivery_cache_exhausts_after_max_retries() {
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: Some(2),
+        };
+        let cache = DeliveryCache::new();
+        cache.add_pending(DEFAULT_CONSUMER, test_event(1)).await;
+
+        // Initial delivery + 2 retries
+        for _ in 0..3 {
+            assert_eq!(
+                ids(&cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await),
+                vec![1]
+            );
+        }
+        assert!(cache.take_exhausted().await.is_empty());
+
+        // Third retry exceeds the limit
+        assert!(
+            cache
+                .get_deliverable(DEFAULT_CONSUMER, 10, &config)
+                .await
+                .is_empty()
+        );
+        let exhausted = cache.take_exhausted().await;
+        assert_eq!(exhausted.len(), 1);
+        assert_eq!(exhausted[0].event.id, 1);
+        assert_eq!(exhausted[0].delivery_count, 3);
+
+        // Exhausted events are gone from the cache
+        assert!(cache.take_exhausted().await.is_empty());
+        assert!(cache.remove(DEFAULT_CONSUMER, 1).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_exhausted_do_not_count_towards_limit() {
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: Some(0),
+        };
+        let cache = DeliveryCache::new();
+        let ts = datetime!(2025-03-12 09:00 UTC);
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(1, EventPriority::Urgent, ts),
+            )
+            .await;
+        assert_eq!(
+            cache
+                .get_deliverable(DEFAULT_CONSUMER, 1, &config)
+                .await
+                .len(),
+            1
+        );
+
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(2, EventPriority::Low, ts),
+            )
+            .await;
+        assert_eq!(
+            ids(&cache.get_deliverable(DEFAULT_CONSUMER, 1, &config).await),
+            vec![2]
+        );
+        assert_eq!(cache.take_exhausted().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_persisted_delivery_count_counts_towards_limit() {
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: Some(2),
+        };
+        let cache = DeliveryCache::new();
+        cache
+            .load_pending(vec![
+                (DEFAULT_CONSUMER.to_string(), test_event(1), 2),
+                (DEFAULT_CONSUMER.to_string(), test_event(2), 3),
+            ])
+            .await;
+
+        // Event 1 has one retry left, event 2 is already over the limit
+        assert_eq!(
+            ids(&cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await),
+            vec![1]
+        );
+        let exhausted = cache.take_exhausted().await;
+        assert_eq!(exhausted.len(), 1);
+        assert_eq!(exhausted[0].event.id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_restore_exhausted() {
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: Some(0),
+        };
+        let cache = DeliveryCache::new();
+        cache.add_pending(DEFAULT_CONSUMER, test_event(1)).await;
+        cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await;
+        cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await;
+
+        let exhausted = cache.take_exhausted().await;
+        assert_eq!(exhausted.len(), 1);
+        cache.restore(exhausted.into_iter().next().unwrap()).await;
+
+        // Restored event is exhausted again on the next fetch
+        assert!(
+            cache
+                .get_deliverable(DEFAULT_CONSUMER, 10, &config)
+                .await
+                .is_empty()
+        );
+        assert_eq!(cache.take_exhausted().await.len(), 1);
+    }
+
+    // ============== Consumer tests ==============
+
+    #[tokio::test]
+    async fn test_delivery_cache_consumers_are_independent() {
+        let cache = DeliveryCache::new();
+        cache.add_pending("agent", test_event(1)).await;
+        cache.add_pending("observer", test_event(1)).await;
+        cache.add_pending("observer", test_event(2)).await;
+
+        // Delivering to one consumer leaves the other's copy pending
+        assert_eq!(
+            ids(&cache.get_deliverable("agent", 10, &default_config()).await),
+            vec![1]
+        );
+        assert_eq!(cache.counts("agent").await, (0, 1));
+        assert_eq!(cache.counts("observer").await, (2, 0));
+
+        // Acking for one consumer does not ack for the other
+        assert!(cache.remove("agent", 1).await.is_some());
+        assert_eq!(
+            ids(&cache
+                .get_deliverable("observer", 10, &default_config())
+                .await),
+            vec![1, 2]
+        );
+        assert!(
+            cache
+                .get_deliverable("unknown", 10, &default_config())
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_exhausted_keep_their_consumer() {
+        let config = RetryConfig {
+            base_interval_ms: 0,
+            multiplier: 2,
+            max_interval_ms: 0,
+            max_retries: Some(0),
+        };
+        let cache = DeliveryCache::new();
+        cache.add_pending("agent", test_event(1)).await;
+        cache.add_pending("observer", test_event(1)).await;
+        cache.get_deliverable("agent", 10, &config).await;
+        cache.get_deliverable("agent", 10, &config).await;
+
+        let exhausted = cache.take_exhausted().await;
+        assert_eq!(exhausted.len(), 1);
+        assert_eq!(exhausted[0].consumer, "agent");
+        assert_eq!(cache.counts("observer").await, (1, 0));
+
+        cache.restore(exhausted.into_iter().next().unwrap()).await;
+        assert_eq!(cache.counts("agent").await, (0, 1));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_remove_consumer() {
+        let cache = DeliveryCache::new();
+        cache.add_consumer("observer").await;
+        cache.add_pending("observer", test_event(1)).await;
+
+        cache.remove_consumer("observer").await;
+        assert!(
+            cache
+                .get_deliverable("observer", 10, &default_config())
+                .await
+                .is_empty()
+        );
+        assert_eq!(cache.counts("observer").await, (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_holds_back_until_deliver_after() {
+        let cache = DeliveryCache::new();
+        let deliver_after = OffsetDateTime::now_utc() + time::Duration::milliseconds(50);
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                Event { deliver_after: Some(deliver_after), ..test_event(1) },
+            )
+            .await;
+        cache.add_pending(DEFAULT_CONSUMER, test_event(2)).await;
+
+        let events = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
+        assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(cache.counts(DEFAULT_CONSUMER).await, (1, 1));
+        // Waiting fetches wake when the held-back event falls due
+        assert_eq!(
+            cache.next_retry_at(DEFAULT_CONSUMER).await,
+            Some(deliver_after)
+        );
+
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+        let events = cache
+            .get_deliverable(DEFAULT_CONSUMER, 10, &default_config())
+            .await;
+        assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_stats_skip_held_back_events() {
+        let cache = DeliveryCache::new();
+        cache.add_consumer("idle").await;
+        let old = datetime!(2024-01-01 00:00 UTC);
+        let held_back = OffsetDateTime::now_utc() + time::Duration::hours(1);
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                prioritized_event(1, EventPriority::Normal, old),
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                Event {
+                    deliver_after: Some(held_back),
+                    ..prioritized_event(2, EventPriority::Low, old)
+                },
+            )
+            .await;
+        cache.add_pending(DEFAULT_CONSUMER, test_event(3)).await;
+        cache
+            .get_deliverable(DEFAULT_CONSUMER, 1, &default_config())
+            .await;
+
+        let stats: HashMap<_, _> = cache.stats().await.into_iter().collect();
+        let default = &stats[DEFAULT_CONSUMER];
+        assert_eq!((default.pending, default.delivered), (2, 1));
+        // Event 1 was delivered and event 2 is held back
+        assert!(default.oldest_pending.is_some_and(|since| since > old));
+        assert!(stats["idle"].oldest_pending.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_sets_aside_expired() {
+        let config = RetryConfig { base_interval_ms: 0, max_interval_ms: 0, ..default_config() };
+        let cache = DeliveryCache::new();
+        let past = OffsetDateTime::now_utc() - time::Duration::seconds(1);
+        let soon = OffsetDateTime::now_utc() + time::Duration::milliseconds(50);
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                Event { expires_at: Some(past), ..test_event(1) },
+            )
+            .await;
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                Event { expires_at: Some(soon), ..test_event(2) },
+            )
+            .await;
+
+        let events = cache.get_deliverable(DEFAULT_CONSUMER, 10, &config).await;
+        assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2]);
+        let expired = cache.take_expired().await;
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].event.id, 1);
+
+        // An unacked event is not redelivered once it expires
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+        assert!(
+            cache
+                .get_deliverable(DEFAULT_CONSUMER, 10, &config)
+                .await
+                .is_empty()
+        );
+        assert_eq!(cache.take_expired().await[0].event.id, 2);
+        assert_eq!(cache.counts(DEFAULT_CONSUMER).await, (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_sweeps_expired_from_every_set() {
+        let config = RetryConfig { base_interval_ms: 60_000, ..default_config() };
+        let cache = DeliveryCache::new();
+        let now = OffsetDateTime::now_utc();
+        let soon = now + time::Duration::milliseconds(50);
+        // Held back past its expiry
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                Event {
+                    deliver_after: Some(now + time::Duration::hours(1)),
+                    expires_at: Some(soon),
+                    ..test_event(1)
+                },
+            )
+            .await;
+        // Delivered and waiting for a retry
+        cache
+            .add_pending(
+                DEFAULT_CONSUMER,
+                Event { expires_at: Some(soon), ..test_event(2) },
+            )
+            .await;
+        assert_eq!(
+            cache
+                .get_deliverable(DEFAULT_CONSUMER, 10, &config)
+                .await
+                .len(),
+            1
+        );
+        // Queued for a consumer that never fetches
+        cache
+            .add_pending("idle", Event { expires_at: Some(soon), ..test_event(3) })
+            .await;
+        cache.add_pending("idle", test_event(4)).await;
+
+        cache.sweep_expired().await;
+        assert!(cache.take_expired().await.is_empty());
+
+        tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+        cache.sweep_expired().await;
+        let mut expired: Vec<EventId> = cache
+            .take_expired()
+            .await
+            .iter()
+            .map(|s| s.event.id)
+            .collect();
+        expired.sort();
+        assert_eq!(expired, vec![1, 2, 3]);
+        assert_eq!(cache.counts(DEFAULT_CONSUMER).await, (0, 0));
+        assert_eq!(cache.counts("idle").await, (1, 0));
+        assert!(cache.next_retry_at(DEFAULT_CONSUMER).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delivery_cache_take_undelivered() {
+        let cache = DeliveryCache::new();
+        let both = vec!["agent".to_string(), "observer".to_string()];
+        cache.add_pending("agent", test_event(1)).await;
+        cache.add_pending("observer", test_event(1)).await;
+
+        // Every holder must be listed
+        assert!(cache.take_undelivered(1, &both[..1]).await.is_none());
+        assert_eq!(cache.take_undelivered(1, &both).await.unwrap().id, 1);
+        assert_eq!(cache.counts("agent").await, (0, 0));
+        assert_eq!(cache.counts("observer").await, (0, 0));
+
+        // Delivered to one consumer: left alone for all
+        cache.add_pending("agent", test_event(2)).await;
+        cache.add_pending("observer", test_event(2)).await;
+        cache.get_deliverable("agent", 10, &default_config()).await;
+        assert!(cache.take_undelivered(2, &both).await.is_none());
+        assert_eq!(cache.counts("observer").await, (1, 0));
+    }
 }
@@ -0,0 +1,215 @@
+//! Storage backend abstraction for the event queue.
+
+use crate::consumer::{ConsumerInfo, UpsertConsumerRequest};
+use crate::event::{
+    ArchivedEvent, BatchAckMode, CreateEventRequest, DeadLetter, Event, EventHistoryQuery, EventId,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// How long an idempotency key is remembered unless configured otherwise.
+pub const DEFAULT_IDEMPOTENCY_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An event to acknowledge as part of [`EventStore::ack_batch`], with the
+/// delivery state recorded if it is archived.
+#[derive(Debug, Clone, Copy)]
+pub struct PendingAck {
+    pub id: EventId,
+    pub delivered_at: Option<OffsetDateTime>,
+    pub retry_count: u32,
+}
+
+/// Per-event result of [`EventStore::ack_batch`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchAckOutcome {
+    /// Events whose delivery was deleted (or archived).
+    pub acked: Vec<EventId>,
+    /// Events the consumer holds no delivery for.
+    pub missing: Vec<EventId>,
+    /// Events whose delete failed with a storage error.
+    pub failed: Vec<EventId>,
+}
+
+/// What [`EventStore::insert`] or [`EventStore::coalesce`] did with a push.
+#[derive(Debug, Clone)]
+pub enum PushOutcome {
+    /// The push was stored; holds the inserted or merged event.
+    Stored(Event),
+    /// Another push already took the idempotency key within the window, so
+    /// nothing was written; holds the event that push was stored as.
+    Duplicate(Event),
+}
+
+/// The queued event a push with a coalesce key may be merged into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoalesceTarget {
+    pub id: EventId,
+    /// Payloads already merged into its array payload; 0 while it still
+    /// carries its payload as pushed.
+    pub merged: usize,
+}
+
+#[cfg(test)]
+impl PushOutcome {
+    /// The stored event, or the original one for a duplicate.
+    pub fn into_event(self) -> Event {
+        match self {
+            Self::Stored(event) | Self::Duplicate(event) => event,
+        }
+    }
+}
+
+/// Durable state behind [`EventQueue`](super::EventQueue): queued events and
+/// their per-consumer deliveries, idempotency keys, the archive, dead letters
+/// and consumers.
+///
+/// An event is kept while at least one consumer still holds a delivery for
+/// it. The `default` consumer always exists. Delivery state beyond the
+/// delivery count lives in [`DeliveryCache`](super::DeliveryCache), not here.
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    /// Find the event first pushed with this herald's idempotency key, if the
+    /// key is still within the window: as it is now while still queued, with
+    /// any payloads merged into it since, and as first pushed once gone.
+    async fn find_by_idempotency_key(
+        &self,
+        herald_id: &str,
+        idempotency_key: &str,
+    ) -> Result<Option<Event>>;
+
+    /// Insert new event and queue it for the given consumers, remembering its
+    /// idempotency key if it has one.
+    /// Returns the created event with ID.
+    ///
+    /// The key is claimed atomically with the insert: if a concurrent push
+    /// claimed it first, nothing is written and its event is returned as
+    /// [`PushOutcome::Duplicate`].
+    async fn insert(&self, req: CreateEventRequest, consumers: &[String]) -> Result<PushOutcome>;
+
+    /// Most recent queued event from this herald with the given type and
+    /// coalesce key: the only one later events may still be merged into.
+    async fn find_coalesce_target(
+        &self,
+        herald_id: &str,
+        event_type: &str,
+        coalesce_key: &str,
+    ) -> Result<Option<CoalesceTarget>>;
+
+    /// Rewrite a queued event's payload and priority after `req` was merged
+    /// into it, now an array of `count` payloads, remembering `req`'s
+    /// idempotency key as belonging to it.
+    ///
+    /// Like [`EventStore::insert`], leaves the event untouched and returns
+    /// [`PushOutcome::Duplicate`] if the key was already taken.
+    async fn coalesce(
+        &self,
+        merged: &Event,
+        count: usize,
+        req: &CreateEventRequest,
+    ) -> Result<PushOutcome>;
+
+    /// Load all queued deliveries as (consumer, event, delivery count), for startup.
+    /// Events keep their `deliver_after` and `expires_at`.
+    async fn load_all(&self) -> Result<Vec<(String, Event, u32)>>;
+
+    /// Increment the consumer's delivery count of the given events.
+    async fn record_deliveries(&self, consumer: &str, ids: &[EventId]) -> Result<()>;
+
+    /// Delete the consumer's delivery of an event. Returns true if deleted.
+    /// The event itself is deleted once no consumer still needs it.
+    async fn delete(&self, consumer: &str, id: EventId) -> Result<bool>;
+
+    /// Like [`EventStore::delete`], but first copies the event to the
+    /// archive. Archived events older than `retention` are pruned.
+    async fn archive(
+        &self,
+        consumer: &str,
+        id: EventId,
+        delivered_at: Option<OffsetDateTime>,
+        retry_count: u32,
+        retention: Duration,
+    ) -> Result<bool>;
+
+    /// Ack a batch of the consumer's deliveries in a single transaction,
+    /// archiving them first if `archive_retention` is set.
+    ///
+    /// With [`BatchAckMode::AllOrNothing`], any missing or failed event rolls
+    /// the whole batch back: `acked` is then empty and `missing` and `failed`
+    /// name the events that caused it. Returns an error only if the
+    /// transaction itself cannot be started or committed.
+    async fn ack_batch(
+        &self,
+        consumer: &str,
+        acks: &[PendingAck],
+        archive_retention: Option<Duration>,
+        mode: BatchAckMode,
+    ) -> Result<BatchAckOutcome>;
+
+    /// List archived events matching the query, most recent first, with the
+    /// total matching count.
+    async fn list_archive(
+        &self,
+        query: &EventHistoryQuery,
+        limit: u32,
+    ) -> Result<(Vec<ArchivedEvent>, usize)>;
+
+    /// Move a consumer's delivery of an event to the dead-letter table.
+    async fn dead_letter(
+        &self,
+        consumer: &str,
+        event: &Event,
+        delivery_count: u32,
+        reason: &str,
+    ) -> Result<DeadLetter>;
+
+    /// List dead-lettered events, newest first.
+    /// Returns the requested page and the total count matching the filter.
+    async fn list_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+        limit: u32,
+    ) -> Result<(Vec<DeadLetter>, usize)>;
+
+    /// Get a consumer's dead-lettered event by ID.
+    async fn get_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<DeadLetter>>;
+
+    /// Move a consumer's dead-lettered event back into its queue as pending.
+    /// The event keeps its ID; its delivery count starts over.
+    async fn requeue_dead_letter(&self, consumer: &str, id: EventId) -> Result<Option<Event>>;
+
+    /// Delete a consumer's dead-lettered event by ID. Returns true if deleted.
+    async fn delete_dead_letter(&self, consumer: &str, id: EventId) -> Result<bool>;
+
+    /// Delete all dead-lettered events matching the filter. Returns the number deleted.
+    async fn purge_dead_letters(
+        &self,
+        consumer: Option<&str>,
+        herald_id: Option<&str>,
+        event_type: Option<&str>,
+    ) -> Result<u64>;
+
+    /// Count dead-lettered events per herald.
+    async fn dead_letter_counts(&self) -> Result<HashMap<String, u64>>;
+
+    /// Load all consumers (for startup).
+    async fn load_consumers(&self) -> Result<Vec<ConsumerInfo>>;
+
+    /// Create a consumer, or replace the filters of an existing one.
+    async fn upsert_consumer(
+        &self,
+        name: &str,
+        request: &UpsertConsumerRequest,
+    ) -> Result<ConsumerInfo>;
+
+    /// Delete a consumer with its deliveries and dead letters. Returns true if deleted.
+    async fn delete_consumer(&self, name: &str) -> Result<bool>;
+
+    /// Release the store's resources, on shutdown. Nothing may use the store
+    /// after.
+    async fn close(&self) {}
+}
@@ -0,0 +1,7 @@
+//! Event payload schemas.
+
+mod store;
+mod types;
+
+pub use store::SqliteSchemaStore;
+pub use types::*;
@@ -0,0 +1,90 @@
+//! SQLite persistence for herald-registered schemas.
+
+use anyhow::Result;
+use sqlx::{Row, SqlitePool};
+
+use crate::schema::EventSchema;
+
+/// SQLite-backed storage of the schemas heralds registered, so they survive
+/// restarts. Schemas from config are not stored.
+#[derive(Clone)]
+pub struct SqliteSchemaStore {
+    pool: SqlitePool,
+}
+
+impl SqliteSchemaStore {
+    pub async fn new(database_path: &str) -> Result<Self> {
+        let db_url = format!("sqlite:{}?mode=rwc", database_path);
+        let pool = SqlitePool::connect(&db_url).await?;
+        Self::run_migrations(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// Closes the connection pool, on shutdown.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn run_migrations(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS event_schemas (
+                event_type TEXT PRIMARY KEY,
+                herald_id TEXT NOT NULL,
+                schema TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Load all stored schemas, for startup.
+    pub async fn load_all(&self) -> Result<Vec<EventSchema>> {
+        let rows = sqlx::query("SELECT * FROM event_schemas ORDER BY event_type")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let schema: String = row.get("schema");
+                Ok(EventSchema {
+                    event_type: row.get("event_type"),
+                    schema: serde_json::from_str(&schema)?,
+                    herald_id: Some(row.get("herald_id")),
+                })
+            })
+            .collect()
+    }
+
+    /// Replace all of a herald's schemas.
+    pub async fn replace(&self, herald_id: &str, schemas: &[EventSchema]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM event_schemas WHERE herald_id = ?")
+            .bind(herald_id)
+            .execute(&mut *tx)
+            .await?;
+        for schema in schemas {
+            sqlx::query(
+                r#"
+                INSERT INTO event_schemas (event_type, herald_id, schema)
+                VALUES (?, ?, ?)
+                ON CONFLICT(event_type) DO UPDATE SET
+                    herald_id = excluded.herald_id,
+                    schema = excluded.schema
+                "#,
+            )
+            .bind(&schema.event_type)
+            .bind(herald_id)
+            .bind(serde_json::to_string(&schema.schema)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+pub use agora_common::schema::*;
+
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use super::SqliteSchemaStore;
+
+/// Schema registry errors.
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    /// The schema is not a valid JSON Schema.
+    #[error("invalid schema for event type '{event_type}': {message}")]
+    Invalid { event_type: String, message: String },
+    /// Another herald, or Agora's config, has a different schema for the event type.
+    #[error("event type '{event_type}' already has a different schema from {owner}")]
+    Conflict { event_type: String, owner: String },
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+struct CompiledSchema {
+    schema: EventSchema,
+    validator: jsonschema::Validator,
+}
+
+impl CompiledSchema {
+    fn compile(schema: EventSchema) -> Result<Self, SchemaError> {
+        match jsonschema::validator_for(&schema.schema) {
+            Ok(validator) => Ok(Self { schema, validator }),
+            Err(e) => {
+                Err(SchemaError::Invalid { event_type: schema.event_type, message: e.to_string() })
+            }
+        }
+    }
+}
+
+/// A herald's schemas, checked by [`SchemaRegistry::prepare`] and ready to
+/// replace its earlier ones.
+pub struct PreparedSchemas {
+    herald_id: String,
+    schemas: Vec<CompiledSchema>,
+}
+
+/// Payload schemas by event type, cached in memory; those registered by
+/// heralds are persisted in SQLite.
+///
+/// Each event type has at most one schema, from config or from the one herald
+/// that registered it. Config schemas cannot be replaced by heralds. Events of
+/// types without a schema are not validated.
+pub struct SchemaRegistry {
+    store: SqliteSchemaStore,
+    schemas: RwLock<HashMap<String, CompiledSchema>>,
+}
+
+impl SchemaRegistry {
+    /// Creates a registry from the configured schemas, loading the ones
+    /// heralds registered before.
+    pub async fn new(
+        store: SqliteSchemaStore,
+        configured: HashMap<String, serde_json::Value>,
+    ) -> Result<Self, SchemaError> {
+        let mut schemas = HashMap::new();
+        for (event_type, schema) in configured {
+            let schema = EventSchema { event_type: event_type.clone(), schema, herald_id: None };
+            schemas.insert(event_type, CompiledSchema::compile(schema)?);
+        }
+
+        for schema in store.load_all().await? {
+            if schemas.contains_key(&schema.event_type) {
+                warn!(
+                    "Ignoring stored schema for {}: it is configured",
+                    schema.event_type
+                );
+                continue;
+            }
+            match CompiledSchema::compile(schema) {
+                Ok(compiled) => {
+                    schemas.insert(compiled.schema.event_type.clone(), compiled);
+                }
+                Err(e) => warn!("Ignoring stored schema: {}", e),
+            }
+        }
+
+        Ok(Self { store, schemas: RwLock::new(schemas) })
+    }
+
+    /// Closes the store, on shutdown.
+    pub async fn close(&self) {
+        self.store.close().await;
+    }
+
+    /// Checks a herald's schemas: each must be a valid JSON Schema, and must
+    /// not differ from a schema the event type already has from elsewhere.
+    ///
+    /// Schemas identical to one from elsewhere are left to their owner.
+    pub async fn prepare(
+        &self,
+        herald_id: &str,
+        schemas: HashMap<String, serde_json::Value>,
+    ) -> Result<PreparedSchemas, SchemaError> {
+        let registered = self.schemas.read().await;
+        let mut prepared = Vec::new();
+
+        for (event_type, schema) in schemas {
+            if let Some(existing) = registered.get(&event_type)
+                && existing.schema.herald_id.as_deref() != Some(herald_id)
+            {
+                if existing.schema.schema == schema {
+                    continue;
+                }
+                return Err(SchemaError::Conflict {
+                    event_type,
+                    owner: match &existing.schema.herald_id {
+                        Some(owner) => format!("herald '{}'", owner),
+                        None => "config".to_string(),
+                    },
+                });
+            }
+
+            let schema = EventSchema { event_type, schema, herald_id: Some(herald_id.to_string()) };
+            prepared.push(CompiledSchema::compile(schema)?);
+        }
+
+        Ok(PreparedSchemas { herald_id: herald_id.to_string(), schemas: prepared })
+    }
+
+    /// Replaces a herald's schemas with prepared ones.
+    pub async fn replace(&self, prepared: PreparedSchemas) -> anyhow::Result<()> {
+        let PreparedSchemas { herald_id, schemas: compiled } = prepared;
+        let mut schemas = self.schemas.write().await;
+
+        let stored: Vec<EventSchema> = compiled.iter().map(|c| c.schema.clone()).collect();
+        self.store.replace(&herald_id, &stored).await?;
+
+        schemas.retain(|_, c| c.schema.herald_id.as_deref() != Some(herald_id.as_str()));
+        for compiled in compiled {
+            schemas.insert(compiled.schema.event_type.clone(), compiled);
+        }
+        Ok(())
+    }
+
+    /// Removes a herald's schemas, when it unregisters.
+    pub async fn remove_herald(&self, herald_id: &str) -> anyhow::Result<()> {
+        let mut schemas = self.schemas.write().await;
+        self.store.replace(herald_id, &[]).await?;
+        schemas.retain(|_, c| c.schema.herald_id.as_deref() != Some(herald_id));
+        Ok(())
+    }
+
+    /// Gets the schema for an event type.
+    pub async fn get(&self, event_type: &str) -> Option<EventSchema> {
+        let schemas = self.schemas.read().await;
+        schemas.get(event_type).map(|c| c.schema.clone())
+    }
+
+    /// Lists all schemas, by event type.
+    pub async fn list(&self) -> Vec<EventSchema> {
+        let schemas = self.schemas.read().await;
+        let mut list: Vec<EventSchema> = schemas.values().map(|c| c.schema.clone()).collect();
+        list.sort_by(|a, b| a.event_type.cmp(&b.event_type));
+        list
+    }
+
+    /// Checks a payload against its event type's schema, if it has one.
+    ///
+    /// Returns every way the payload fails the schema.
+    pub async fn validate(
+        &self,
+        event_type: &str,
+        payload: &serde_json::Value,
+    ) -> Result<(), Vec<PayloadError>> {
+        let schemas = self.schemas.read().await;
+        let Some(compiled) = schemas.get(event_type) else {
+            return Ok(());
+        };
+
+        let errors: Vec<PayloadError> = compiled
+            .validator
+            .iter_errors(payload)
+            .map(|e| PayloadError { path: e.instance_path().to_string(), message: e.to_string() })
+            .collect();
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message_schema() -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["content"],
+            "properties": { "content": { "type": "string" } }
+        })
+    }
+
+    async fn registry(configured: &[(&str, serde_json::Value)]) -> SchemaRegistry {
+        let store = SqliteSchemaStore::new(":memory:").await.unwrap();
+        let configured = configured
+            .iter()
+            .map(|(event_type, schema)| (event_type.to_string(), schema.clone()))
+            .collect();
+        SchemaRegistry::new(store, configured).await.unwrap()
+    }
+
+    async fn register(
+        registry: &SchemaRegistry,
+        herald_id: &str,
+        schemas: &[(&str, serde_json::Value)],
+    ) -> Result<(), SchemaError> {
+        let schemas = schemas
+            .iter()
+            .map(|(event_type, schema)| (event_type.to_string(), schema.clone()))
+            .collect();
+        let prepared = registry.prepare(herald_id, schemas).await?;
+        registry.replace(prepared).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_every_error() {
+        let registry = registry(&[("chat.message", message_schema())]).await;
+
+        assert!(
+            registry
+                .validate("chat.message", &json!({ "content": "hi" }))
+                .await
+                .is_ok()
+        );
+        // Types without a schema are not validated
+        assert!(registry.validate("other", &json!(42)).await.is_ok());
+
+        let errors = registry
+            .validate("chat.message", &json!({ "content": 42 }))
+            .await
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "/content");
+
+        let errors = registry
+            .validate("chat.message", &json!("hi"))
+            .await
+            .unwrap_err();
+        assert_eq!(errors[0].path, "");
+    }
+
+    #[tokio::test]
+    async fn test_herald_schemas_conflicts_and_replacement() {
+        let registry = registry(&[("chat.message", message_schema())]).await;
+
+        // Invalid schemas are rejected
+        assert!(matches!(
+            register(&registry, "h", &[("h.ping", json!({ "type": "bogus" }))]).await,
+            Err(SchemaError::Invalid { .. })
+        ));
+
+        // A config schema can be repeated, not replaced
+        register(&registry, "h", &[("chat.message", message_schema())])
+            .await
+            .unwrap();
+        assert_eq!(registry.get("chat.message").await.unwrap().herald_id, None);
+        assert!(matches!(
+            register(&registry, "h", &[("chat.message", json!({}))]).await,
+            Err(SchemaError::Conflict { .. })
+        ));
+
+        register(&registry, "h", &[("h.ping", json!({ "type": "object" }))])
+            .await
+            .unwrap();
+        assert!(matches!(
+            register(&registry, "other", &[("h.ping", json!({}))]).await,
+            Err(SchemaError::Conflict { .. })
+        ));
+
+        // Registering again replaces the herald's schemas
+        register(&registry, "h", &[("h.pong", json!({ "type": "object" }))])
+            .await
+            .unwrap();
+        let types: Vec<String> = registry
+            .list()
+            .await
+            .into_iter()
+            .map(|s| s.event_type)
+            .collect();
+        assert_eq!(types, vec!["chat.message", "h.pong"]);
+
+        registry.remove_herald("h").await.unwrap();
+        assert!(registry.get("h.pong").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_herald_schemas_survive_restart() {
+        let store = SqliteSchemaStore::new(":memory:").await.unwrap();
+        let registry = SchemaRegistry::new(store.clone(), HashMap::new())
+            .await
+            .unwrap();
+        register(&registry, "h", &[("chat.message", message_schema())])
+            .await
+            .unwrap();
+
+        let restarted = SchemaRegistry::new(store, HashMap::new()).await.unwrap();
+        let schema = restarted.get("chat.message").await.unwrap();
+        assert_eq!(schema.herald_id.as_deref(), Some("h"));
+        assert_eq!(schema.schema, message_schema());
+    }
+}
@@ -4,19 +4,77 @@ use axum::Router;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
-use tracing::info;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::config::Config;
-use crate::handlers::{EventHandler, HeraldHandler};
-use crate::herald::HeraldRegistry;
-use crate::queue::EventQueue;
+use crate::config::{Config, StorageBackend};
+use crate::handlers::{
+    ConsumerHandler, DeadLetterHandler, EventHandler, HeraldHandler, MetricsHandler, SchemaHandler,
+};
+use crate::herald::{
+    HeraldLimiter, HeraldRegistry, HeraldStatusEvents, SYSTEM_HERALD_ID, SqliteHeraldStore,
+    system_schemas,
+};
+use crate::metrics::Metrics;
+use crate::queue::{EventQueue, EventStore, MemoryEventStore, SqliteEventStore};
+use crate::schema::{SchemaRegistry, SqliteSchemaStore};
 
 /// Application state shared across handlers.
 #[derive(Clone)]
 pub struct AppState {
     pub event_queue: Arc<EventQueue>,
     pub herald_registry: Arc<HeraldRegistry>,
+    pub herald_limiter: Arc<HeraldLimiter>,
+    pub herald_status_events: Arc<HeraldStatusEvents>,
+    pub schema_registry: Arc<SchemaRegistry>,
+    pub metrics: Arc<Metrics>,
+    /// Cancelled when the server starts shutting down, to end event streams.
+    pub shutdown: CancellationToken,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State over in-memory storage with default settings, for handler tests.
+    pub(crate) async fn for_tests() -> Self {
+        use crate::config::{ExpirePolicy, HeraldStatusEventsConfig, RetryConfig};
+        use crate::herald::HeraldLimits;
+        use std::collections::{HashMap, HashSet};
+
+        let event_queue = Arc::new(
+            EventQueue::new(
+                Box::new(MemoryEventStore::new()),
+                RetryConfig::default(),
+                ExpirePolicy::Drop,
+                None,
+            )
+            .await
+            .unwrap(),
+        );
+        let herald_store = SqliteHeraldStore::new(":memory:").await.unwrap();
+        let schema_store = SqliteSchemaStore::new(":memory:").await.unwrap();
+        Self {
+            herald_registry: Arc::new(
+                HeraldRegistry::new(herald_store, HashMap::new())
+                    .await
+                    .unwrap(),
+            ),
+            herald_limiter: Arc::new(HeraldLimiter::new(HeraldLimits::default(), HashMap::new())),
+            herald_status_events: Arc::new(HeraldStatusEvents::new(
+                event_queue.clone(),
+                &HeraldStatusEventsConfig::default(),
+                HashSet::new(),
+            )),
+            schema_registry: Arc::new(
+                SchemaRegistry::new(schema_store, HashMap::new())
+                    .await
+                    .unwrap(),
+            ),
+            metrics: Arc::new(Metrics::new().unwrap()),
+            event_queue,
+            shutdown: CancellationToken::new(),
+        }
+    }
 }
 
 /// HTTP server for the Agora event hub.
@@ -39,60 +97,135 @@ impl AgoraServer {
 
         info!("Initializing Agora event hub");
 
-        let event_queue =
-            Arc::new(EventQueue::new(&config.database_path, config.retry.clone()).await?);
-        let herald_registry = Arc::new(HeraldRegistry::new());
+        let idempotency_window = Duration::from_millis(config.idempotency_window_ms);
+        let (event_store, herald_store, schema_store): (Box<dyn EventStore>, _, _) = match config
+            .storage
+        {
+            StorageBackend::Sqlite => (
+                Box::new(
+                    SqliteEventStore::new(&config.database_path)
+                        .await?
+                        .with_idempotency_window(idempotency_window),
+                ),
+                SqliteHeraldStore::new(&config.database_path).await?,
+                SqliteSchemaStore::new(&config.database_path).await?,
+            ),
+            StorageBackend::Memory => {
+                info!("Using in-memory storage; nothing is kept across restarts");
+                (
+                    Box::new(MemoryEventStore::new().with_idempotency_window(idempotency_window)),
+                    // Each `:memory:` connection pool gets its own private database
+                    SqliteHeraldStore::new(":memory:").await?,
+                    SqliteSchemaStore::new(":memory:").await?,
+                )
+            }
+        };
+
+        let herald_limits = config
+            .heralds
+            .iter()
+            .filter_map(|(id, herald)| Some((id.clone(), herald.limits.clone()?)))
+            .collect();
+        let herald_limiter = Arc::new(HeraldLimiter::new(
+            config.herald_limits.clone(),
+            herald_limits,
+        ));
+
+        let metrics = Arc::new(Metrics::new()?);
+        let event_queue = Arc::new(
+            EventQueue::new(
+                event_store,
+                config.retry.clone(),
+                config.on_expire,
+                config
+                    .archive
+                    .enabled
+                    .then(|| Duration::from_millis(config.archive.retention_ms)),
+            )
+            .await?
+            .with_coalesce_rules(config.coalesce.clone())
+            .with_limiter(herald_limiter.clone())
+            .with_metrics(metrics.clone()),
+        );
+        let configured_tokens = config
+            .heralds
+            .iter()
+            .filter_map(|(id, herald)| Some((id.clone(), herald.token.clone()?)))
+            .collect();
+        let herald_registry = Arc::new(
+            HeraldRegistry::new(herald_store, configured_tokens)
+                .await?
+                .with_admin_token(config.admin_token.clone()),
+        );
+
+        let unreported_heralds = config
+            .heralds
+            .iter()
+            .filter(|(_, herald)| !herald.status_events)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let herald_status_events = Arc::new(HeraldStatusEvents::new(
+            event_queue.clone(),
+            &config.herald_status_events,
+            unreported_heralds,
+        ));
 
-        let state = Arc::new(AppState { event_queue, herald_registry });
+        let schema_registry =
+            Arc::new(SchemaRegistry::new(schema_store, config.schemas.clone()).await?);
+        let system = schema_registry
+            .prepare(SYSTEM_HERALD_ID, system_schemas())
+            .await?;
+        schema_registry.replace(system).await?;
+
+        let state = Arc::new(AppState {
+            event_queue,
+            herald_registry,
+            herald_limiter,
+            herald_status_events,
+            schema_registry,
+            metrics,
+            shutdown: CancellationToken::new(),
+        });
 
         Ok(Self { config, state })
     }
 
-    /// Starts the server.
+    /// Runs the server until SIGTERM or Ctrl-C.
+    ///
+    /// On shutdown, Agora stops accepting connections, ends event streams and
+    /// gives in-flight requests up to `shutdown_timeout_ms` to finish, then
+    /// stops the heartbeat checker and closes the database.
     pub async fn run(self) -> anyhow::Result<()> {
-        use axum::routing::{delete, get, patch, post};
-        use tower_http::{
-            cors::{Any, CorsLayer},
-            trace::TraceLayer,
-        };
-
-        // Spawn background heartbeat timeout checker
+        // Spawn background heartbeat timeout checker, which also sweeps
+        // expired events that no fetch would reach
+        let event_queue = self.state.event_queue.clone();
         let herald_registry = self.state.herald_registry.clone();
+        let herald_status_events = self.state.herald_status_events.clone();
         let check_interval = Duration::from_millis(self.config.heartbeat_check_interval_ms);
         let timeout_ms = self.config.timeout_ms;
+        let shutdown = self.state.shutdown.clone();
 
-        tokio::spawn(async move {
+        let heartbeat_checker = tokio::spawn(async move {
             let mut interval = tokio::time::interval(check_interval);
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = interval.tick() => {}
+                }
                 let changed = herald_registry.check_timeouts(timeout_ms).await;
-                for (id, status) in changed {
-                    info!("Herald '{}' status changed to {:?}", id, status);
+                for herald in changed {
+                    info!(
+                        "Herald '{}' status changed to {:?}",
+                        herald.id, herald.status
+                    );
+                    herald_status_events.disconnected(&herald).await;
                 }
+                herald_status_events.report_deferred().await;
+                event_queue.expire_stale().await;
             }
         });
 
-        let app = Router::new()
-            .route("/health", get(health_check))
-            // Herald routes
-            .route("/heralds", post(HeraldHandler::register))
-            .route("/heralds", get(HeraldHandler::list))
-            .route("/heralds/{id}", get(HeraldHandler::get))
-            .route("/heralds/{id}", delete(HeraldHandler::unregister))
-            .route("/heralds/{id}/heartbeat", post(HeraldHandler::heartbeat))
-            // Event routes
-            .route("/events", post(EventHandler::create))
-            .route("/events", patch(EventHandler::batch_update))
-            .route("/events/fetch", post(EventHandler::fetch))
-            .route("/events/{id}", patch(EventHandler::update))
-            .with_state((*self.state).clone())
-            .layer(
-                CorsLayer::new()
-                    .allow_origin(Any)
-                    .allow_methods(Any)
-                    .allow_headers(Any),
-            )
-            .layer(TraceLayer::new_for_http());
+        let app = router((*self.state).clone());
 
         let bind_address = self.config.bind_address();
         let addr: SocketAddr = bind_address
@@ -105,14 +238,76 @@ impl AgoraServer {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to bind to address: {}", e))?;
 
-        axum::serve(listener, app)
-            .await
-            .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+        let shutdown = self.state.shutdown.clone();
+        let drain_timeout = Duration::from_millis(self.config.shutdown_timeout_ms);
+        epha_shutdown::serve(listener, app, shutdown.clone(), drain_timeout).await?;
+
+        if let Err(e) = heartbeat_checker.await {
+            error!("Heartbeat checker failed: {}", e);
+        }
+
+        self.state.event_queue.close().await;
+        self.state.herald_registry.close().await;
+        self.state.schema_registry.close().await;
+        info!("Agora stopped");
 
         Ok(())
     }
 }
 
+/// Agora's HTTP API over `state`.
+pub(crate) fn router(state: AppState) -> Router {
+    use axum::routing::{delete, get, patch, post, put};
+    use tower_http::{
+        cors::{Any, CorsLayer},
+        trace::TraceLayer,
+    };
+
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/metrics", get(MetricsHandler::render))
+        // Herald routes
+        .route("/heralds", post(HeraldHandler::register))
+        .route("/heralds", get(HeraldHandler::list))
+        .route("/heralds/{id}", get(HeraldHandler::get))
+        .route("/heralds/{id}", delete(HeraldHandler::unregister))
+        .route("/heralds/{id}/heartbeat", post(HeraldHandler::heartbeat))
+        // Event routes
+        .route("/events", get(EventHandler::list))
+        .route("/events", post(EventHandler::create))
+        .route("/events", patch(EventHandler::batch_update))
+        .route("/events/fetch", post(EventHandler::fetch))
+        .route("/events/stream", get(EventHandler::stream))
+        .route("/events/watch", get(EventHandler::watch))
+        .route("/events/history", get(EventHandler::history))
+        .route("/events/{id}", patch(EventHandler::update))
+        // Schema routes
+        .route("/schemas", get(SchemaHandler::list))
+        .route("/schemas/{event_type}", get(SchemaHandler::get))
+        // Consumer routes
+        .route("/consumers", get(ConsumerHandler::list))
+        .route("/consumers/{name}", get(ConsumerHandler::get))
+        .route("/consumers/{name}", put(ConsumerHandler::upsert))
+        .route("/consumers/{name}", delete(ConsumerHandler::delete))
+        // Dead-letter routes
+        .route("/dead-letters", get(DeadLetterHandler::list))
+        .route("/dead-letters", delete(DeadLetterHandler::purge))
+        .route("/dead-letters/{id}", get(DeadLetterHandler::get))
+        .route("/dead-letters/{id}", delete(DeadLetterHandler::delete))
+        .route(
+            "/dead-letters/{id}/requeue",
+            post(DeadLetterHandler::requeue),
+        )
+        .with_state(state)
+        .layer(
+            CorsLayer::new()
+                .allow_origin(Any)
+                .allow_methods(Any)
+                .allow_headers(Any),
+        )
+        .layer(TraceLayer::new_for_http())
+}
+
 /// Health check endpoint.
 async fn health_check() -> &'static str {
     "OK"
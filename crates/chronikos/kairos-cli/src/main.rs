@@ -1,7 +1,8 @@
 use anyhow::{Result, anyhow};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use kairos_client::{
-    CreateScheduleRequest, KairosClient, Period, Priority, Schedule, ScheduleStatus, TriggerSpec,
+    CreateScheduleRequest, KairosClient, MisfirePolicy, Period, Priority, Schedule, ScheduleStatus,
+    TriggerSpec, UpdateScheduleRequest,
 };
 use reqwest::Client;
 use std::env;
@@ -10,8 +11,16 @@ use time::{OffsetDateTime, format_description};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 const ENV_KAIROS_URL: &str = "KAIROS_URL";
+const ENV_KAIROS_TZ: &str = "KAIROS_TZ";
 const DEFAULT_URL: &str = "http://localhost:8081";
 
+/// Server connection and defaults the commands run with.
+struct Context {
+    client: KairosClient,
+    /// IANA time zone for `at_time` and cron fields; UTC when None.
+    timezone: Option<String>,
+}
+
 fn get_server_url() -> String {
     env::var(ENV_KAIROS_URL)
         .ok()
@@ -19,6 +28,11 @@ fn get_server_url() -> String {
         .unwrap_or_else(|| DEFAULT_URL.to_string())
 }
 
+fn get_timezone(tz: Option<String>) -> Option<String> {
+    tz.or_else(|| env::var(ENV_KAIROS_TZ).ok())
+        .filter(|s| !s.is_empty())
+}
+
 fn build_http_client() -> Client {
     Client::builder()
         .connect_timeout(Duration::from_secs(10))
@@ -42,6 +56,11 @@ struct Cli {
     #[arg(short, long, global = true)]
     url: Option<String>,
 
+    /// IANA time zone for --at times and cron fields, e.g. Europe/Berlin
+    /// (overrides KAIROS_TZ env var; defaults to UTC)
+    #[arg(long, global = true)]
+    tz: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -109,6 +128,24 @@ enum Commands {
         /// Priority
         #[arg(long, default_value = "normal")]
         priority: Priority,
+        #[command(flatten)]
+        end: EndArgs,
+    },
+    /// Schedule a recurring event by cron expression
+    Cron {
+        /// Cron expression with 5 fields (minute hour day month weekday) or 6
+        /// (seconds first), e.g. "30 9 * * MON-FRI"
+        expression: String,
+        /// Schedule name/description
+        name: String,
+        /// JSON payload
+        #[arg(long)]
+        payload: Option<String>,
+        /// Priority
+        #[arg(long, default_value = "normal")]
+        priority: Priority,
+        #[command(flatten)]
+        end: EndArgs,
     },
     /// List schedules
     List {
@@ -126,10 +163,85 @@ enum Commands {
         /// Schedule ID
         id: String,
     },
+    /// Defer a schedule's next fire; recurring schedules carry on from there
+    Snooze {
+        /// Schedule ID
+        id: String,
+        /// Until when (RFC3339 timestamp, or duration like 30m, 2h; units must be lowercase)
+        until: String,
+    },
+    /// Pause a schedule
+    Pause {
+        /// Schedule ID
+        id: String,
+    },
+    /// Resume a paused schedule, skipping occurrences due while it was paused
+    Resume {
+        /// Schedule ID
+        id: String,
+    },
+    /// Edit a schedule
+    Edit(EditArgs),
     /// Show service status
     Status,
 }
 
+/// Changes to make to a schedule; anything left out is unchanged.
+#[derive(Args)]
+struct EditArgs {
+    /// Schedule ID
+    id: String,
+    /// New name/description
+    #[arg(long)]
+    name: Option<String>,
+    /// Fire once at this time instead (RFC3339 timestamp or relative time like +1h)
+    #[arg(long, conflicts_with_all = ["in_", "every", "cron"])]
+    once: Option<String>,
+    /// Fire once after this delay instead (e.g., 30s, 5m, 2h, 1d)
+    #[arg(long = "in", conflicts_with_all = ["every", "cron"])]
+    in_: Option<String>,
+    /// Repeat every period instead (minutely, hourly, daily, weekly, monthly, yearly)
+    #[arg(long, conflicts_with = "cron")]
+    every: Option<Period>,
+    /// Time of day for --every (e.g., "09:00")
+    #[arg(long, requires = "every")]
+    at: Option<String>,
+    /// Repeat on this cron expression instead
+    #[arg(long)]
+    cron: Option<String>,
+    /// New JSON payload
+    #[arg(long)]
+    payload: Option<String>,
+    /// New comma-separated tags, replacing the old ones
+    #[arg(long)]
+    tags: Option<String>,
+    /// New priority
+    #[arg(long)]
+    priority: Option<Priority>,
+    /// What to do about missed occurrences (fire_once, skip, or fire_all:<max>)
+    #[arg(long)]
+    misfire: Option<MisfirePolicy>,
+    #[command(flatten)]
+    end: EndArgs,
+}
+
+/// When a recurring schedule is done.
+#[derive(Args)]
+struct EndArgs {
+    /// Stop after this time (RFC3339 timestamp or relative time like +7d)
+    #[arg(long)]
+    until: Option<String>,
+    /// Stop after firing this many times
+    #[arg(long)]
+    times: Option<u32>,
+}
+
+impl EndArgs {
+    fn until(&self) -> Result<Option<OffsetDateTime>> {
+        self.until.as_deref().map(parse_datetime).transpose()
+    }
+}
+
 #[tokio::main]
 async fn main() {
     tracing_subscriber::registry()
@@ -141,25 +253,33 @@ async fn main() {
         .init();
 
     let cli = Cli::parse();
-    let client = get_client();
+    let ctx = Context { client: get_client(), timezone: get_timezone(cli.tz) };
+    let client = &ctx.client;
 
     let result = match cli.command {
         Commands::Schedule { name, when, repeat, payload, tags, priority } => {
-            handle_schedule(name, when, repeat, payload, tags, priority, &client).await
+            handle_schedule(name, when, repeat, payload, tags, priority, &ctx).await
         }
         Commands::At { time, name, payload, priority } => {
-            handle_at(time, name, payload, priority, &client).await
+            handle_at(time, name, payload, priority, &ctx).await
         }
         Commands::In { duration, name, payload, priority } => {
-            handle_in(duration, name, payload, priority, &client).await
+            handle_in(duration, name, payload, priority, &ctx).await
+        }
+        Commands::Every { period, name, at, payload, priority, end } => {
+            handle_every(period, name, at, payload, priority, end, &ctx).await
         }
-        Commands::Every { period, name, at, payload, priority } => {
-            handle_every(period, name, at, payload, priority, &client).await
+        Commands::Cron { expression, name, payload, priority, end } => {
+            handle_cron(expression, name, payload, priority, end, &ctx).await
         }
-        Commands::List { status, tag } => handle_list(status, tag, &client).await,
-        Commands::Next => handle_next(&client).await,
-        Commands::Cancel { id } => handle_cancel(id, &client).await,
-        Commands::Status => handle_status(&client).await,
+        Commands::List { status, tag } => handle_list(status, tag, client).await,
+        Commands::Next => handle_next(client).await,
+        Commands::Cancel { id } => handle_cancel(id, client).await,
+        Commands::Snooze { id, until } => handle_snooze(id, until, client).await,
+        Commands::Pause { id } => handle_set_status(id, ScheduleStatus::Paused, client).await,
+        Commands::Resume { id } => handle_set_status(id, ScheduleStatus::Active, client).await,
+        Commands::Edit(args) => handle_edit(args, client).await,
+        Commands::Status => handle_status(client).await,
     };
 
     if let Err(e) = result {
@@ -177,7 +297,7 @@ async fn handle_schedule(
     payload: Option<String>,
     tags: Option<String>,
     priority: Priority,
-    client: &KairosClient,
+    ctx: &Context,
 ) -> Result<()> {
     let trigger = if let Some(period) = repeat {
         TriggerSpec::Every { period, at_time: parse_at_time(&when)? }
@@ -188,12 +308,16 @@ async fn handle_schedule(
     let request = CreateScheduleRequest {
         name,
         trigger,
+        timezone: ctx.timezone.clone(),
+        misfire_policy: MisfirePolicy::default(),
         payload: parse_payload(payload.as_deref())?,
         tags: parse_tags(tags.as_deref()),
         priority,
+        until: None,
+        max_occurrences: None,
     };
 
-    let schedule = client.create_schedule(request).await?;
+    let schedule = ctx.client.create_schedule(request).await?;
     print_schedule(&schedule);
     Ok(())
 }
@@ -203,18 +327,22 @@ async fn handle_at(
     name: String,
     payload: Option<String>,
     priority: Priority,
-    client: &KairosClient,
+    ctx: &Context,
 ) -> Result<()> {
     let at = parse_datetime(&time)?;
     let request = CreateScheduleRequest {
         name,
         trigger: TriggerSpec::Once { at },
+        timezone: ctx.timezone.clone(),
+        misfire_policy: MisfirePolicy::default(),
         payload: parse_payload(payload.as_deref())?,
         tags: vec![],
         priority,
+        until: None,
+        max_occurrences: None,
     };
 
-    let schedule = client.create_schedule(request).await?;
+    let schedule = ctx.client.create_schedule(request).await?;
     print_schedule(&schedule);
     Ok(())
 }
@@ -224,18 +352,22 @@ async fn handle_in(
     name: String,
     payload: Option<String>,
     priority: Priority,
-    client: &KairosClient,
+    ctx: &Context,
 ) -> Result<()> {
     let duration_seconds = parse_duration(&duration)?;
     let request = CreateScheduleRequest {
         name,
         trigger: TriggerSpec::In { duration_seconds },
+        timezone: ctx.timezone.clone(),
+        misfire_policy: MisfirePolicy::default(),
         payload: parse_payload(payload.as_deref())?,
         tags: vec![],
         priority,
+        until: None,
+        max_occurrences: None,
     };
 
-    let schedule = client.create_schedule(request).await?;
+    let schedule = ctx.client.create_schedule(request).await?;
     print_schedule(&schedule);
     Ok(())
 }
@@ -246,17 +378,47 @@ async fn handle_every(
     at: Option<String>,
     payload: Option<String>,
     priority: Priority,
-    client: &KairosClient,
+    end: EndArgs,
+    ctx: &Context,
 ) -> Result<()> {
     let request = CreateScheduleRequest {
         name,
         trigger: TriggerSpec::Every { period, at_time: at.clone() },
+        timezone: ctx.timezone.clone(),
+        misfire_policy: MisfirePolicy::default(),
+        payload: parse_payload(payload.as_deref())?,
+        tags: vec![],
+        priority,
+        until: end.until()?,
+        max_occurrences: end.times,
+    };
+
+    let schedule = ctx.client.create_schedule(request).await?;
+    print_schedule(&schedule);
+    Ok(())
+}
+
+async fn handle_cron(
+    expression: String,
+    name: String,
+    payload: Option<String>,
+    priority: Priority,
+    end: EndArgs,
+    ctx: &Context,
+) -> Result<()> {
+    let request = CreateScheduleRequest {
+        name,
+        trigger: TriggerSpec::Cron { expression },
+        timezone: ctx.timezone.clone(),
+        misfire_policy: MisfirePolicy::default(),
         payload: parse_payload(payload.as_deref())?,
         tags: vec![],
         priority,
+        until: end.until()?,
+        max_occurrences: end.times,
     };
 
-    let schedule = client.create_schedule(request).await?;
+    let schedule = ctx.client.create_schedule(request).await?;
     print_schedule(&schedule);
     Ok(())
 }
@@ -295,6 +457,38 @@ async fn handle_cancel(id: String, client: &KairosClient) -> Result<()> {
     Ok(())
 }
 
+async fn handle_snooze(id: String, until: String, client: &KairosClient) -> Result<()> {
+    let request = UpdateScheduleRequest {
+        defer_until: Some(parse_defer_until(&until)?),
+        ..Default::default()
+    };
+    let schedule = client.update_schedule(&id, request).await?;
+    print_schedule(&schedule);
+    Ok(())
+}
+
+async fn handle_set_status(
+    id: String,
+    status: ScheduleStatus,
+    client: &KairosClient,
+) -> Result<()> {
+    let request = UpdateScheduleRequest { status: Some(status), ..Default::default() };
+    let schedule = client.update_schedule(&id, request).await?;
+    print_schedule(&schedule);
+    Ok(())
+}
+
+async fn handle_edit(args: EditArgs, client: &KairosClient) -> Result<()> {
+    let id = args.id.clone();
+    let request = build_edit_request(args)?;
+    if request.is_empty() {
+        return Err(anyhow!("Nothing to edit. See kairos-cli edit --help"));
+    }
+    let schedule = client.update_schedule(&id, request).await?;
+    print_schedule(&schedule);
+    Ok(())
+}
+
 async fn handle_status(client: &KairosClient) -> Result<()> {
     let status = client.get_status().await?;
     println!("Healthy: {}", status.healthy);
@@ -323,8 +517,19 @@ fn print_schedule(schedule: &Schedule) {
     println!("ID:       {}", schedule.id);
     println!("Name:     {}", schedule.name);
     println!("Trigger:  {:?}", schedule.trigger);
+    if let Some(timezone) = &schedule.timezone {
+        println!("Zone:     {}", timezone);
+    }
     println!("Status:   {:?}", schedule.status);
     println!("Priority: {:?}", schedule.priority);
+    println!("Misfire:  {}", schedule.misfire_policy);
+    match schedule.max_occurrences {
+        Some(max) => println!("Fired:    {} of {}", schedule.fire_count, max),
+        None => println!("Fired:    {}", schedule.fire_count),
+    }
+    if let Some(until) = schedule.until {
+        println!("Until:    {}", until.format(&fmt).unwrap());
+    }
     println!("Next:     {}", next_str);
     println!("Tags:     {:?}", schedule.tags);
     println!(
@@ -352,6 +557,50 @@ fn parse_datetime(s: &str) -> Result<OffsetDateTime> {
     ))
 }
 
+/// Converts a snooze target to `defer_until`: an RFC3339 timestamp as is, or
+/// a duration as an ISO 8601 one.
+fn parse_defer_until(s: &str) -> Result<String> {
+    if OffsetDateTime::parse(s, &format_description::well_known::Rfc3339).is_ok() {
+        return Ok(s.to_string());
+    }
+    let seconds = parse_relative_time(s.strip_prefix('+').unwrap_or(s)).map_err(|_| {
+        anyhow!(
+            "Invalid snooze time: {}. Use RFC3339 (e.g., 2026-03-15T14:30:00Z) or a duration (e.g., 30m, 2h)",
+            s
+        )
+    })?;
+    Ok(format!("PT{}S", seconds))
+}
+
+fn build_edit_request(args: EditArgs) -> Result<UpdateScheduleRequest> {
+    let trigger = if let Some(when) = args.once {
+        Some(TriggerSpec::Once { at: parse_datetime(&when)? })
+    } else if let Some(duration) = args.in_ {
+        Some(TriggerSpec::In { duration_seconds: parse_duration(&duration)? })
+    } else if let Some(period) = args.every {
+        let at_time = args.at.as_deref().map(parse_at_time).transpose()?.flatten();
+        Some(TriggerSpec::Every { period, at_time })
+    } else {
+        args.cron.map(|expression| TriggerSpec::Cron { expression })
+    };
+
+    Ok(UpdateScheduleRequest {
+        name: args.name,
+        trigger,
+        payload: args
+            .payload
+            .as_deref()
+            .map(|p| parse_payload(Some(p)))
+            .transpose()?,
+        tags: args.tags.as_deref().map(|t| parse_tags(Some(t))),
+        priority: args.priority,
+        misfire_policy: args.misfire,
+        until: args.end.until()?,
+        max_occurrences: args.end.times,
+        ..Default::default()
+    })
+}
+
 fn parse_relative_time(s: &str) -> Result<i64> {
     let mut num = 0u64;
     let mut i = 0;
@@ -537,6 +786,67 @@ mod cli_tests {
         assert!(parse_datetime("2026-13-01T00:00:00Z").is_err()); // Invalid month
     }
 
+    // === parse_defer_until tests ===
+
+    #[test]
+    fn test_parse_defer_until() {
+        assert_eq!(parse_defer_until("30m").unwrap(), "PT1800S");
+        assert_eq!(parse_defer_until("+2h").unwrap(), "PT7200S");
+        assert_eq!(
+            parse_defer_until("2026-03-15T14:30:00Z").unwrap(),
+            "2026-03-15T14:30:00Z"
+        );
+        assert!(parse_defer_until("2H").is_err());
+        assert!(parse_defer_until("tomorrow").is_err());
+    }
+
+    // === edit tests ===
+
+    fn edit_request(args: &[&str]) -> Result<UpdateScheduleRequest> {
+        let cli = Cli::try_parse_from(["kairos-cli", "edit", "id"].iter().chain(args))?;
+        match cli.command {
+            Commands::Edit(args) => build_edit_request(args),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_build_edit_request() {
+        assert!(edit_request(&[]).unwrap().is_empty());
+
+        let req = edit_request(&["--every", "daily", "--at", "09:00", "--tags", "a,b"]).unwrap();
+        assert_eq!(
+            req.trigger,
+            Some(TriggerSpec::Every { period: Period::Daily, at_time: Some("09:00".into()) })
+        );
+        assert_eq!(req.tags, Some(vec!["a".to_string(), "b".to_string()]));
+        assert!(req.name.is_none() && req.payload.is_none() && req.priority.is_none());
+
+        let req = edit_request(&["--cron", "0 9 * * *", "--priority", "high"]).unwrap();
+        assert_eq!(
+            req.trigger,
+            Some(TriggerSpec::Cron { expression: "0 9 * * *".into() })
+        );
+        assert_eq!(req.priority, Some(Priority::High));
+
+        let req = edit_request(&["--misfire", "fire-all:5"]).unwrap();
+        assert_eq!(req.misfire_policy, Some(MisfirePolicy::FireAll { max: 5 }));
+        assert!(edit_request(&["--misfire", "fire_all"]).is_err());
+
+        let req = edit_request(&["--until", "2026-03-15T14:30:00Z", "--times", "7"]).unwrap();
+        assert_eq!(
+            req.until,
+            Some(time::macros::datetime!(2026-03-15 14:30 UTC))
+        );
+        assert_eq!(req.max_occurrences, Some(7));
+        assert!(edit_request(&["--until", "next week"]).is_err());
+
+        // One trigger at a time, and --at only with --every
+        assert!(edit_request(&["--in", "5m", "--cron", "* * * * *"]).is_err());
+        assert!(edit_request(&["--at", "09:00"]).is_err());
+        assert!(edit_request(&["--every", "daily", "--at", "9am"]).is_err());
+    }
+
     // === parse_payload tests ===
 
     #[test]
@@ -9,7 +9,7 @@ pub use client::{KairosClient, KairosClientError};
 
 // Re-export commonly used types from kairos-common
 pub use kairos_common::schedule::{
-    AckTriggeredRequest, CreateScheduleRequest, Period, Priority, Schedule, ScheduleId,
-    ScheduleStatus, SchedulesListResponse, StatusResponse, TriggerSpec, TriggeredSchedule,
-    UpdateScheduleRequest,
+    AckTriggeredRequest, CreateScheduleRequest, MisfirePolicy, Period, Priority, Schedule,
+    ScheduleId, ScheduleStatus, SchedulesListResponse, StatusResponse, TriggerSpec,
+    TriggeredSchedule, UpdateScheduleRequest,
 };
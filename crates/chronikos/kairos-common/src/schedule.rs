@@ -105,6 +105,49 @@ impl std::str::FromStr for Period {
     }
 }
 
+/// What to do about occurrences a schedule missed, e.g. while Kairos was down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Fire once for all of them.
+    #[default]
+    FireOnce,
+    /// Fire each of them, up to `max`; any more are dropped, oldest first.
+    FireAll { max: u32 },
+    /// Drop them without firing.
+    Skip,
+}
+
+impl std::fmt::Display for MisfirePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MisfirePolicy::FireOnce => write!(f, "fire_once"),
+            MisfirePolicy::FireAll { max } => write!(f, "fire_all:{}", max),
+            MisfirePolicy::Skip => write!(f, "skip"),
+        }
+    }
+}
+
+impl std::str::FromStr for MisfirePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_lowercase().replace('-', "_");
+        match lower.split_once(':') {
+            None if lower == "fire_once" || lower == "once" => Ok(MisfirePolicy::FireOnce),
+            None if lower == "skip" => Ok(MisfirePolicy::Skip),
+            Some(("fire_all" | "all", max)) => max
+                .parse()
+                .map(|max| MisfirePolicy::FireAll { max })
+                .map_err(|_| format!("Invalid misfire cap: {}", max)),
+            _ => Err(format!(
+                "Unknown misfire policy: {} (use fire_once, skip or fire_all:<max>)",
+                s
+            )),
+        }
+    }
+}
+
 /// Trigger specification for a schedule.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -122,8 +165,8 @@ pub enum TriggerSpec {
         /// Optional time of day for daily/weekly/monthly/yearly (e.g., "09:00")
         at_time: Option<String>,
     },
-    /// Cron expression (v2).
-    #[allow(dead_code)]
+    /// Recurring event on a cron expression: five fields
+    /// (`minute hour day-of-month month day-of-week`), or six with seconds first.
     Cron { expression: String },
 }
 
@@ -136,6 +179,13 @@ pub struct Schedule {
     pub name: String,
     /// Trigger specification.
     pub trigger: TriggerSpec,
+    /// IANA time zone `at_time` and cron fields are in (e.g.,
+    /// "Europe/Berlin"); UTC when None.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// What to do about missed occurrences.
+    #[serde(default)]
+    pub misfire_policy: MisfirePolicy,
     /// Custom payload to be included in triggered events.
     pub payload: serde_json::Value,
     /// Tags for filtering.
@@ -153,6 +203,15 @@ pub struct Schedule {
     /// Last fire time.
     #[serde(with = "time::serde::rfc3339::option")]
     pub last_fire: Option<OffsetDateTime>,
+    /// Completes the schedule once no occurrence is left before this time.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    /// Completes the schedule once it has fired this many times.
+    #[serde(default)]
+    pub max_occurrences: Option<u32>,
+    /// How many times the schedule has fired.
+    #[serde(default)]
+    pub fire_count: u32,
 }
 
 /// Request to create a new schedule.
@@ -162,6 +221,12 @@ pub struct CreateScheduleRequest {
     pub name: String,
     /// Trigger specification.
     pub trigger: TriggerSpec,
+    /// IANA time zone for `at_time` and cron fields (optional, defaults to UTC).
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// What to do about missed occurrences (optional, defaults to FireOnce).
+    #[serde(default)]
+    pub misfire_policy: MisfirePolicy,
     /// Custom payload (optional, defaults to empty object).
     #[serde(default)]
     pub payload: serde_json::Value,
@@ -171,15 +236,66 @@ pub struct CreateScheduleRequest {
     /// Schedule priority (optional, defaults to Normal).
     #[serde(default)]
     pub priority: Priority,
+    /// Last time an occurrence may fire (optional, defaults to no end).
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    /// Most times the schedule may fire (optional, defaults to no limit).
+    #[serde(default)]
+    pub max_occurrences: Option<u32>,
 }
 
-/// Request to update a schedule.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Request to update a schedule. Fields left out are unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UpdateScheduleRequest {
     /// New status (optional).
     pub status: Option<ScheduleStatus>,
     /// Defer next fire time (optional, ISO 8601 duration or RFC3339 timestamp).
     pub defer_until: Option<String>,
+    /// New name (optional).
+    pub name: Option<String>,
+    /// New trigger (optional); next fire time is recalculated from it.
+    pub trigger: Option<TriggerSpec>,
+    /// New payload (optional).
+    pub payload: Option<serde_json::Value>,
+    /// New tags, replacing the old ones (optional).
+    pub tags: Option<Vec<String>>,
+    /// New priority (optional).
+    pub priority: Option<Priority>,
+    /// New misfire policy (optional).
+    pub misfire_policy: Option<MisfirePolicy>,
+    /// New last time an occurrence may fire (optional).
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub until: Option<OffsetDateTime>,
+    /// New most times the schedule may fire, counting past fires (optional).
+    pub max_occurrences: Option<u32>,
+}
+
+impl UpdateScheduleRequest {
+    /// Whether the request leaves everything unchanged.
+    pub fn is_empty(&self) -> bool {
+        let Self {
+            status,
+            defer_until,
+            name,
+            trigger,
+            payload,
+            tags,
+            priority,
+            misfire_policy,
+            until,
+            max_occurrences,
+        } = self;
+        status.is_none()
+            && defer_until.is_none()
+            && name.is_none()
+            && trigger.is_none()
+            && payload.is_none()
+            && tags.is_none()
+            && priority.is_none()
+            && misfire_policy.is_none()
+            && until.is_none()
+            && max_occurrences.is_none()
+    }
 }
 
 /// A schedule that has been triggered and is ready to be consumed.
@@ -190,6 +306,12 @@ pub struct TriggeredSchedule {
     /// When it was triggered.
     #[serde(with = "time::serde::rfc3339")]
     pub triggered_at: OffsetDateTime,
+    /// When it was due; earlier than `triggered_at` if it fired late.
+    #[serde(with = "time::serde::rfc3339")]
+    pub scheduled_at: OffsetDateTime,
+    /// Missed occurrences that were not fired, under the misfire policy.
+    #[serde(default)]
+    pub missed_count: u32,
 }
 
 /// Request to acknowledge triggered schedules.
@@ -13,15 +13,33 @@ pub struct Config {
     pub poll_interval_ms: u64,
     /// Heartbeat interval in seconds
     pub heartbeat_interval_sec: u64,
+    /// Agora token for this herald, if preconfigured in Agora.
+    /// Without one, the herald uses the token Agora issues at registration.
+    #[serde(default)]
+    pub agora_token: Option<String>,
+    /// File holding `agora_token`, read at startup, so the secret can be kept
+    /// out of the config.
+    #[serde(default)]
+    pub agora_token_file: Option<String>,
 }
 
 impl Config {
     pub fn load(path: &Path) -> Self {
         let content = std::fs::read_to_string(path)
             .unwrap_or_else(|e| panic!("Failed to read config '{}': {}", path.display(), e));
-        let config: Self = serde_json::from_str(&content)
+        let mut config: Self = serde_json::from_str(&content)
             .unwrap_or_else(|e| panic!("Failed to parse config '{}': {}", path.display(), e));
 
+        if let Some(file) = &config.agora_token_file {
+            assert!(
+                config.agora_token.is_none(),
+                "agora_token and agora_token_file are mutually exclusive"
+            );
+            let token = std::fs::read_to_string(file)
+                .unwrap_or_else(|e| panic!("Failed to read agora_token_file '{}': {}", file, e));
+            config.agora_token = Some(token.trim().to_string());
+        }
+
         // Validate required fields
         assert!(
             !config.kairos_url.trim().is_empty(),
@@ -0,0 +1,113 @@
+//! Herald pushing triggered schedules as `kairos.trigger` events.
+
+use agora_herald::{EventBuilder, EventPayload, EventPriority, Herald};
+use async_trait::async_trait;
+use kairos_client::{KairosClient, Priority, ScheduleId, TriggeredSchedule};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use tracing::debug;
+
+/// Payload of a `kairos.trigger` event.
+#[derive(Debug, Serialize)]
+pub struct KairosTrigger<'a> {
+    pub schedule_id: &'a ScheduleId,
+    pub schedule_name: &'a str,
+    pub tags: &'a [String],
+    pub user_payload: &'a serde_json::Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub triggered_at: OffsetDateTime,
+    /// When the schedule was due; earlier than `triggered_at` if it fired late.
+    #[serde(with = "time::serde::rfc3339")]
+    pub scheduled_at: OffsetDateTime,
+    /// Missed occurrences that were not fired.
+    pub missed_count: u32,
+}
+
+impl EventPayload for KairosTrigger<'_> {
+    const EVENT_TYPE: &'static str = "kairos.trigger";
+}
+
+pub struct KairosHerald {
+    kairos_client: KairosClient,
+}
+
+impl KairosHerald {
+    pub fn new(kairos_client: KairosClient) -> Self {
+        Self { kairos_client }
+    }
+}
+
+#[async_trait]
+impl Herald for KairosHerald {
+    const ID: &'static str = "kairos-herald";
+    const DESCRIPTION: &'static str = "Kairos Herald - pushes triggered schedules to Agora";
+
+    type Item = TriggeredSchedule;
+
+    async fn poll(&mut self) -> anyhow::Result<Vec<TriggeredSchedule>> {
+        Ok(self.kairos_client.get_triggered().await?)
+    }
+
+    fn produce(&self, item: &TriggeredSchedule) -> anyhow::Result<EventBuilder> {
+        let schedule = &item.schedule;
+        let payload = KairosTrigger {
+            schedule_id: &schedule.id,
+            schedule_name: &schedule.name,
+            tags: &schedule.tags,
+            user_payload: &schedule.payload,
+            triggered_at: item.triggered_at,
+            scheduled_at: item.scheduled_at,
+            missed_count: item.missed_count,
+        };
+
+        // A trigger stays in Kairos until acked, so if the ack fails it is
+        // pushed again; the key lets Agora drop the repeat.
+        Ok(EventBuilder::typed(&payload)?
+            .priority(event_priority(schedule.priority))
+            .timestamp(item.triggered_at)
+            .idempotency_key(format!(
+                "{}@{}",
+                schedule.id,
+                item.scheduled_at.unix_timestamp_nanos()
+            )))
+    }
+
+    fn schemas(&self) -> HashMap<String, serde_json::Value> {
+        let schema = json!({
+            "type": "object",
+            "required": [
+                "schedule_id", "schedule_name", "tags", "user_payload",
+                "triggered_at", "scheduled_at", "missed_count",
+            ],
+            "properties": {
+                "schedule_id": { "type": "string" },
+                "schedule_name": { "type": "string" },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "user_payload": {},
+                "triggered_at": { "type": "string", "format": "date-time" },
+                "scheduled_at": { "type": "string", "format": "date-time" },
+                "missed_count": { "type": "integer", "minimum": 0 },
+            },
+        });
+        HashMap::from([(KairosTrigger::EVENT_TYPE.to_string(), schema)])
+    }
+
+    async fn pushed(&mut self, items: Vec<TriggeredSchedule>) -> anyhow::Result<()> {
+        let ids: Vec<ScheduleId> = items.into_iter().map(|item| item.schedule.id).collect();
+        debug!("Acknowledging {} processed schedules", ids.len());
+        let acknowledged = self.kairos_client.ack_triggered(ids).await?;
+        debug!("Acknowledged {} schedules", acknowledged);
+        Ok(())
+    }
+}
+
+fn event_priority(priority: Priority) -> EventPriority {
+    match priority {
+        Priority::Low => EventPriority::Low,
+        Priority::Normal => EventPriority::Normal,
+        Priority::High => EventPriority::High,
+        Priority::Urgent => EventPriority::Urgent,
+    }
+}
@@ -12,6 +12,21 @@ pub struct Config {
     pub database_path: String,
     /// Interval for checking scheduled events (milliseconds).
     pub tick_interval_ms: u64,
+    /// How late an occurrence can fire before it counts as missed and its
+    /// schedule's misfire policy applies (milliseconds).
+    #[serde(default = "default_misfire_grace")]
+    pub misfire_grace_ms: u64,
+    /// How long shutdown waits for in-flight requests to finish (milliseconds).
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout_ms: u64,
+}
+
+fn default_misfire_grace() -> u64 {
+    60_000
+}
+
+fn default_shutdown_timeout() -> u64 {
+    30_000
 }
 
 impl Config {
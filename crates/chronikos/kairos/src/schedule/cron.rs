@@ -0,0 +1,406 @@
+//! Cron expressions for recurring schedules.
+
+use anyhow::{Result, anyhow, bail};
+use time::{Date, Duration, Month, PrimitiveDateTime, Time, Weekday};
+
+/// How many years ahead [`CronSchedule::next_after`] looks for a match.
+///
+/// Long enough for the rarest satisfiable expressions, like Feb 29 falling on
+/// a given weekday (every 28 years at most).
+const SEARCH_YEARS: i32 = 30;
+
+const MONTH_NAMES: [&str; 12] =
+    ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A parsed cron expression.
+///
+/// Five fields (`minute hour day-of-month month day-of-week`) or six, with
+/// seconds first. Each field takes `*`, values, ranges (`a-b`), steps (`*/n`,
+/// `a/n`, `a-b/n`) and comma-separated lists of them. Months and weekdays
+/// can be named (`JAN`, `MON`), and Sunday is 0 or 7.
+///
+/// Day-of-month also takes `L` (last day), `LW` (last weekday) and `nW`
+/// (weekday nearest day n, within the month); day-of-week takes `nL` (last
+/// such weekday of the month) and `n#k` (k-th such weekday). `?` means the
+/// same as `*` in either day field. As in Vixie cron, when both day fields
+/// are restricted, a day matching either one matches.
+///
+/// `@yearly`, `@monthly`, `@weekly`, `@daily` and `@hourly` are accepted too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    seconds: u64,
+    minutes: u64,
+    hours: u32,
+    days: DaysOfMonth,
+    months: u16,
+    weekdays: DaysOfWeek,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DaysOfMonth {
+    /// Bit n set for day n.
+    days: u32,
+    last: bool,
+    last_weekday: bool,
+    /// Bit n set for the weekday nearest day n.
+    nearest_weekdays: u32,
+    restricted: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct DaysOfWeek {
+    /// Bit n set for n days from Sunday.
+    weekdays: u8,
+    /// Bit n set for the last such weekday of the month.
+    last: u8,
+    /// (weekday, k) for the k-th such weekday of the month.
+    nth: Vec<(u8, u8)>,
+    restricted: bool,
+}
+
+impl CronSchedule {
+    /// Parses a cron expression.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other if other.starts_with('@') => bail!("Unknown cron alias: {}", other),
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let (seconds, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            n => bail!(
+                "Cron expression must have 5 or 6 fields, got {}: '{}'",
+                n,
+                expression
+            ),
+        };
+
+        Ok(Self {
+            seconds: parse_field(seconds, "second", 0, 59, &[])?,
+            minutes: parse_field(rest[0], "minute", 0, 59, &[])?,
+            hours: parse_field(rest[1], "hour", 0, 23, &[])? as u32,
+            days: parse_days_of_month(rest[2])?,
+            months: parse_field(rest[3], "month", 1, 12, &MONTH_NAMES)? as u16,
+            weekdays: parse_days_of_week(rest[4])?,
+        })
+    }
+
+    /// The first time strictly after `from` that matches, if there is one
+    /// within the next 30 years.
+    pub fn next_after(&self, from: PrimitiveDateTime) -> Option<PrimitiveDateTime> {
+        let limit = from.year() + SEARCH_YEARS;
+        let mut t = from.replace_nanosecond(0).ok()? + Duration::SECOND;
+
+        while t.year() <= limit {
+            if self.months & (1 << t.month() as u8) == 0 {
+                let next = t.month().next();
+                let year = if next == Month::January { t.year() + 1 } else { t.year() };
+                t = Date::from_calendar_date(year, next, 1).ok()?.midnight();
+            } else if !self.matches_day(t.date()) {
+                t = t.date().next_day()?.midnight();
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = t.replace_time(Time::from_hms(t.hour(), 0, 0).ok()?) + Duration::HOUR;
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t = t.replace_time(Time::from_hms(t.hour(), t.minute(), 0).ok()?)
+                    + Duration::MINUTE;
+            } else if self.seconds & (1 << t.second()) == 0 {
+                t += Duration::SECOND;
+            } else {
+                return Some(t);
+            }
+        }
+
+        None
+    }
+
+    fn matches_day(&self, date: Date) -> bool {
+        let dom = self.days.matches(date);
+        let dow = self.weekdays.matches(date);
+        match (self.days.restricted, self.weekdays.restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+impl DaysOfMonth {
+    fn matches(&self, date: Date) -> bool {
+        let day = date.day();
+        let length = date.month().length(date.year());
+
+        self.days & (1 << day) != 0
+            || (self.last && day == length)
+            || (self.last_weekday && day == nearest_weekday(date.year(), date.month(), length))
+            || (1..=length).any(|n| {
+                self.nearest_weekdays & (1 << n) != 0
+                    && day == nearest_weekday(date.year(), date.month(), n)
+            })
+    }
+}
+
+impl DaysOfWeek {
+    fn matches(&self, date: Date) -> bool {
+        let weekday = date.weekday().number_days_from_sunday();
+        let bit = 1 << weekday;
+        let length = date.month().length(date.year());
+        let nth = (date.day() - 1) / 7 + 1;
+
+        self.weekdays & bit != 0
+            || (self.last & bit != 0 && date.day() + 7 > length)
+            || self.nth.iter().any(|&(w, k)| w == weekday && k == nth)
+    }
+}
+
+/// The weekday nearest `day` of the month, without leaving the month.
+fn nearest_weekday(year: i32, month: Month, day: u8) -> u8 {
+    let Ok(date) = Date::from_calendar_date(year, month, day) else {
+        return day;
+    };
+    match date.weekday() {
+        Weekday::Saturday if day == 1 => 3,
+        Weekday::Saturday => day - 1,
+        Weekday::Sunday if day == month.length(year) => day - 2,
+        Weekday::Sunday => day + 1,
+        _ => day,
+    }
+}
+
+/// Parses a field of values, ranges, steps and lists into a bitmask with
+/// bit n set for value n.
+fn parse_field(field: &str, name: &str, min: u8, max: u8, names: &[&str]) -> Result<u64> {
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        mask |= parse_item(item, name, min, max, names)?;
+    }
+    Ok(mask)
+}
+
+fn parse_item(item: &str, name: &str, min: u8, max: u8, names: &[&str]) -> Result<u64> {
+    let (range, step) = match item.split_once('/') {
+        Some((range, step)) => {
+            let step: u8 = step
+                .parse()
+                .map_err(|_| anyhow!("Invalid {} step: '{}'", name, item))?;
+            if step == 0 {
+                bail!("Invalid {} step: '{}'", name, item);
+            }
+            (range, Some(step))
+        }
+        None => (item, None),
+    };
+
+    let (start, end) = match range {
+        "*" | "?" => (min, max),
+        _ => match range.split_once('-') {
+            Some((start, end)) => (
+                parse_value(start, name, min, max, names)?,
+                parse_value(end, name, min, max, names)?,
+            ),
+            // `a/n` runs from a to the end of the field
+            None if step.is_some() => (parse_value(range, name, min, max, names)?, max),
+            None => {
+                let value = parse_value(range, name, min, max, names)?;
+                (value, value)
+            }
+        },
+    };
+    if start > end {
+        bail!("Invalid {} range: '{}'", name, item);
+    }
+
+    let mut mask = 0u64;
+    for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+        mask |= 1 << value;
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, name: &str, min: u8, max: u8, names: &[&str]) -> Result<u8> {
+    let upper = value.to_ascii_uppercase();
+    let parsed = match names.iter().position(|n| *n == upper) {
+        Some(index) => Some(min + index as u8),
+        None => value.parse::<u8>().ok(),
+    };
+    match parsed {
+        Some(v) if (min..=max).contains(&v) => Ok(v),
+        _ => Err(anyhow!(
+            "Invalid {}: '{}' (expected {}-{})",
+            name,
+            value,
+            min,
+            max
+        )),
+    }
+}
+
+fn parse_days_of_month(field: &str) -> Result<DaysOfMonth> {
+    let mut days = DaysOfMonth { restricted: is_restricted(field), ..Default::default() };
+
+    for item in field.split(',') {
+        match item.to_ascii_uppercase().as_str() {
+            "L" => days.last = true,
+            "LW" => days.last_weekday = true,
+            upper => match upper.strip_suffix('W') {
+                Some(day) => {
+                    let day = parse_value(day, "day of month", 1, 31, &[])?;
+                    days.nearest_weekdays |= 1 << day;
+                }
+                None => days.days |= parse_item(item, "day of month", 1, 31, &[])? as u32,
+            },
+        }
+    }
+    Ok(days)
+}
+
+fn parse_days_of_week(field: &str) -> Result<DaysOfWeek> {
+    let mut weekdays = DaysOfWeek { restricted: is_restricted(field), ..Default::default() };
+
+    for item in field.split(',') {
+        if let Some((weekday, k)) = item.split_once('#') {
+            let weekday = parse_weekday(weekday)?;
+            let k: u8 = k
+                .parse()
+                .ok()
+                .filter(|k| (1..=5).contains(k))
+                .ok_or_else(|| anyhow!("Invalid day of week occurrence: '{}'", item))?;
+            weekdays.nth.push((weekday, k));
+        } else if let Some(weekday) = item
+            .strip_suffix('L')
+            .or_else(|| item.strip_suffix('l'))
+            .filter(|w| !w.is_empty())
+        {
+            weekdays.last |= 1 << parse_weekday(weekday)?;
+        } else {
+            // 7 is Sunday too
+            let mask = parse_item(item, "day of week", 0, 7, &WEEKDAY_NAMES)?;
+            weekdays.weekdays |= (mask | mask >> 7) as u8 & 0x7f;
+        }
+    }
+    Ok(weekdays)
+}
+
+/// Whether a day field restricts days; as in Vixie cron, one starting with
+/// `*` (even `*/2`) does not.
+fn is_restricted(field: &str) -> bool {
+    !field.starts_with('*') && !field.starts_with('?')
+}
+
+fn parse_weekday(value: &str) -> Result<u8> {
+    Ok(parse_value(value, "day of week", 0, 7, &WEEKDAY_NAMES)? % 7)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn next(expression: &str, from: PrimitiveDateTime) -> PrimitiveDateTime {
+        CronSchedule::parse(expression)
+            .unwrap()
+            .next_after(from)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fields_ranges_steps_and_lists() {
+        let from = datetime!(2025-03-12 09:17:30);
+
+        assert_eq!(next("* * * * *", from), datetime!(2025-03-12 09:18:00));
+        assert_eq!(next("*/15 * * * *", from), datetime!(2025-03-12 09:30:00));
+        assert_eq!(
+            next("5,20-25 * * * *", from),
+            datetime!(2025-03-12 09:20:00)
+        );
+        assert_eq!(next("0 9-17/4 * * *", from), datetime!(2025-03-12 13:00:00));
+        assert_eq!(next("10/20 * * * *", from), datetime!(2025-03-12 09:30:00));
+        assert_eq!(next("0 0 1 * *", from), datetime!(2025-04-01 00:00:00));
+        // Six fields lead with seconds
+        assert_eq!(next("*/20 * * * * *", from), datetime!(2025-03-12 09:17:40));
+    }
+
+    #[test]
+    fn test_names_and_sunday() {
+        // 2025-03-12 is a Wednesday
+        let from = datetime!(2025-03-12 09:00);
+
+        assert_eq!(next("0 9 * * MON-FRI", from), datetime!(2025-03-13 09:00));
+        assert_eq!(next("0 9 * * sun", from), datetime!(2025-03-16 09:00));
+        assert_eq!(next("0 9 * * 7", from), datetime!(2025-03-16 09:00));
+        assert_eq!(next("0 0 1 jun,DEC *", from), datetime!(2025-06-01 00:00));
+        assert_eq!(next("@weekly", from), datetime!(2025-03-16 00:00));
+    }
+
+    #[test]
+    fn test_day_of_month_or_day_of_week() {
+        // Both restricted: either matches, so the 13th or a Friday
+        let from = datetime!(2025-03-12 09:00);
+        assert_eq!(next("0 0 13 * FRI", from), datetime!(2025-03-13 00:00));
+        assert_eq!(next("0 0 20 * FRI", from), datetime!(2025-03-14 00:00));
+        assert_eq!(next("0 0 ? * FRI", from), datetime!(2025-03-14 00:00));
+    }
+
+    #[test]
+    fn test_last_nearest_and_nth() {
+        let from = datetime!(2025-02-10 00:00);
+
+        assert_eq!(next("0 0 L * *", from), datetime!(2025-02-28 00:00));
+        // 2025-05-31 is a Saturday
+        assert_eq!(
+            next("0 0 LW * *", datetime!(2025-05-01 00:00)),
+            datetime!(2025-05-30 00:00)
+        );
+        // 2025-03-01 is a Saturday; the nearest weekday in March is Monday the 3rd
+        assert_eq!(next("0 0 1W * *", from), datetime!(2025-03-03 00:00));
+        // 2025-02-15 is a Saturday, the 16th a Sunday
+        assert_eq!(next("0 0 15W * *", from), datetime!(2025-02-14 00:00));
+        assert_eq!(next("0 0 16W * *", from), datetime!(2025-02-17 00:00));
+        assert_eq!(next("0 0 * * FRI#2", from), datetime!(2025-02-14 00:00));
+        assert_eq!(next("0 0 * * 1#5", from), datetime!(2025-03-31 00:00));
+        assert_eq!(next("0 0 * * 5L", from), datetime!(2025-02-28 00:00));
+    }
+
+    #[test]
+    fn test_rare_and_impossible_dates() {
+        let from = datetime!(2025-01-01 00:00);
+
+        assert_eq!(next("0 0 29 2 *", from), datetime!(2028-02-29 00:00));
+        let never = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert!(never.next_after(from).is_none());
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "30-10 * * * *",
+            "* * * FOO *",
+            "* * * * MON#6",
+            "* * 32W * *",
+            "@sometimes",
+        ] {
+            assert!(
+                CronSchedule::parse(expression).is_err(),
+                "'{}' should be rejected",
+                expression
+            );
+        }
+    }
+}
@@ -1,5 +1,9 @@
 //! Schedule management for Kairos.
 
+mod cron;
 mod types;
+mod zone;
 
+pub use cron::CronSchedule;
 pub use types::*;
+pub use zone::Zone;
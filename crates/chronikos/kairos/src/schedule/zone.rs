@@ -0,0 +1,122 @@
+//! Time zones for schedule wall-clock times.
+
+use anyhow::{Result, anyhow};
+use time::{Duration, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use time_tz::{
+    Offset, OffsetDateTimeExt, OffsetResult, PrimitiveDateTimeExt, TimeZone, Tz, timezones,
+};
+
+/// The zone a schedule's wall-clock times (`at_time`, cron fields) are in:
+/// an IANA zone, or UTC for schedules without one.
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    Utc,
+    Iana(&'static Tz),
+}
+
+impl Zone {
+    /// Looks up a schedule's zone by IANA name, e.g. `Europe/Berlin`.
+    pub fn parse(timezone: Option<&str>) -> Result<Self> {
+        match timezone {
+            None => Ok(Zone::Utc),
+            Some(name) => timezones::get_by_name(name)
+                .map(Zone::Iana)
+                .ok_or_else(|| anyhow!("Unknown time zone: {}", name)),
+        }
+    }
+
+    /// The wall-clock time in this zone at an instant.
+    pub fn to_local(self, instant: OffsetDateTime) -> PrimitiveDateTime {
+        let local = match self {
+            Zone::Utc => instant.to_offset(UtcOffset::UTC),
+            Zone::Iana(tz) => instant.to_timezone(tz),
+        };
+        PrimitiveDateTime::new(local.date(), local.time())
+    }
+
+    /// The instants a wall-clock time in this zone stands for, earliest
+    /// first, in UTC.
+    ///
+    /// A time repeated when clocks fall back stands for two instants. A time
+    /// skipped when clocks spring forward stands for the instant it would
+    /// have been without the change, so 02:30 in a gap from 02:00 to 03:00
+    /// is 03:30.
+    pub fn resolve(self, local: PrimitiveDateTime) -> Vec<OffsetDateTime> {
+        let tz = match self {
+            Zone::Utc => return vec![local.assume_utc()],
+            Zone::Iana(tz) => tz,
+        };
+
+        let mut instants = match local.assume_timezone(tz) {
+            OffsetResult::Some(instant) => vec![instant],
+            OffsetResult::Ambiguous(a, b) => vec![a, b],
+            OffsetResult::None => {
+                // The offset from before the gap carries the time past it
+                let before = tz.get_offset_utc(&(local.assume_utc() - Duration::DAY));
+                vec![local.assume_offset(before.to_utc())]
+            }
+        };
+        instants.sort();
+        instants
+            .into_iter()
+            .map(|instant| instant.to_offset(UtcOffset::UTC))
+            .collect()
+    }
+
+    /// The earliest instant a wall-clock time in this zone stands for.
+    pub fn resolve_earliest(self, local: PrimitiveDateTime) -> OffsetDateTime {
+        self.resolve(local)[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::datetime;
+
+    fn berlin() -> Zone {
+        Zone::parse(Some("Europe/Berlin")).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(matches!(Zone::parse(None), Ok(Zone::Utc)));
+        assert!(Zone::parse(Some("America/New_York")).is_ok());
+        assert!(Zone::parse(Some("Mars/Olympus_Mons")).is_err());
+    }
+
+    #[test]
+    fn test_local_round_trip() {
+        let zone = berlin();
+        // CET in winter, CEST in summer
+        assert_eq!(
+            zone.to_local(datetime!(2025-01-15 08:00 UTC)),
+            datetime!(2025-01-15 09:00)
+        );
+        assert_eq!(
+            zone.resolve(datetime!(2025-07-15 09:00)),
+            vec![datetime!(2025-07-15 07:00 UTC)]
+        );
+    }
+
+    #[test]
+    fn test_resolve_across_dst_changes() {
+        let zone = berlin();
+
+        // Clocks spring forward from 02:00 to 03:00 on 2025-03-30
+        assert_eq!(
+            zone.resolve(datetime!(2025-03-30 02:30)),
+            vec![datetime!(2025-03-30 01:30 UTC)]
+        );
+        assert_eq!(
+            zone.to_local(datetime!(2025-03-30 01:30 UTC)),
+            datetime!(2025-03-30 03:30)
+        );
+
+        // Clocks fall back from 03:00 to 02:00 on 2025-10-26
+        assert_eq!(
+            zone.resolve(datetime!(2025-10-26 02:30)),
+            vec![datetime!(2025-10-26 00:30 UTC), datetime!(2025-10-26 01:30 UTC)]
+        );
+    }
+}
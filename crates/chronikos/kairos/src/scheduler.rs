@@ -3,25 +3,67 @@
 use std::sync::Arc;
 use std::time::Duration;
 use time::OffsetDateTime;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info};
 
-use crate::schedule::{ScheduleStatus, TriggerSpec};
-use crate::store::{ScheduleStore, calculate_initial_next_fire, calculate_next_fire};
+use crate::schedule::{MisfirePolicy, Schedule, ScheduleStatus, Zone};
+use crate::store::{
+    ScheduleStore, calculate_initial_next_fire, calculate_next_occurrence, can_fire_at,
+};
+
+/// Most occurrences of one schedule counted on a tick; a schedule that missed
+/// more has the rest dropped without being counted.
+const MAX_DUE_OCCURRENCES: usize = 10_000;
+
+/// Which of a due schedule's occurrences fires on a tick.
+#[derive(Debug, PartialEq)]
+struct Firing {
+    /// The occurrence to fire, if any.
+    scheduled_at: Option<OffsetDateTime>,
+    /// Missed occurrences dropped without firing.
+    missed_count: u32,
+    /// The schedule's next fire time after this tick.
+    next_fire: Option<OffsetDateTime>,
+}
+
+impl Firing {
+    /// Drops the oldest `dropped` due occurrences and fires the next, if any;
+    /// `following` is the first occurrence after them all.
+    fn dropping(
+        occurrences: &[OffsetDateTime],
+        dropped: usize,
+        following: Option<OffsetDateTime>,
+    ) -> Self {
+        Self {
+            scheduled_at: occurrences.get(dropped).copied(),
+            missed_count: dropped as u32,
+            next_fire: occurrences.get(dropped + 1).copied().or(following),
+        }
+    }
+}
 
 /// Scheduler engine that checks for due schedules.
 pub struct Scheduler {
     store: Arc<ScheduleStore>,
     tick_interval: Duration,
+    misfire_grace: time::Duration,
 }
 
 impl Scheduler {
     /// Creates a new scheduler.
-    pub fn new(store: Arc<ScheduleStore>, tick_interval_ms: u64) -> Self {
-        Self { store, tick_interval: Duration::from_millis(tick_interval_ms) }
+    pub fn new(store: Arc<ScheduleStore>, tick_interval_ms: u64, misfire_grace_ms: u64) -> Self {
+        Self {
+            store,
+            tick_interval: Duration::from_millis(tick_interval_ms),
+            misfire_grace: time::Duration::milliseconds(misfire_grace_ms as i64),
+        }
     }
 
-    /// Starts the scheduler loop.
-    pub async fn run(self) {
+    /// Runs the scheduler loop until `shutdown` is cancelled.
+    ///
+    /// A tick in progress is finished first, so a due schedule is never left
+    /// half-triggered.
+    pub async fn run(self, shutdown: CancellationToken) {
         let mut interval = tokio::time::interval(self.tick_interval);
 
         info!(
@@ -30,12 +72,17 @@ impl Scheduler {
         );
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
 
             if let Err(e) = self.tick().await {
                 error!("Scheduler tick error: {}", e);
             }
         }
+
+        info!("Scheduler stopped");
     }
 
     /// Performs a scheduler tick at the current time.
@@ -51,6 +98,9 @@ impl Scheduler {
     /// - One-time schedules: `next_fire` is set to `None`
     ///
     /// This prevents double-triggering even if status is manually reset to Active.
+    ///
+    /// Occurrences due more than the misfire grace ago were missed, and the
+    /// schedule's misfire policy decides which of them fire.
     pub async fn tick_at(&self, now: OffsetDateTime) -> anyhow::Result<()> {
         debug!("Scheduler tick at {}", now);
 
@@ -58,9 +108,12 @@ impl Scheduler {
         let schedules = self.store.list(Some(ScheduleStatus::Active), None).await?;
 
         for mut schedule in schedules {
+            let zone = Zone::parse(schedule.timezone.as_deref())?;
+
             // Calculate next_fire if not set
             if schedule.next_fire.is_none() {
-                let next = calculate_initial_next_fire(&schedule.trigger, schedule.created_at)?;
+                let next =
+                    calculate_initial_next_fire(&schedule.trigger, schedule.created_at, zone)?;
                 schedule.next_fire = Some(next);
                 self.store
                     .update_fire_times(&schedule.id, Some(next), None, ScheduleStatus::Active)
@@ -75,43 +128,117 @@ impl Scheduler {
             if let Some(next_fire) = schedule.next_fire
                 && next_fire <= now
             {
-                info!(
-                    "Schedule {} '{}' is due, marking as triggered",
-                    schedule.id, schedule.name
-                );
-
-                // Calculate new next_fire immediately:
-                // - For recurring: next occurrence
-                // - For one-time: None
-                let new_next_fire = match &schedule.trigger {
-                    TriggerSpec::Every { period, at_time } => {
-                        Some(calculate_next_fire(period, at_time, now)?)
+                let firing = self.plan_firing(&schedule, next_fire, now, zone)?;
+                if firing.missed_count > 0 {
+                    info!(
+                        "Schedule {} '{}' missed {} occurrences, not firing them ({:?})",
+                        schedule.id, schedule.name, firing.missed_count, schedule.misfire_policy
+                    );
+                }
+
+                match (firing.scheduled_at, firing.next_fire) {
+                    // Mark as triggered with updated next_fire
+                    (Some(scheduled_at), next_fire) => {
+                        info!(
+                            "Schedule {} '{}' is due, marking as triggered",
+                            schedule.id, schedule.name
+                        );
+                        self.store
+                            .mark_triggered(
+                                &schedule.id,
+                                scheduled_at,
+                                now,
+                                firing.missed_count,
+                                next_fire,
+                            )
+                            .await?;
                     }
-                    // Once, In are one-time triggers; Cron TODO: should be recurring when implemented
-                    _ => None,
-                };
-
-                // Mark as triggered with updated next_fire
-                self.store
-                    .update_fire_times(
-                        &schedule.id,
-                        new_next_fire,
-                        Some(next_fire),
-                        ScheduleStatus::Triggered,
-                    )
-                    .await?;
+                    (None, Some(next_fire)) => {
+                        self.store
+                            .update_fire_times(
+                                &schedule.id,
+                                Some(next_fire),
+                                schedule.last_fire,
+                                ScheduleStatus::Active,
+                            )
+                            .await?;
+                    }
+                    (None, None) => {
+                        self.store
+                            .update_status(&schedule.id, ScheduleStatus::Completed)
+                            .await?;
+                    }
+                }
             }
         }
 
         Ok(())
     }
+
+    /// Works out which occurrences of a schedule, due since `due`, fire now.
+    ///
+    /// The occurrence fired is the oldest not dropped. Recurring schedules
+    /// carry on from the next occurrence still to fire, so after firing all
+    /// missed ones they continue from the first occurrence after `now`, until
+    /// they reach their `until` or `max_occurrences`.
+    fn plan_firing(
+        &self,
+        schedule: &Schedule,
+        due: OffsetDateTime,
+        now: OffsetDateTime,
+        zone: Zone,
+    ) -> anyhow::Result<Firing> {
+        // Ended before this occurrence, e.g. by an edit
+        if !can_fire_at(schedule, due) {
+            return Ok(Firing { scheduled_at: None, missed_count: 0, next_fire: None });
+        }
+
+        // Occurrences due by now, oldest first
+        let mut occurrences = vec![due];
+        while occurrences.len() < MAX_DUE_OCCURRENCES {
+            let last = occurrences[occurrences.len() - 1];
+            match calculate_next_occurrence(&schedule.trigger, last, zone)? {
+                Some(next) if next <= now && can_fire_at(schedule, next) => occurrences.push(next),
+                _ => break,
+            }
+        }
+        let following = calculate_next_occurrence(&schedule.trigger, now, zone)?
+            .filter(|&next| can_fire_at(schedule, next));
+        let missed = occurrences
+            .iter()
+            .take_while(|&&at| at < now - self.misfire_grace)
+            .count();
+
+        let mut firing = match schedule.misfire_policy {
+            // One fire stands for all of them
+            MisfirePolicy::FireOnce => Firing {
+                scheduled_at: Some(due),
+                missed_count: occurrences.len() as u32 - 1,
+                next_fire: following,
+            },
+            MisfirePolicy::FireAll { max } => {
+                Firing::dropping(&occurrences, missed.saturating_sub(max as usize), following)
+            }
+            MisfirePolicy::Skip => Firing::dropping(&occurrences, missed, following),
+        };
+
+        // The last fire allowed ends the schedule
+        if firing.scheduled_at.is_some()
+            && schedule
+                .max_occurrences
+                .is_some_and(|max| schedule.fire_count + 1 >= max)
+        {
+            firing.next_fire = None;
+        }
+        Ok(firing)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::schedule::{Period, TriggerSpec};
-    use crate::store::calculate_next_fire;
+    use crate::schedule::{Period, TriggerSpec, UpdateScheduleRequest};
+    use crate::store::{apply_update, calculate_next_cron_fire, calculate_next_fire};
     use time::ext::NumericalDuration;
     use time::macros::datetime;
 
@@ -120,7 +247,7 @@ mod tests {
         let now = OffsetDateTime::now_utc();
         let at_time = Some("09:00".to_string());
 
-        let next = calculate_next_fire(&Period::Daily, &at_time, now).unwrap();
+        let next = calculate_next_fire(&Period::Daily, &at_time, now, Zone::Utc).unwrap();
 
         // Should be tomorrow at 09:00
         assert!(next > now);
@@ -132,7 +259,7 @@ mod tests {
     fn test_calculate_next_fire_hourly() {
         let now = OffsetDateTime::now_utc();
 
-        let next = calculate_next_fire(&Period::Hourly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Hourly, &None, now, Zone::Utc).unwrap();
 
         // Should be about 1 hour from now
         assert!(next > now);
@@ -145,7 +272,7 @@ mod tests {
         let at = now + 1.hours();
         let trigger = TriggerSpec::Once { at };
 
-        let next = calculate_initial_next_fire(&trigger, now).unwrap();
+        let next = calculate_initial_next_fire(&trigger, now, Zone::Utc).unwrap();
 
         assert_eq!(next, at);
     }
@@ -155,7 +282,7 @@ mod tests {
         let now = OffsetDateTime::now_utc();
         let trigger = TriggerSpec::In { duration_seconds: 3600 };
 
-        let next = calculate_initial_next_fire(&trigger, now).unwrap();
+        let next = calculate_initial_next_fire(&trigger, now, Zone::Utc).unwrap();
 
         assert!(next > now);
         assert!(next - now <= 2.hours());
@@ -166,7 +293,7 @@ mod tests {
     #[test]
     fn test_calculate_next_fire_monthly_normal() {
         let now = datetime!(2025-03-15 10:00 UTC);
-        let next = calculate_next_fire(&Period::Monthly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Monthly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.month(), time::Month::April);
         assert_eq!(next.day(), 15);
     }
@@ -175,7 +302,7 @@ mod tests {
     fn test_calculate_next_fire_monthly_31_to_28() {
         // Jan 31 -> Feb 28 (non-leap year)
         let now = datetime!(2025-01-31 10:00 UTC);
-        let next = calculate_next_fire(&Period::Monthly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Monthly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.month(), time::Month::February);
         assert_eq!(next.day(), 28);
         assert_eq!(next.year(), 2025);
@@ -185,7 +312,7 @@ mod tests {
     fn test_calculate_next_fire_monthly_31_to_29_leap_year() {
         // Jan 31 -> Feb 29 (leap year)
         let now = datetime!(2024-01-31 10:00 UTC);
-        let next = calculate_next_fire(&Period::Monthly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Monthly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.month(), time::Month::February);
         assert_eq!(next.day(), 29);
         assert_eq!(next.year(), 2024);
@@ -195,7 +322,7 @@ mod tests {
     fn test_calculate_next_fire_monthly_december_wrap() {
         // Dec 15 -> Jan 15 next year
         let now = datetime!(2025-12-15 10:00 UTC);
-        let next = calculate_next_fire(&Period::Monthly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Monthly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.month(), time::Month::January);
         assert_eq!(next.day(), 15);
         assert_eq!(next.year(), 2026);
@@ -205,7 +332,7 @@ mod tests {
     fn test_calculate_next_fire_monthly_30_day_month() {
         // Mar 31 -> Apr 30 (April has only 30 days)
         let now = datetime!(2025-03-31 10:00 UTC);
-        let next = calculate_next_fire(&Period::Monthly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Monthly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.month(), time::Month::April);
         assert_eq!(next.day(), 30);
     }
@@ -214,7 +341,7 @@ mod tests {
     fn test_calculate_next_fire_monthly_feb_28_to_march() {
         // Feb 28 -> Mar 28
         let now = datetime!(2025-02-28 10:00 UTC);
-        let next = calculate_next_fire(&Period::Monthly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Monthly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.month(), time::Month::March);
         assert_eq!(next.day(), 28);
     }
@@ -222,7 +349,7 @@ mod tests {
     #[test]
     fn test_calculate_next_fire_monthly_preserves_time() {
         let now = datetime!(2025-03-15 14:30:45 UTC);
-        let next = calculate_next_fire(&Period::Monthly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Monthly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.hour(), 14);
         assert_eq!(next.minute(), 30);
         assert_eq!(next.second(), 45);
@@ -233,7 +360,7 @@ mod tests {
     #[test]
     fn test_calculate_next_fire_yearly_normal() {
         let now = datetime!(2025-03-15 10:00 UTC);
-        let next = calculate_next_fire(&Period::Yearly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Yearly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.year(), 2026);
         assert_eq!(next.month(), time::Month::March);
         assert_eq!(next.day(), 15);
@@ -243,7 +370,7 @@ mod tests {
     fn test_calculate_next_fire_yearly_leap_to_non_leap() {
         // Feb 29 (leap year) -> Feb 28 (non-leap year)
         let now = datetime!(2024-02-29 10:00 UTC);
-        let next = calculate_next_fire(&Period::Yearly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Yearly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.year(), 2025);
         assert_eq!(next.month(), time::Month::February);
         assert_eq!(next.day(), 28);
@@ -254,14 +381,14 @@ mod tests {
         // Feb 29 schedule downgrades to Feb 28 and stays there
         // (we don't upgrade back to Feb 29 to avoid affecting explicit Feb 28 schedules)
         let now = datetime!(2024-02-29 10:00 UTC);
-        let next1 = calculate_next_fire(&Period::Yearly, &None, now).unwrap();
+        let next1 = calculate_next_fire(&Period::Yearly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next1.day(), 28); // 2025
         assert_eq!(next1.year(), 2025);
-        let next2 = calculate_next_fire(&Period::Yearly, &None, next1).unwrap();
+        let next2 = calculate_next_fire(&Period::Yearly, &None, next1, Zone::Utc).unwrap();
         assert_eq!(next2.day(), 28); // 2026
-        let next3 = calculate_next_fire(&Period::Yearly, &None, next2).unwrap();
+        let next3 = calculate_next_fire(&Period::Yearly, &None, next2, Zone::Utc).unwrap();
         assert_eq!(next3.day(), 28); // 2027
-        let next4 = calculate_next_fire(&Period::Yearly, &None, next3).unwrap();
+        let next4 = calculate_next_fire(&Period::Yearly, &None, next3, Zone::Utc).unwrap();
         assert_eq!(next4.year(), 2028);
         assert_eq!(next4.day(), 28); // Stays on Feb 28 even in leap year
     }
@@ -270,13 +397,13 @@ mod tests {
     fn test_calculate_next_fire_yearly_explicit_feb_28() {
         // Explicit Feb 28 schedule should stay on Feb 28, never upgrade to Feb 29
         let now = datetime!(2025-02-28 10:00 UTC);
-        let next1 = calculate_next_fire(&Period::Yearly, &None, now).unwrap();
+        let next1 = calculate_next_fire(&Period::Yearly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next1.day(), 28);
         assert_eq!(next1.year(), 2026);
         // Transition to leap year 2028
-        let next2 = calculate_next_fire(&Period::Yearly, &None, next1).unwrap();
+        let next2 = calculate_next_fire(&Period::Yearly, &None, next1, Zone::Utc).unwrap();
         assert_eq!(next2.day(), 28); // 2027
-        let next3 = calculate_next_fire(&Period::Yearly, &None, next2).unwrap();
+        let next3 = calculate_next_fire(&Period::Yearly, &None, next2, Zone::Utc).unwrap();
         assert_eq!(next3.year(), 2028);
         assert_eq!(next3.day(), 28); // Still Feb 28, not upgraded to Feb 29
     }
@@ -284,7 +411,7 @@ mod tests {
     #[test]
     fn test_calculate_next_fire_yearly_preserves_time() {
         let now = datetime!(2025-06-15 08:45:30 UTC);
-        let next = calculate_next_fire(&Period::Yearly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Yearly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.hour(), 8);
         assert_eq!(next.minute(), 45);
         assert_eq!(next.second(), 30);
@@ -293,12 +420,78 @@ mod tests {
     #[test]
     fn test_calculate_next_fire_yearly_dec_31() {
         let now = datetime!(2025-12-31 23:59:59 UTC);
-        let next = calculate_next_fire(&Period::Yearly, &None, now).unwrap();
+        let next = calculate_next_fire(&Period::Yearly, &None, now, Zone::Utc).unwrap();
         assert_eq!(next.year(), 2026);
         assert_eq!(next.month(), time::Month::December);
         assert_eq!(next.day(), 31);
     }
 
+    #[test]
+    fn test_calculate_next_fire_follows_local_time_across_dst() {
+        let berlin = Zone::parse(Some("Europe/Berlin")).unwrap();
+        let at_time = Some("09:00".to_string());
+
+        // 09:00 CET is 08:00 UTC; after clocks spring forward, 09:00 CEST is 07:00 UTC
+        let next = calculate_next_fire(
+            &Period::Daily,
+            &at_time,
+            datetime!(2025-03-29 09:00 UTC),
+            berlin,
+        )
+        .unwrap();
+        assert_eq!(next, datetime!(2025-03-30 07:00 UTC));
+
+        // 02:30 is skipped on 2025-03-30, and fires an hour later
+        let at_time = Some("02:30".to_string());
+        let next = calculate_next_fire(
+            &Period::Daily,
+            &at_time,
+            datetime!(2025-03-29 02:00 UTC),
+            berlin,
+        )
+        .unwrap();
+        assert_eq!(next, datetime!(2025-03-30 01:30 UTC));
+
+        // 02:30 happens twice on 2025-10-26, and fires only the first time
+        let next = calculate_next_fire(
+            &Period::Daily,
+            &at_time,
+            datetime!(2025-10-25 01:00 UTC),
+            berlin,
+        )
+        .unwrap();
+        assert_eq!(next, datetime!(2025-10-26 00:30 UTC));
+        let next = calculate_next_fire(&Period::Daily, &at_time, next, berlin).unwrap();
+        assert_eq!(next, datetime!(2025-10-27 01:30 UTC));
+    }
+
+    #[test]
+    fn test_calculate_next_cron_fire_across_dst() {
+        let berlin = Zone::parse(Some("Europe/Berlin")).unwrap();
+
+        // Every 30 minutes: the repeated hour on 2025-10-26 fires both times round
+        let mut fires = Vec::new();
+        let mut from = datetime!(2025-10-26 00:00 UTC);
+        for _ in 0..4 {
+            from = calculate_next_cron_fire("*/30 * * * *", from, berlin)
+                .unwrap()
+                .unwrap();
+            fires.push(from);
+        }
+        assert_eq!(
+            fires,
+            vec![
+                datetime!(2025-10-26 00:30 UTC),
+                datetime!(2025-10-26 01:00 UTC),
+                datetime!(2025-10-26 01:30 UTC),
+                datetime!(2025-10-26 02:00 UTC),
+            ]
+        );
+
+        // Unknown zones are rejected
+        assert!(Zone::parse(Some("Europe/Atlantis")).is_err());
+    }
+
     // === Bug 1 Tests: Recurring schedule next_fire updated on trigger ===
 
     #[tokio::test]
@@ -306,13 +499,15 @@ mod tests {
         use crate::schedule::{Priority, Schedule};
 
         let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
-        let scheduler = Scheduler::new(store.clone(), 1000);
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
 
         // Create hourly schedule
         let schedule = Schedule {
             id: "hourly".into(),
             name: "Hourly test".into(),
             trigger: TriggerSpec::Every { period: Period::Hourly, at_time: None },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
             payload: serde_json::json!({}),
             tags: vec![],
             priority: Priority::Normal,
@@ -320,6 +515,9 @@ mod tests {
             created_at: datetime!(2025-03-12 08:00 UTC),
             next_fire: Some(datetime!(2025-03-12 09:00 UTC)),
             last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
         };
         store.create(&schedule).await.unwrap();
 
@@ -345,12 +543,14 @@ mod tests {
         use crate::schedule::{Priority, Schedule};
 
         let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
-        let scheduler = Scheduler::new(store.clone(), 1000);
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
 
         let schedule = Schedule {
             id: "hourly".into(),
             name: "Hourly test".into(),
             trigger: TriggerSpec::Every { period: Period::Hourly, at_time: None },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
             payload: serde_json::json!({}),
             tags: vec![],
             priority: Priority::Normal,
@@ -358,6 +558,9 @@ mod tests {
             created_at: datetime!(2025-03-12 08:00 UTC),
             next_fire: Some(datetime!(2025-03-12 09:00 UTC)),
             last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
         };
         store.create(&schedule).await.unwrap();
 
@@ -390,12 +593,14 @@ mod tests {
         use crate::schedule::{Priority, Schedule};
 
         let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
-        let scheduler = Scheduler::new(store.clone(), 1000);
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
 
         let schedule = Schedule {
             id: "once".into(),
             name: "One-time test".into(),
             trigger: TriggerSpec::Once { at: datetime!(2025-03-12 09:00 UTC) },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
             payload: serde_json::json!({}),
             tags: vec![],
             priority: Priority::Normal,
@@ -403,6 +608,9 @@ mod tests {
             created_at: datetime!(2025-03-12 08:00 UTC),
             next_fire: Some(datetime!(2025-03-12 09:00 UTC)),
             last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
         };
         store.create(&schedule).await.unwrap();
 
@@ -417,18 +625,92 @@ mod tests {
         assert!(triggered.next_fire.is_none());
     }
 
+    #[tokio::test]
+    async fn test_cron_schedule_recurs_on_trigger() {
+        use crate::schedule::{Priority, Schedule};
+
+        let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
+
+        // Weekdays at 09:30; 2025-03-14 is a Friday
+        let schedule = Schedule {
+            id: "cron".into(),
+            name: "Cron test".into(),
+            trigger: TriggerSpec::Cron { expression: "30 9 * * MON-FRI".into() },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
+            payload: serde_json::json!({}),
+            tags: vec![],
+            priority: Priority::Normal,
+            status: ScheduleStatus::Active,
+            created_at: datetime!(2025-03-14 08:00 UTC),
+            next_fire: None,
+            last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
+        };
+        store.create(&schedule).await.unwrap();
+
+        scheduler
+            .tick_at(datetime!(2025-03-14 09:00 UTC))
+            .await
+            .unwrap();
+        let pending = store.get("cron").await.unwrap().unwrap();
+        assert_eq!(pending.status, ScheduleStatus::Active);
+        assert_eq!(pending.next_fire, Some(datetime!(2025-03-14 09:30 UTC)));
+
+        scheduler
+            .tick_at(datetime!(2025-03-14 09:30:02 UTC))
+            .await
+            .unwrap();
+        let triggered = store.get("cron").await.unwrap().unwrap();
+        assert_eq!(triggered.status, ScheduleStatus::Triggered);
+        assert_eq!(triggered.last_fire, Some(datetime!(2025-03-14 09:30 UTC)));
+        // Skips the weekend
+        assert_eq!(triggered.next_fire, Some(datetime!(2025-03-17 09:30 UTC)));
+
+        // Acking reactivates it rather than completing it
+        store
+            .ack_triggered_at(&["cron".into()], datetime!(2025-03-14 09:31 UTC))
+            .await
+            .unwrap();
+        let acked = store.get("cron").await.unwrap().unwrap();
+        assert_eq!(acked.status, ScheduleStatus::Active);
+        assert_eq!(acked.next_fire, Some(datetime!(2025-03-17 09:30 UTC)));
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_stops_on_shutdown() {
+        let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
+        let scheduler = Scheduler::new(store, 10, 60_000);
+        let shutdown = CancellationToken::new();
+
+        let running = tokio::spawn(scheduler.run(shutdown.clone()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!running.is_finished());
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(1), running)
+            .await
+            .expect("scheduler should stop when shut down")
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_full_recurring_schedule_lifecycle() {
         use crate::schedule::{Priority, Schedule};
 
         let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
-        let scheduler = Scheduler::new(store.clone(), 1000);
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
 
         // Create minutely schedule (simplified for testing)
         let schedule = Schedule {
             id: "minutely".into(),
             name: "Minutely test".into(),
             trigger: TriggerSpec::Every { period: Period::Minutely, at_time: None },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
             payload: serde_json::json!({}),
             tags: vec![],
             priority: Priority::Normal,
@@ -436,6 +718,9 @@ mod tests {
             created_at: datetime!(2025-03-12 09:00 UTC),
             next_fire: Some(datetime!(2025-03-12 09:00 UTC)),
             last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
         };
         store.create(&schedule).await.unwrap();
 
@@ -483,4 +768,217 @@ mod tests {
             Some(datetime!(2025-03-12 09:02:05 UTC))
         );
     }
+
+    // === Misfires ===
+
+    fn daily_schedule(misfire_policy: MisfirePolicy) -> Schedule {
+        use crate::schedule::Priority;
+
+        Schedule {
+            id: "daily".into(),
+            name: "Daily test".into(),
+            trigger: TriggerSpec::Every { period: Period::Daily, at_time: Some("09:00".into()) },
+            timezone: None,
+            misfire_policy,
+            payload: serde_json::json!({}),
+            tags: vec![],
+            priority: Priority::Normal,
+            status: ScheduleStatus::Active,
+            created_at: datetime!(2025-03-01 08:00 UTC),
+            next_fire: Some(datetime!(2025-03-10 09:00 UTC)),
+            last_fire: Some(datetime!(2025-03-09 09:00 UTC)),
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_firing_by_misfire_policy() {
+        let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
+        let scheduler = Scheduler::new(store, 1000, 60_000);
+        let due = datetime!(2025-03-10 09:00 UTC);
+        let plan =
+            |policy, now| scheduler.plan_firing(&daily_schedule(policy), due, now, Zone::Utc);
+
+        // Down from before the 10th's 09:00 until after the 13th's
+        let now = datetime!(2025-03-13 09:30 UTC);
+        assert_eq!(
+            plan(MisfirePolicy::FireOnce, now).unwrap(),
+            Firing {
+                scheduled_at: Some(due),
+                missed_count: 3,
+                next_fire: Some(datetime!(2025-03-14 09:00 UTC)),
+            }
+        );
+        assert_eq!(
+            plan(MisfirePolicy::FireAll { max: 2 }, now).unwrap(),
+            Firing {
+                scheduled_at: Some(datetime!(2025-03-12 09:00 UTC)),
+                missed_count: 2,
+                next_fire: Some(datetime!(2025-03-13 09:00 UTC)),
+            }
+        );
+        assert_eq!(
+            plan(MisfirePolicy::Skip, now).unwrap(),
+            Firing {
+                scheduled_at: None,
+                missed_count: 4,
+                next_fire: Some(datetime!(2025-03-14 09:00 UTC)),
+            }
+        );
+
+        // Late within the grace is not missed
+        let now = datetime!(2025-03-10 09:00:30 UTC);
+        for policy in
+            [MisfirePolicy::FireOnce, MisfirePolicy::FireAll { max: 0 }, MisfirePolicy::Skip]
+        {
+            assert_eq!(
+                plan(policy, now).unwrap(),
+                Firing {
+                    scheduled_at: Some(due),
+                    missed_count: 0,
+                    next_fire: Some(datetime!(2025-03-11 09:00 UTC)),
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fire_all_catches_up_across_acks() {
+        let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
+        store
+            .create(&daily_schedule(MisfirePolicy::FireAll { max: 2 }))
+            .await
+            .unwrap();
+
+        // Each ack lets the next missed occurrence fire, oldest first
+        let now = datetime!(2025-03-13 09:30 UTC);
+        let mut fired = Vec::new();
+        for _ in 0..2 {
+            scheduler.tick_at(now).await.unwrap();
+            let triggered = store.get_triggered().await.unwrap();
+            assert_eq!(triggered.len(), 1);
+            assert_eq!(triggered[0].triggered_at, now);
+            fired.push((triggered[0].scheduled_at, triggered[0].missed_count));
+            store
+                .ack_triggered_at(&["daily".into()], now)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            fired,
+            vec![(datetime!(2025-03-12 09:00 UTC), 2), (datetime!(2025-03-13 09:00 UTC), 0),]
+        );
+
+        // Caught up, so it carries on as usual
+        scheduler.tick_at(now).await.unwrap();
+        let schedule = store.get("daily").await.unwrap().unwrap();
+        assert_eq!(schedule.status, ScheduleStatus::Active);
+        assert_eq!(schedule.next_fire, Some(datetime!(2025-03-14 09:00 UTC)));
+    }
+
+    // === End conditions ===
+
+    #[tokio::test]
+    async fn test_schedule_completes_at_its_end() {
+        let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
+
+        let mut twice = daily_schedule(MisfirePolicy::FireOnce);
+        twice.id = "twice".into();
+        twice.max_occurrences = Some(2);
+        store.create(&twice).await.unwrap();
+        let mut until = daily_schedule(MisfirePolicy::FireOnce);
+        until.id = "until".into();
+        until.until = Some(datetime!(2025-03-11 12:00 UTC));
+        store.create(&until).await.unwrap();
+
+        // Both fire on the 10th and the 11th, and then are done
+        let ids = ["twice".to_string(), "until".to_string()];
+        for now in [datetime!(2025-03-10 09:00 UTC), datetime!(2025-03-11 09:00 UTC)] {
+            scheduler.tick_at(now).await.unwrap();
+            assert_eq!(store.get_triggered().await.unwrap().len(), 2);
+            store.ack_triggered_at(&ids, now).await.unwrap();
+        }
+        for id in &ids {
+            let schedule = store.get(id).await.unwrap().unwrap();
+            assert_eq!(schedule.status, ScheduleStatus::Completed);
+            assert_eq!(schedule.fire_count, 2);
+            assert_eq!(schedule.last_fire, Some(datetime!(2025-03-11 09:00 UTC)));
+        }
+
+        // Reactivating is rejected until the limit leaves room to fire again
+        let now = datetime!(2025-03-11 10:00 UTC);
+        let reactivate = |max_occurrences, until| UpdateScheduleRequest {
+            status: Some(ScheduleStatus::Active),
+            max_occurrences,
+            until,
+            ..Default::default()
+        };
+        for (id, raised) in [
+            ("twice", reactivate(Some(3), None)),
+            (
+                "until",
+                reactivate(None, Some(datetime!(2025-03-12 12:00 UTC))),
+            ),
+        ] {
+            let read = store.get(id).await.unwrap().unwrap();
+            assert!(apply_update(&mut read.clone(), reactivate(None, None), now).is_err());
+            let mut edited = read.clone();
+            apply_update(&mut edited, raised, now).unwrap();
+            assert!(store.update(&edited, &read).await.unwrap());
+        }
+
+        // Each fires once more, on the 12th, and is done again
+        let now = datetime!(2025-03-12 09:00 UTC);
+        scheduler.tick_at(now).await.unwrap();
+        assert_eq!(store.get_triggered().await.unwrap().len(), 2);
+        store.ack_triggered_at(&ids, now).await.unwrap();
+        for id in &ids {
+            let schedule = store.get(id).await.unwrap().unwrap();
+            assert_eq!(schedule.status, ScheduleStatus::Completed);
+            assert_eq!(schedule.fire_count, 3);
+            assert_eq!(schedule.last_fire, Some(now));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resumed_one_time_schedule_fires_once() {
+        let store = Arc::new(ScheduleStore::new(":memory:").await.unwrap());
+        let scheduler = Scheduler::new(store.clone(), 1000, 60_000);
+
+        let mut once = daily_schedule(MisfirePolicy::FireOnce);
+        once.id = "once".into();
+        once.trigger = TriggerSpec::Once { at: datetime!(2025-03-10 09:00 UTC) };
+        once.status = ScheduleStatus::Paused;
+        once.last_fire = None;
+        store.create(&once).await.unwrap();
+
+        // Resumed after its time, it fires late rather than being skipped
+        let now = datetime!(2025-03-12 10:00 UTC);
+        let read = store.get("once").await.unwrap().unwrap();
+        let mut resumed = read.clone();
+        let req =
+            UpdateScheduleRequest { status: Some(ScheduleStatus::Active), ..Default::default() };
+        apply_update(&mut resumed, req, now).unwrap();
+        assert!(store.update(&resumed, &read).await.unwrap());
+
+        scheduler.tick_at(now).await.unwrap();
+        let triggered = store.get_triggered().await.unwrap();
+        assert_eq!(triggered.len(), 1);
+        assert_eq!(triggered[0].scheduled_at, datetime!(2025-03-10 09:00 UTC));
+        store
+            .ack_triggered_at(&["once".to_string()], now)
+            .await
+            .unwrap();
+
+        // And only once
+        scheduler.tick_at(now + 1.days()).await.unwrap();
+        assert!(store.get_triggered().await.unwrap().is_empty());
+        let done = store.get("once").await.unwrap().unwrap();
+        assert_eq!(done.status, ScheduleStatus::Completed);
+        assert_eq!(done.fire_count, 1);
+    }
 }
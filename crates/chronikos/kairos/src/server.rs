@@ -10,14 +10,16 @@ use axum::{
 use serde::Deserialize;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tracing::info;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use uuid::Uuid;
 
 use crate::config::Config;
 use crate::schedule::*;
 use crate::scheduler::Scheduler;
-use crate::store::{ScheduleStore, calculate_initial_next_fire};
+use crate::store::{ScheduleStore, apply_update, calculate_initial_next_fire, can_fire_at};
 
 /// Application state shared across handlers.
 #[derive(Clone)]
@@ -51,7 +53,11 @@ impl KairosServer {
         Ok(Self { config, state })
     }
 
-    /// Starts the server.
+    /// Runs the server until SIGTERM or Ctrl-C.
+    ///
+    /// On shutdown, Kairos stops accepting connections and gives in-flight
+    /// requests up to `shutdown_timeout_ms` to finish, then stops the
+    /// scheduler and closes the database.
     pub async fn run(self) -> anyhow::Result<()> {
         use tower_http::{
             cors::{Any, CorsLayer},
@@ -59,8 +65,13 @@ impl KairosServer {
         };
 
         // Spawn the scheduler
-        let scheduler = Scheduler::new(self.state.store.clone(), self.config.tick_interval_ms);
-        tokio::spawn(scheduler.run());
+        let shutdown = CancellationToken::new();
+        let scheduler = Scheduler::new(
+            self.state.store.clone(),
+            self.config.tick_interval_ms,
+            self.config.misfire_grace_ms,
+        );
+        let scheduler = tokio::spawn(scheduler.run(shutdown.clone()));
 
         let app = Router::new()
             .route("/health", get(health_check))
@@ -94,9 +105,15 @@ impl KairosServer {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to bind to address: {}", e))?;
 
-        axum::serve(listener, app)
-            .await
-            .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+        let drain_timeout = Duration::from_millis(self.config.shutdown_timeout_ms);
+        epha_shutdown::serve(listener, app, shutdown.clone(), drain_timeout).await?;
+
+        if let Err(e) = scheduler.await {
+            error!("Scheduler failed: {}", e);
+        }
+
+        self.state.store.close().await;
+        info!("Kairos stopped");
 
         Ok(())
     }
@@ -143,7 +160,9 @@ async fn create_schedule(
     let id = Uuid::new_v4().to_string();
     let now = time::OffsetDateTime::now_utc();
 
-    let next_fire = match calculate_initial_next_fire(&req.trigger, now) {
+    let next_fire = match Zone::parse(req.timezone.as_deref())
+        .and_then(|zone| calculate_initial_next_fire(&req.trigger, now, zone))
+    {
         Ok(t) => Some(t),
         Err(e) => {
             return (
@@ -157,6 +176,8 @@ async fn create_schedule(
         id: id.clone(),
         name: req.name,
         trigger: req.trigger,
+        timezone: req.timezone,
+        misfire_policy: req.misfire_policy,
         payload: req.payload,
         tags: req.tags,
         priority: req.priority,
@@ -164,8 +185,23 @@ async fn create_schedule(
         created_at: now,
         next_fire,
         last_fire: None,
+        until: req.until,
+        max_occurrences: req.max_occurrences,
+        fire_count: 0,
     };
 
+    if schedule
+        .next_fire
+        .is_some_and(|next| !can_fire_at(&schedule, next))
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Schedule would not fire before its until or max_occurrences"
+            })),
+        );
+    }
+
     match state.store.create(&schedule).await {
         Ok(_) => (
             StatusCode::CREATED,
@@ -264,42 +300,48 @@ async fn delete_schedule(
     }
 }
 
-/// Update a schedule.
+/// Update a schedule: edit it, change its status, or defer its next fire.
 async fn update_schedule(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Json(req): Json<UpdateScheduleRequest>,
 ) -> impl IntoResponse {
-    if let Some(status) = req.status {
-        match state.store.update_status(&id, status).await {
-            Ok(_) => {
-                // Return updated schedule
-                match state.store.get(&id).await {
-                    Ok(Some(schedule)) => {
-                        return (
-                            StatusCode::OK,
-                            Json(serde_json::to_value(schedule).unwrap()),
-                        );
-                    }
-                    _ => {
-                        return (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(serde_json::json!({ "error": "schedule not found after update" })),
-                        );
-                    }
-                }
-            }
-            Err(e) => {
-                return (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(serde_json::json!({ "error": e.to_string() })),
-                );
-            }
+    let read = match state.store.get(&id).await {
+        Ok(Some(schedule)) => schedule,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "schedule not found" })),
+            );
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            );
         }
+    };
+
+    let mut schedule = read.clone();
+    if let Err(e) = apply_update(&mut schedule, req, time::OffsetDateTime::now_utc()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        );
     }
 
-    (
-        StatusCode::BAD_REQUEST,
-        Json(serde_json::json!({ "error": "no update specified" })),
-    )
+    match state.store.update(&schedule, &read).await {
+        Ok(true) => (
+            StatusCode::OK,
+            Json(serde_json::to_value(schedule).unwrap()),
+        ),
+        Ok(false) => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "schedule changed during the update; retry" })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        ),
+    }
 }
@@ -3,7 +3,7 @@
 use anyhow::Result;
 use sqlx::SqlitePool;
 use time::util::is_leap_year;
-use time::{Month, OffsetDateTime};
+use time::{Month, OffsetDateTime, PrimitiveDateTime};
 
 use crate::schedule::*;
 
@@ -34,6 +34,11 @@ impl ScheduleStore {
         Ok(Self { pool })
     }
 
+    /// Closes the connection pool, on shutdown.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
     async fn run_migrations(pool: &SqlitePool) -> Result<()> {
         sqlx::query(
             r#"
@@ -46,19 +51,52 @@ impl ScheduleStore {
                 trigger_period TEXT,
                 trigger_at_time TEXT,
                 trigger_cron_expression TEXT,
+                timezone TEXT,
+                misfire_policy TEXT NOT NULL DEFAULT 'fire_once',
                 payload TEXT NOT NULL,
                 tags TEXT NOT NULL DEFAULT '[]',
                 priority TEXT NOT NULL DEFAULT 'normal',
                 status TEXT NOT NULL DEFAULT 'active',
                 created_at TEXT NOT NULL,
                 next_fire TEXT,
-                last_fire TEXT
+                last_fire TEXT,
+                triggered_at TEXT,
+                missed_count INTEGER NOT NULL DEFAULT 0,
+                until TEXT,
+                max_occurrences INTEGER,
+                fire_count INTEGER NOT NULL DEFAULT 0
             )
             "#,
         )
         .execute(pool)
         .await?;
 
+        // Columns added since, with defaults for existing schedules
+        for (column, definition) in [
+            ("timezone", "TEXT"),
+            ("misfire_policy", "TEXT NOT NULL DEFAULT 'fire_once'"),
+            ("triggered_at", "TEXT"),
+            ("missed_count", "INTEGER NOT NULL DEFAULT 0"),
+            ("until", "TEXT"),
+            ("max_occurrences", "INTEGER"),
+            ("fire_count", "INTEGER NOT NULL DEFAULT 0"),
+        ] {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('schedules') WHERE name = ?",
+            )
+            .bind(column)
+            .fetch_one(pool)
+            .await?;
+            if !exists {
+                sqlx::query(&format!(
+                    "ALTER TABLE schedules ADD COLUMN {} {}",
+                    column, definition
+                ))
+                .execute(pool)
+                .await?;
+            }
+        }
+
         Ok(())
     }
 
@@ -70,9 +108,10 @@ impl ScheduleStore {
             r#"
             INSERT INTO schedules (
                 id, name, trigger_type, trigger_at, trigger_duration_seconds,
-                trigger_period, trigger_at_time, trigger_cron_expression,
-                payload, tags, priority, status, created_at, next_fire, last_fire
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                trigger_period, trigger_at_time, trigger_cron_expression, timezone,
+                misfire_policy, payload, tags, priority, status, created_at, next_fire, last_fire,
+                until, max_occurrences, fire_count
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&schedule.id)
@@ -83,6 +122,8 @@ impl ScheduleStore {
         .bind(trigger_row.trigger_period.as_ref())
         .bind(trigger_row.trigger_at_time.as_ref())
         .bind(trigger_row.trigger_cron_expression.as_ref())
+        .bind(schedule.timezone.as_ref())
+        .bind(schedule.misfire_policy.to_string())
         .bind(serde_json::to_string(&schedule.payload)?)
         .bind(serde_json::to_string(&schedule.tags)?)
         .bind(schedule.priority.to_string())
@@ -104,6 +145,14 @@ impl ScheduleStore {
                 .map(|t| t.format(&time::format_description::well_known::Rfc3339))
                 .transpose()?,
         )
+        .bind(
+            schedule
+                .until
+                .map(|t| t.format(&time::format_description::well_known::Rfc3339))
+                .transpose()?,
+        )
+        .bind(schedule.max_occurrences.map(i64::from))
+        .bind(i64::from(schedule.fire_count))
         .execute(&self.pool)
         .await?;
 
@@ -193,9 +242,26 @@ impl ScheduleStore {
 
         let mut triggered = Vec::new();
         for row in rows {
+            use sqlx::Row;
+
+            let triggered_at: Option<String> = row.get("triggered_at");
+            let missed_count: i64 = row.get("missed_count");
             let schedule = self.deserialize_schedule(row)?;
-            let triggered_at = schedule.next_fire.unwrap_or(schedule.created_at);
-            triggered.push(TriggeredSchedule { schedule, triggered_at });
+            // Schedules triggered before trigger times were recorded fall
+            // back to their fire times
+            let triggered_at = match triggered_at {
+                Some(s) => {
+                    OffsetDateTime::parse(&s, &time::format_description::well_known::Rfc3339)?
+                }
+                None => schedule.next_fire.unwrap_or(schedule.created_at),
+            };
+            let scheduled_at = schedule.last_fire.unwrap_or(triggered_at);
+            triggered.push(TriggeredSchedule {
+                schedule,
+                triggered_at,
+                scheduled_at,
+                missed_count: missed_count as u32,
+            });
         }
 
         Ok(triggered)
@@ -238,6 +304,84 @@ impl ScheduleStore {
         Ok(())
     }
 
+    /// Marks a schedule triggered for its occurrence at `scheduled_at`, and
+    /// counts the fire.
+    ///
+    /// `missed_count` is how many missed occurrences were not fired, and
+    /// `next_fire` the occurrence after this one.
+    pub async fn mark_triggered(
+        &self,
+        id: &str,
+        scheduled_at: OffsetDateTime,
+        triggered_at: OffsetDateTime,
+        missed_count: u32,
+        next_fire: Option<OffsetDateTime>,
+    ) -> Result<()> {
+        let format = |t: OffsetDateTime| t.format(&time::format_description::well_known::Rfc3339);
+
+        sqlx::query(
+            r#"
+            UPDATE schedules
+            SET status = 'triggered', last_fire = ?, triggered_at = ?, missed_count = ?,
+                next_fire = ?, fire_count = fire_count + 1
+            WHERE id = ?
+            "#,
+        )
+        .bind(format(scheduled_at)?)
+        .bind(format(triggered_at)?)
+        .bind(missed_count as i64)
+        .bind(next_fire.map(format).transpose()?)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Saves an edited schedule's definition, status, end and next fire time.
+    ///
+    /// `read` is the schedule as loaded before the edit. Nothing is written,
+    /// and `false` returned, if its status or next fire changed since, e.g.
+    /// because the scheduler fired it in between.
+    pub async fn update(&self, schedule: &Schedule, read: &Schedule) -> Result<bool> {
+        let format = |t: OffsetDateTime| t.format(&time::format_description::well_known::Rfc3339);
+        let trigger_row = self.serialize_trigger(&schedule.trigger);
+
+        let result = sqlx::query(
+            r#"
+            UPDATE schedules SET
+                name = ?, trigger_type = ?, trigger_at = ?, trigger_duration_seconds = ?,
+                trigger_period = ?, trigger_at_time = ?, trigger_cron_expression = ?,
+                timezone = ?, misfire_policy = ?, payload = ?, tags = ?, priority = ?, status = ?,
+                next_fire = ?, until = ?, max_occurrences = ?
+            WHERE id = ? AND status = ? AND next_fire IS ?
+            "#,
+        )
+        .bind(&schedule.name)
+        .bind(&trigger_row.trigger_type)
+        .bind(trigger_row.trigger_at.as_ref())
+        .bind(trigger_row.trigger_duration_seconds)
+        .bind(trigger_row.trigger_period.as_ref())
+        .bind(trigger_row.trigger_at_time.as_ref())
+        .bind(trigger_row.trigger_cron_expression.as_ref())
+        .bind(schedule.timezone.as_ref())
+        .bind(schedule.misfire_policy.to_string())
+        .bind(serde_json::to_string(&schedule.payload)?)
+        .bind(serde_json::to_string(&schedule.tags)?)
+        .bind(schedule.priority.to_string())
+        .bind(schedule.status.to_string())
+        .bind(schedule.next_fire.map(format).transpose()?)
+        .bind(schedule.until.map(format).transpose()?)
+        .bind(schedule.max_occurrences.map(i64::from))
+        .bind(&schedule.id)
+        .bind(read.status.to_string())
+        .bind(read.next_fire.map(format).transpose()?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Deletes a schedule.
     pub async fn delete(&self, id: &str) -> Result<bool> {
         let result = sqlx::query("DELETE FROM schedules WHERE id = ?")
@@ -263,32 +407,64 @@ impl ScheduleStore {
                     continue;
                 }
 
-                // For recurring schedules, reactivate with next fire time
-                if let TriggerSpec::Every { period, at_time } = &schedule.trigger {
-                    // Use existing next_fire if already calculated (Bug 1 fix),
-                    // otherwise calculate from now (compatibility for old data)
-                    let next = if schedule.next_fire.map(|nf| nf > now).unwrap_or(false) {
-                        schedule.next_fire.unwrap()
-                    } else {
-                        calculate_next_fire(period, at_time, now)?
-                    };
-                    self.update_fire_times(
-                        id,
-                        Some(next),
-                        schedule.next_fire,
-                        ScheduleStatus::Active,
-                    )
-                    .await?;
-                } else {
-                    // One-time schedules are completed
-                    self.update_status(id, ScheduleStatus::Completed).await?;
+                // For recurring schedules, reactivate with next fire time.
+                let next = match schedule.next_fire {
+                    // Already calculated at trigger time (Bug 1 fix); kept even
+                    // if due already, as a missed occurrence to catch up on
+                    Some(nf) if schedule.last_fire.map_or(nf > now, |lf| nf > lf) => Some(nf),
+                    // Compatibility for old data, where next_fire was the
+                    // fire time: calculate from now
+                    Some(_) => {
+                        let zone = Zone::parse(schedule.timezone.as_deref())?;
+                        calculate_next_occurrence(&schedule.trigger, now, zone)?
+                            .filter(|&at| can_fire_at(&schedule, at))
+                    }
+                    None => None,
+                };
+
+                if self.finish_triggered(&schedule, next).await? {
+                    count += 1;
                 }
-                count += 1;
             }
         }
         Ok(count)
     }
 
+    /// Reactivates an acked schedule to fire at `next`. One-time schedules,
+    /// cron schedules that never fire again, and schedules that reached their
+    /// end have no `next` and are completed.
+    ///
+    /// Nothing is written, and `false` returned, if the schedule is no longer
+    /// triggered or its next fire changed since `read` was loaded, e.g.
+    /// because it was paused or edited in between.
+    async fn finish_triggered(
+        &self,
+        read: &Schedule,
+        next: Option<OffsetDateTime>,
+    ) -> Result<bool> {
+        let format = |t: OffsetDateTime| t.format(&time::format_description::well_known::Rfc3339);
+        let status = match next {
+            Some(_) => ScheduleStatus::Active,
+            None => ScheduleStatus::Completed,
+        };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE schedules SET status = ?, next_fire = COALESCE(?, next_fire)
+            WHERE id = ? AND status = ? AND next_fire IS ?
+            "#,
+        )
+        .bind(status.to_string())
+        .bind(next.map(format).transpose()?)
+        .bind(&read.id)
+        .bind(ScheduleStatus::Triggered.to_string())
+        .bind(read.next_fire.map(format).transpose()?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Acknowledges triggered schedules (convenience method using current time).
     pub async fn ack_triggered(&self, ids: &[ScheduleId]) -> Result<usize> {
         self.ack_triggered_at(ids, OffsetDateTime::now_utc()).await
@@ -374,6 +550,8 @@ impl ScheduleStore {
         let trigger_period: Option<String> = row.get("trigger_period");
         let trigger_at_time: Option<String> = row.get("trigger_at_time");
         let trigger_cron_expression: Option<String> = row.get("trigger_cron_expression");
+        let timezone: Option<String> = row.get("timezone");
+        let misfire_policy_str: String = row.get("misfire_policy");
         let payload_str: String = row.get("payload");
         let tags_str: String = row.get("tags");
         let priority_str: String = row.get("priority");
@@ -381,6 +559,9 @@ impl ScheduleStore {
         let created_at_str: String = row.get("created_at");
         let next_fire_str: Option<String> = row.get("next_fire");
         let last_fire_str: Option<String> = row.get("last_fire");
+        let until_str: Option<String> = row.get("until");
+        let max_occurrences: Option<i64> = row.get("max_occurrences");
+        let fire_count: i64 = row.get("fire_count");
 
         let trigger = match trigger_type.as_str() {
             "once" => TriggerSpec::Once {
@@ -405,6 +586,9 @@ impl ScheduleStore {
         let tags: Vec<String> = serde_json::from_str(&tags_str)?;
         let priority: Priority = parse_priority(&priority_str)?;
         let status: ScheduleStatus = parse_status(&status_str)?;
+        let misfire_policy: MisfirePolicy = misfire_policy_str
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!("{}", e))?;
         let created_at = OffsetDateTime::parse(
             &created_at_str,
             &time::format_description::well_known::Rfc3339,
@@ -415,11 +599,16 @@ impl ScheduleStore {
         let last_fire = last_fire_str
             .map(|s| OffsetDateTime::parse(&s, &time::format_description::well_known::Rfc3339))
             .transpose()?;
+        let until = until_str
+            .map(|s| OffsetDateTime::parse(&s, &time::format_description::well_known::Rfc3339))
+            .transpose()?;
 
         Ok(Schedule {
             id,
             name,
             trigger,
+            timezone,
+            misfire_policy,
             payload,
             tags,
             priority,
@@ -427,6 +616,9 @@ impl ScheduleStore {
             created_at,
             next_fire,
             last_fire,
+            until,
+            max_occurrences: max_occurrences.map(|max| max as u32),
+            fire_count: fire_count as u32,
         })
     }
 }
@@ -452,7 +644,7 @@ fn parse_status(s: &str) -> Result<ScheduleStatus> {
 }
 
 /// Adds one month to the given datetime, clamping day if next month is shorter.
-fn add_one_month(dt: OffsetDateTime) -> OffsetDateTime {
+fn add_one_month(dt: PrimitiveDateTime) -> PrimitiveDateTime {
     let next_month = dt.month().next();
     let next_year = if dt.month() == Month::December { dt.year() + 1 } else { dt.year() };
 
@@ -470,7 +662,7 @@ fn add_one_month(dt: OffsetDateTime) -> OffsetDateTime {
 }
 
 /// Adds one year to the given datetime, handling Feb 29 on non-leap years.
-fn add_one_year(dt: OffsetDateTime) -> OffsetDateTime {
+fn add_one_year(dt: PrimitiveDateTime) -> PrimitiveDateTime {
     let next_year = dt.year() + 1;
 
     // Handle Feb 29 -> Feb 28 on non-leap years
@@ -484,11 +676,19 @@ fn add_one_year(dt: OffsetDateTime) -> OffsetDateTime {
 }
 
 /// Calculates the next fire time for a recurring schedule.
+///
+/// Daily and longer periods follow wall-clock time in `zone`, so "daily at
+/// 09:00" stays at 09:00 local time across DST changes. A time repeated when
+/// clocks fall back fires the first time round; a time skipped when they
+/// spring forward fires as far after the change as it was into the gap.
 pub fn calculate_next_fire(
     period: &Period,
     at_time: &Option<String>,
     from: OffsetDateTime,
+    zone: Zone,
 ) -> Result<OffsetDateTime> {
+    let local = zone.to_local(from);
+
     // Parse at_time if provided (e.g., "09:00")
     // When at_time is None, preserve the original time including seconds
     let (hour, minute, second) = if let Some(time_str) = at_time {
@@ -502,69 +702,270 @@ pub fn calculate_next_fire(
             0u8, // Reset seconds when explicit time is provided
         )
     } else {
-        (from.hour(), from.minute(), from.second())
+        (local.hour(), local.minute(), local.second())
     };
 
-    let next = match period {
-        Period::Minutely => {
-            // Next minute
-            from + time::Duration::minutes(1)
-        }
-        Period::Hourly => {
-            // Next hour at the same minute
-            from + time::Duration::hours(1)
-        }
-        Period::Daily => {
-            // Next day at the specified time
-            let candidate = from
-                .replace_hour(hour)?
-                .replace_minute(minute)?
-                .replace_second(second)?;
-            if candidate > from { candidate } else { candidate + time::Duration::days(1) }
-        }
-        Period::Weekly => {
-            // Next week at the specified time
-            let candidate = from
-                .replace_hour(hour)?
-                .replace_minute(minute)?
-                .replace_second(second)?;
-            if candidate > from { candidate } else { candidate + time::Duration::weeks(1) }
-        }
-        Period::Monthly => {
-            let candidate = from
-                .replace_hour(hour)?
-                .replace_minute(minute)?
-                .replace_second(second)?;
-            if candidate > from { candidate } else { add_one_month(candidate) }
+    let advance: fn(PrimitiveDateTime) -> PrimitiveDateTime = match period {
+        // Next minute or hour: elapsed time, whatever the clocks do
+        Period::Minutely => return Ok(from + time::Duration::minutes(1)),
+        Period::Hourly => return Ok(from + time::Duration::hours(1)),
+        Period::Daily => |dt| dt + time::Duration::days(1),
+        Period::Weekly => |dt| dt + time::Duration::weeks(1),
+        Period::Monthly => add_one_month,
+        Period::Yearly => add_one_year,
+    };
+
+    // The specified time in the current period, if still to come,
+    // otherwise in the next one
+    let candidate = local
+        .replace_hour(hour)?
+        .replace_minute(minute)?
+        .replace_second(second)?;
+    if zone.resolve_earliest(candidate) > from {
+        Ok(zone.resolve_earliest(candidate))
+    } else {
+        Ok(zone.resolve_earliest(advance(candidate)))
+    }
+}
+
+/// Calculates the next fire time for a cron schedule, or None if it never
+/// fires again.
+///
+/// Cron fields are matched against wall-clock time in `zone`. A time
+/// repeated when clocks fall back fires both times round; one skipped when
+/// they spring forward fires as far after the change as it was into the gap.
+pub fn calculate_next_cron_fire(
+    expression: &str,
+    from: OffsetDateTime,
+    zone: Zone,
+) -> Result<Option<OffsetDateTime>> {
+    let cron = CronSchedule::parse(expression)?;
+
+    // Around DST changes, wall-clock order is not instant order: search from
+    // a DST change back, and on until a DST change past the earliest match
+    let slack = match zone {
+        Zone::Utc => time::Duration::ZERO,
+        Zone::Iana(_) => time::Duration::hours(1),
+    };
+    let mut local = zone.to_local(from) - slack;
+    let mut earliest: Option<OffsetDateTime> = None;
+    while let Some(next) = cron.next_after(local) {
+        if earliest.is_some_and(|earliest| next > zone.to_local(earliest) + slack) {
+            break;
         }
-        Period::Yearly => {
-            let candidate = from
-                .replace_hour(hour)?
-                .replace_minute(minute)?
-                .replace_second(second)?;
-            if candidate > from { candidate } else { add_one_year(candidate) }
+        if let Some(instant) = zone.resolve(next).into_iter().find(|t| *t > from) {
+            earliest = Some(earliest.map_or(instant, |earliest| earliest.min(instant)));
         }
-    };
+        local = next;
+    }
+    Ok(earliest)
+}
 
-    Ok(next)
+/// Whether a schedule may fire at `at`, before reaching its `until` or
+/// `max_occurrences`.
+pub fn can_fire_at(schedule: &Schedule, at: OffsetDateTime) -> bool {
+    schedule.until.is_none_or(|until| at <= until)
+        && schedule
+            .max_occurrences
+            .is_none_or(|max| schedule.fire_count < max)
+}
+
+/// Calculates the occurrence of a trigger after `from`, or None for
+/// one-time triggers and cron schedules that never fire again.
+pub fn calculate_next_occurrence(
+    trigger: &TriggerSpec,
+    from: OffsetDateTime,
+    zone: Zone,
+) -> Result<Option<OffsetDateTime>> {
+    match trigger {
+        TriggerSpec::Every { period, at_time } => {
+            Ok(Some(calculate_next_fire(period, at_time, from, zone)?))
+        }
+        TriggerSpec::Cron { expression } => calculate_next_cron_fire(expression, from, zone),
+        TriggerSpec::Once { .. } | TriggerSpec::In { .. } => Ok(None),
+    }
 }
 
 /// Calculates the initial next_fire time for a new schedule.
 pub fn calculate_initial_next_fire(
     trigger: &TriggerSpec,
     now: OffsetDateTime,
+    zone: Zone,
 ) -> Result<OffsetDateTime> {
     match trigger {
         TriggerSpec::Once { at } => Ok(*at),
         TriggerSpec::In { duration_seconds } => {
             Ok(now + time::Duration::seconds(*duration_seconds as i64))
         }
-        TriggerSpec::Every { period, at_time } => calculate_next_fire(period, at_time, now),
-        TriggerSpec::Cron { .. } => {
-            // v2: implement cron parsing
-            Err(anyhow::anyhow!("Cron expressions not yet supported"))
+        TriggerSpec::Every { period, at_time } => calculate_next_fire(period, at_time, now, zone),
+        TriggerSpec::Cron { expression } => calculate_next_cron_fire(expression, now, zone)?
+            .ok_or_else(|| anyhow::anyhow!("Cron expression '{}' never fires", expression)),
+    }
+}
+
+/// Applies an update request to a schedule.
+///
+/// A new trigger recalculates `next_fire` from `now`, and brings a completed
+/// schedule back unless a status is given. Resuming a paused recurring
+/// schedule whose `next_fire` has passed carries on from the first occurrence
+/// after `now`, so occurrences paused over are not fired as misfires; a
+/// one-time schedule has no later occurrence and fires once when resumed.
+/// `defer_until` then moves `next_fire` to a later time; a recurring schedule
+/// carries on from there. Reactivating a completed schedule without a new
+/// trigger carries on from the first occurrence after `now`. Updates that
+/// leave a schedule unable to fire again are rejected, as is setting
+/// `triggered`, which only the scheduler does.
+pub fn apply_update(
+    schedule: &mut Schedule,
+    req: UpdateScheduleRequest,
+    now: OffsetDateTime,
+) -> Result<()> {
+    if req.is_empty() {
+        return Err(anyhow::anyhow!("no update specified"));
+    }
+    if req.status == Some(ScheduleStatus::Triggered) {
+        return Err(anyhow::anyhow!("Status cannot be set to triggered"));
+    }
+    let reactivated = schedule.status == ScheduleStatus::Completed
+        && req.trigger.is_none()
+        && req
+            .status
+            .is_some_and(|status| status != ScheduleStatus::Completed);
+
+    if let Some(name) = req.name {
+        schedule.name = name;
+    }
+    if let Some(payload) = req.payload {
+        schedule.payload = payload;
+    }
+    if let Some(tags) = req.tags {
+        schedule.tags = tags;
+    }
+    if let Some(priority) = req.priority {
+        schedule.priority = priority;
+    }
+    if let Some(misfire_policy) = req.misfire_policy {
+        schedule.misfire_policy = misfire_policy;
+    }
+    if let Some(until) = req.until {
+        schedule.until = Some(until);
+    }
+    if let Some(max_occurrences) = req.max_occurrences {
+        schedule.max_occurrences = Some(max_occurrences);
+    }
+
+    if let Some(trigger) = req.trigger {
+        let zone = Zone::parse(schedule.timezone.as_deref())?;
+        schedule.next_fire = Some(calculate_initial_next_fire(&trigger, now, zone)?);
+        schedule.trigger = trigger;
+        if schedule.status == ScheduleStatus::Completed {
+            schedule.status = ScheduleStatus::Active;
         }
     }
+    if let Some(status) = req.status {
+        if schedule.status == ScheduleStatus::Paused
+            && status == ScheduleStatus::Active
+            && let Some(next_fire) = schedule.next_fire
+            && next_fire <= now
+        {
+            let zone = Zone::parse(schedule.timezone.as_deref())?;
+            if let Some(next) = calculate_next_occurrence(&schedule.trigger, now, zone)? {
+                schedule.next_fire = Some(next);
+            }
+        }
+        schedule.status = status;
+    }
+    if reactivated {
+        let zone = Zone::parse(schedule.timezone.as_deref())?;
+        schedule.next_fire = calculate_next_occurrence(&schedule.trigger, now, zone)?;
+        if schedule.next_fire.is_none() {
+            return Err(anyhow::anyhow!("Schedule has no occurrence left to fire"));
+        }
+    }
+
+    if let Some(defer_until) = req.defer_until {
+        if schedule.status == ScheduleStatus::Completed {
+            return Err(anyhow::anyhow!("Cannot defer a completed schedule"));
+        }
+        schedule.next_fire = Some(parse_defer_until(&defer_until, now)?);
+    }
+
+    if schedule.status != ScheduleStatus::Completed
+        && let Some(next_fire) = schedule.next_fire
+        && !can_fire_at(schedule, next_fire)
+    {
+        return Err(anyhow::anyhow!(
+            "Schedule would not fire again before its until or max_occurrences"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses `defer_until`: an RFC3339 timestamp, or an ISO 8601 duration
+/// (e.g., "PT30M") from `now`. Either must be in the future.
+fn parse_defer_until(s: &str, now: OffsetDateTime) -> Result<OffsetDateTime> {
+    let until = match OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339) {
+        Ok(at) => at,
+        Err(_) => now + parse_iso_duration(s)?,
+    };
+    if until <= now {
+        return Err(anyhow::anyhow!("defer_until must be in the future: {}", s));
+    }
+    Ok(until)
+}
+
+/// Parses an ISO 8601 duration of weeks, days, hours, minutes and seconds
+/// (e.g., "P1DT12H", "PT90M", "P2W"). Years and months are not accepted,
+/// as their length varies.
+fn parse_iso_duration(s: &str) -> Result<time::Duration> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid defer_until: {}. Use RFC3339 or an ISO 8601 duration (e.g., PT30M)",
+            s
+        )
+    };
+
+    let rest = s.strip_prefix('P').ok_or_else(invalid)?;
+    let (date, time_part) = match rest.split_once('T') {
+        Some((date, time_part)) if !time_part.is_empty() => (date, Some(time_part)),
+        Some(_) => return Err(invalid()),
+        None => (rest, None),
+    };
+    if date.is_empty() && time_part.is_none() {
+        return Err(invalid());
+    }
+
+    let mut total = time::Duration::ZERO;
+    for (part, units) in [(date, "WD"), (time_part.unwrap_or(""), "HMS")] {
+        let mut num = String::new();
+        let mut last_unit = None;
+        for c in part.chars() {
+            if c.is_ascii_digit() {
+                num.push(c);
+                continue;
+            }
+            // Units must appear once each, in order
+            let position = units.find(c).ok_or_else(invalid)?;
+            if num.is_empty() || last_unit.is_some_and(|last| position <= last) {
+                return Err(invalid());
+            }
+            let n: i64 = num.parse()?;
+            total += match c {
+                'W' => time::Duration::weeks(n),
+                'D' => time::Duration::days(n),
+                'H' => time::Duration::hours(n),
+                'M' => time::Duration::minutes(n),
+                _ => time::Duration::seconds(n),
+            };
+            num.clear();
+            last_unit = Some(position);
+        }
+        if !num.is_empty() {
+            return Err(invalid());
+        }
+    }
+    Ok(total)
 }
 
 #[cfg(test)]
@@ -621,6 +1022,8 @@ mod store_tests {
             id: "past".into(),
             name: "Past schedule".into(),
             trigger: TriggerSpec::Once { at: datetime!(2025-03-12 08:55 UTC) },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
             payload: serde_json::json!({}),
             tags: vec![],
             priority: Priority::Normal,
@@ -628,11 +1031,16 @@ mod store_tests {
             created_at: datetime!(2025-03-12 08:00 UTC),
             next_fire: Some(datetime!(2025-03-12 08:55 UTC)),
             last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
         };
         let future = Schedule {
             id: "future".into(),
             name: "Future schedule".into(),
             trigger: TriggerSpec::Once { at: datetime!(2025-03-12 09:30 UTC) },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
             payload: serde_json::json!({}),
             tags: vec![],
             priority: Priority::Normal,
@@ -640,6 +1048,9 @@ mod store_tests {
             created_at: datetime!(2025-03-12 08:00 UTC),
             next_fire: Some(datetime!(2025-03-12 09:30 UTC)),
             last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
         };
 
         store.create(&past).await.unwrap();
@@ -663,12 +1074,17 @@ mod store_tests {
             id: "hourly".into(),
             name: "Hourly test".into(),
             trigger: TriggerSpec::Every { period: Period::Hourly, at_time: None },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
             payload: serde_json::json!({}),
             tags: vec![],
             priority: Priority::Normal,
             status: ScheduleStatus::Triggered,
             next_fire: Some(datetime!(2025-03-12 10:00 UTC)), // Already updated
             last_fire: Some(datetime!(2025-03-12 09:00 UTC)),
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
             created_at: datetime!(2025-03-12 08:00 UTC),
         };
         store.create(&schedule).await.unwrap();
@@ -685,4 +1101,329 @@ mod store_tests {
         // next_fire should remain unchanged (already calculated at trigger time)
         assert_eq!(updated.next_fire, Some(datetime!(2025-03-12 10:00 UTC)));
     }
+
+    #[tokio::test]
+    async fn test_ack_does_not_undo_a_pause() {
+        let store = ScheduleStore::new(":memory:").await.unwrap();
+        let schedule = Schedule {
+            id: "hourly".into(),
+            name: "Hourly test".into(),
+            trigger: TriggerSpec::Every { period: Period::Hourly, at_time: None },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
+            payload: serde_json::json!({}),
+            tags: vec![],
+            priority: Priority::Normal,
+            status: ScheduleStatus::Triggered,
+            next_fire: Some(datetime!(2025-03-12 10:00 UTC)),
+            last_fire: Some(datetime!(2025-03-12 09:00 UTC)),
+            until: None,
+            max_occurrences: None,
+            fire_count: 1,
+            created_at: datetime!(2025-03-12 08:00 UTC),
+        };
+        store.create(&schedule).await.unwrap();
+
+        // Paused between the ack's read and its write
+        let read = store.get("hourly").await.unwrap().unwrap();
+        store
+            .update_status("hourly", ScheduleStatus::Paused)
+            .await
+            .unwrap();
+        let next = Some(datetime!(2025-03-12 10:00 UTC));
+        assert!(!store.finish_triggered(&read, next).await.unwrap());
+        assert!(!store.finish_triggered(&read, None).await.unwrap());
+        let stored = store.get("hourly").await.unwrap().unwrap();
+        assert_eq!(stored.status, ScheduleStatus::Paused);
+
+        // Deferred by an edit while triggered
+        store
+            .update_status("hourly", ScheduleStatus::Triggered)
+            .await
+            .unwrap();
+        let mut deferred = read.clone();
+        deferred.next_fire = Some(datetime!(2025-03-12 15:00 UTC));
+        assert!(store.update(&deferred, &read).await.unwrap());
+        assert!(!store.finish_triggered(&read, next).await.unwrap());
+        let stored = store.get("hourly").await.unwrap().unwrap();
+        assert_eq!(stored.status, ScheduleStatus::Triggered);
+        assert_eq!(stored.next_fire, Some(datetime!(2025-03-12 15:00 UTC)));
+
+        // Unchanged since the read, the ack goes through
+        assert!(
+            store
+                .finish_triggered(&stored, stored.next_fire)
+                .await
+                .unwrap()
+        );
+        let stored = store.get("hourly").await.unwrap().unwrap();
+        assert_eq!(stored.status, ScheduleStatus::Active);
+        assert_eq!(stored.next_fire, Some(datetime!(2025-03-12 15:00 UTC)));
+    }
+
+    // === Updates ===
+
+    #[test]
+    fn test_parse_defer_until() {
+        let now = datetime!(2025-03-12 09:00 UTC);
+        assert_eq!(
+            parse_defer_until("PT30M", now).unwrap(),
+            datetime!(2025-03-12 09:30 UTC)
+        );
+        assert_eq!(
+            parse_defer_until("P1DT2H3S", now).unwrap(),
+            datetime!(2025-03-13 11:00:03 UTC)
+        );
+        assert_eq!(
+            parse_defer_until("P2W", now).unwrap(),
+            datetime!(2025-03-26 09:00 UTC)
+        );
+        assert_eq!(
+            parse_defer_until("2025-03-12T12:00:00+02:00", now).unwrap(),
+            datetime!(2025-03-12 10:00 UTC)
+        );
+
+        // Not in the future
+        assert!(parse_defer_until("PT0S", now).is_err());
+        assert!(parse_defer_until("2025-03-12T08:00:00Z", now).is_err());
+        // Malformed, or of varying length
+        for invalid in ["", "P", "PT", "30M", "PT30", "PTM", "PT5M1H", "P1M", "P1Y", "P1DT"] {
+            assert!(parse_defer_until(invalid, now).is_err(), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_update() {
+        let store = ScheduleStore::new(":memory:").await.unwrap();
+        let now = datetime!(2025-03-12 09:00 UTC);
+        let mut schedule = Schedule {
+            id: "reminder".into(),
+            name: "Reminder".into(),
+            trigger: TriggerSpec::Once { at: datetime!(2025-03-12 08:00 UTC) },
+            timezone: Some("Europe/Berlin".into()),
+            misfire_policy: MisfirePolicy::FireOnce,
+            payload: serde_json::json!({}),
+            tags: vec![],
+            priority: Priority::Normal,
+            status: ScheduleStatus::Completed,
+            created_at: datetime!(2025-03-12 07:00 UTC),
+            next_fire: None,
+            last_fire: Some(datetime!(2025-03-12 08:00 UTC)),
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
+        };
+        store.create(&schedule).await.unwrap();
+
+        // Nothing to update, or an invalid one, is rejected
+        assert!(apply_update(&mut schedule, UpdateScheduleRequest::default(), now).is_err());
+        let cron = TriggerSpec::Cron { expression: "0 0 30 2 *".into() };
+        let req = UpdateScheduleRequest { trigger: Some(cron), ..Default::default() };
+        assert!(apply_update(&mut schedule, req, now).is_err());
+        // Completed schedules cannot be deferred
+        let req = UpdateScheduleRequest { defer_until: Some("PT1H".into()), ..Default::default() };
+        assert!(apply_update(&mut schedule, req, now).is_err());
+
+        // A new trigger brings the schedule back, at its time in the schedule's zone
+        let daily = TriggerSpec::Every { period: Period::Daily, at_time: Some("09:00".into()) };
+        let req = UpdateScheduleRequest {
+            name: Some("Daily reminder".into()),
+            trigger: Some(daily.clone()),
+            tags: Some(vec!["daily".into()]),
+            priority: Some(Priority::High),
+            max_occurrences: Some(7),
+            ..Default::default()
+        };
+        apply_update(&mut schedule, req, now).unwrap();
+        assert_eq!(schedule.status, ScheduleStatus::Active);
+        assert_eq!(schedule.next_fire, Some(datetime!(2025-03-13 08:00 UTC)));
+
+        // Ending the schedule before its next fire is rejected
+        let req = UpdateScheduleRequest {
+            until: Some(datetime!(2025-03-12 12:00 UTC)),
+            ..Default::default()
+        };
+        assert!(apply_update(&mut schedule.clone(), req, now).is_err());
+        let req = UpdateScheduleRequest { max_occurrences: Some(0), ..Default::default() };
+        assert!(apply_update(&mut schedule.clone(), req, now).is_err());
+
+        // Deferring moves just the next fire
+        let req = UpdateScheduleRequest {
+            defer_until: Some("2025-03-13T12:00:00Z".into()),
+            ..Default::default()
+        };
+        apply_update(&mut schedule, req, now).unwrap();
+        assert_eq!(schedule.next_fire, Some(datetime!(2025-03-13 12:00 UTC)));
+
+        let read = store.get("reminder").await.unwrap().unwrap();
+        assert!(store.update(&schedule, &read).await.unwrap());
+        let updated = store.get("reminder").await.unwrap().unwrap();
+        assert_eq!(updated.name, "Daily reminder");
+        assert_eq!(updated.trigger, daily);
+        assert_eq!(updated.tags, vec!["daily"]);
+        assert_eq!(updated.priority, Priority::High);
+        assert_eq!(updated.max_occurrences, Some(7));
+        assert_eq!(updated.status, ScheduleStatus::Active);
+        assert_eq!(updated.next_fire, Some(datetime!(2025-03-13 12:00 UTC)));
+        assert_eq!(updated.last_fire, Some(datetime!(2025-03-12 08:00 UTC)));
+    }
+
+    #[test]
+    fn test_apply_update_resume_skips_paused_occurrences() {
+        let now = datetime!(2025-03-12 10:00 UTC);
+        let mut schedule = Schedule {
+            id: "standup".into(),
+            name: "Standup".into(),
+            trigger: TriggerSpec::Every { period: Period::Daily, at_time: Some("09:00".into()) },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireAll { max: 10 },
+            payload: serde_json::json!({}),
+            tags: vec![],
+            priority: Priority::Normal,
+            status: ScheduleStatus::Paused,
+            created_at: datetime!(2025-03-01 07:00 UTC),
+            next_fire: Some(datetime!(2025-03-09 09:00 UTC)),
+            last_fire: Some(datetime!(2025-03-08 09:00 UTC)),
+            until: None,
+            max_occurrences: None,
+            fire_count: 7,
+        };
+        let resume =
+            || UpdateScheduleRequest { status: Some(ScheduleStatus::Active), ..Default::default() };
+
+        // Occurrences due while paused are skipped, not replayed
+        let mut recurring = schedule.clone();
+        apply_update(&mut recurring, resume(), now).unwrap();
+        assert_eq!(recurring.status, ScheduleStatus::Active);
+        assert_eq!(recurring.next_fire, Some(datetime!(2025-03-13 09:00 UTC)));
+
+        // A next fire still ahead, e.g. a deferral, is kept
+        let mut deferred = schedule.clone();
+        deferred.next_fire = Some(datetime!(2025-03-20 12:00 UTC));
+        apply_update(&mut deferred, resume(), now).unwrap();
+        assert_eq!(deferred.next_fire, Some(datetime!(2025-03-20 12:00 UTC)));
+
+        // A one-time schedule has no later occurrence and still fires once
+        schedule.trigger = TriggerSpec::Once { at: datetime!(2025-03-09 09:00 UTC) };
+        apply_update(&mut schedule, resume(), now).unwrap();
+        assert_eq!(schedule.status, ScheduleStatus::Active);
+        assert_eq!(schedule.next_fire, Some(datetime!(2025-03-09 09:00 UTC)));
+    }
+
+    #[test]
+    fn test_apply_update_reactivates_only_schedules_that_can_fire() {
+        let now = datetime!(2025-03-12 10:00 UTC);
+        let schedule = Schedule {
+            id: "standup".into(),
+            name: "Standup".into(),
+            trigger: TriggerSpec::Every { period: Period::Daily, at_time: Some("09:00".into()) },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
+            payload: serde_json::json!({}),
+            tags: vec![],
+            priority: Priority::Normal,
+            status: ScheduleStatus::Completed,
+            created_at: datetime!(2025-03-01 07:00 UTC),
+            next_fire: None,
+            last_fire: Some(datetime!(2025-03-11 09:00 UTC)),
+            until: None,
+            max_occurrences: None,
+            fire_count: 2,
+        };
+        let status = |status| UpdateScheduleRequest { status: Some(status), ..Default::default() };
+
+        // Only the scheduler triggers a schedule
+        let mut active = schedule.clone();
+        active.status = ScheduleStatus::Active;
+        active.next_fire = Some(datetime!(2025-03-13 09:00 UTC));
+        assert!(apply_update(&mut active, status(ScheduleStatus::Triggered), now).is_err());
+
+        // Completed by max_occurrences: reactivating alone is rejected
+        let mut by_count = schedule.clone();
+        by_count.max_occurrences = Some(2);
+        assert!(apply_update(&mut by_count.clone(), status(ScheduleStatus::Active), now).is_err());
+        assert!(apply_update(&mut by_count.clone(), status(ScheduleStatus::Paused), now).is_err());
+        // With room for more, it carries on from now
+        let req = UpdateScheduleRequest {
+            status: Some(ScheduleStatus::Active),
+            max_occurrences: Some(3),
+            ..Default::default()
+        };
+        apply_update(&mut by_count, req, now).unwrap();
+        assert_eq!(by_count.status, ScheduleStatus::Active);
+        assert_eq!(by_count.next_fire, Some(datetime!(2025-03-13 09:00 UTC)));
+
+        // Completed by until: likewise
+        let mut by_until = schedule.clone();
+        by_until.until = Some(datetime!(2025-03-11 12:00 UTC));
+        assert!(apply_update(&mut by_until.clone(), status(ScheduleStatus::Active), now).is_err());
+        let req = UpdateScheduleRequest {
+            status: Some(ScheduleStatus::Active),
+            until: Some(datetime!(2025-03-20 12:00 UTC)),
+            ..Default::default()
+        };
+        apply_update(&mut by_until, req, now).unwrap();
+        assert_eq!(by_until.status, ScheduleStatus::Active);
+        assert_eq!(by_until.next_fire, Some(datetime!(2025-03-13 09:00 UTC)));
+
+        // A completed one-time schedule has nothing left to fire
+        let mut once = schedule.clone();
+        once.trigger = TriggerSpec::Once { at: datetime!(2025-03-11 09:00 UTC) };
+        assert!(apply_update(&mut once, status(ScheduleStatus::Active), now).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_does_not_undo_a_fire() {
+        let store = ScheduleStore::new(":memory:").await.unwrap();
+        let now = datetime!(2025-03-12 09:00 UTC);
+        let schedule = Schedule {
+            id: "standup".into(),
+            name: "Standup".into(),
+            trigger: TriggerSpec::Every { period: Period::Daily, at_time: Some("09:00".into()) },
+            timezone: None,
+            misfire_policy: MisfirePolicy::FireOnce,
+            payload: serde_json::json!({}),
+            tags: vec![],
+            priority: Priority::Normal,
+            status: ScheduleStatus::Active,
+            created_at: datetime!(2025-03-11 07:00 UTC),
+            next_fire: Some(now),
+            last_fire: None,
+            until: None,
+            max_occurrences: None,
+            fire_count: 0,
+        };
+        store.create(&schedule).await.unwrap();
+
+        // The scheduler fires the schedule between the edit's read and write
+        let read = store.get("standup").await.unwrap().unwrap();
+        let next = datetime!(2025-03-13 09:00 UTC);
+        store
+            .mark_triggered("standup", now, now, 0, Some(next))
+            .await
+            .unwrap();
+
+        let mut edited = read.clone();
+        let req =
+            UpdateScheduleRequest { name: Some("Daily standup".into()), ..Default::default() };
+        apply_update(&mut edited, req, now).unwrap();
+        assert!(!store.update(&edited, &read).await.unwrap());
+
+        let stored = store.get("standup").await.unwrap().unwrap();
+        assert_eq!(stored.name, "Standup");
+        assert_eq!(stored.status, ScheduleStatus::Triggered);
+        assert_eq!(stored.next_fire, Some(next));
+        assert_eq!(stored.fire_count, 1);
+
+        // Editing what was stored after the fire goes through
+        let mut edited = stored.clone();
+        let req =
+            UpdateScheduleRequest { name: Some("Daily standup".into()), ..Default::default() };
+        apply_update(&mut edited, req, now).unwrap();
+        assert!(store.update(&edited, &stored).await.unwrap());
+        let stored = store.get("standup").await.unwrap().unwrap();
+        assert_eq!(stored.name, "Daily standup");
+        assert_eq!(stored.status, ScheduleStatus::Triggered);
+        assert_eq!(stored.next_fire, Some(next));
+    }
 }
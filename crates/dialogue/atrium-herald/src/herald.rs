@@ -0,0 +1,84 @@
+//! Herald pushing unread atrium messages as `chat.message` events.
+
+use agora_herald::{EventBuilder, EventPayload, Herald};
+use async_trait::async_trait;
+use atrium_client::{AuthenticatedClient, Message};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use tracing::{debug, info};
+
+/// Payload of a `chat.message` event.
+#[derive(Debug, Serialize)]
+pub struct ChatMessage<'a> {
+    pub id: i32,
+    pub content: &'a str,
+    pub sender: &'a str,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl EventPayload for ChatMessage<'_> {
+    const EVENT_TYPE: &'static str = "chat.message";
+}
+
+pub struct AtriumHerald {
+    atrium_client: AuthenticatedClient,
+}
+
+impl AtriumHerald {
+    pub fn new(atrium_client: AuthenticatedClient) -> Self {
+        Self { atrium_client }
+    }
+}
+
+#[async_trait]
+impl Herald for AtriumHerald {
+    const ID: &'static str = "atrium-herald";
+    const DESCRIPTION: &'static str = "Atrium Herald - produces chat.message events";
+
+    type Item = Message;
+
+    async fn poll(&mut self) -> anyhow::Result<Vec<Message>> {
+        let unread = self.atrium_client.get_unread_messages(Some(100)).await?;
+
+        if unread.messages.is_empty() {
+            debug!("No new messages");
+        } else {
+            info!("Found {} new messages", unread.messages.len());
+        }
+
+        Ok(unread.messages)
+    }
+
+    fn produce(&self, msg: &Message) -> anyhow::Result<EventBuilder> {
+        let payload = ChatMessage {
+            id: msg.id,
+            content: &msg.content,
+            sender: &msg.sender,
+            created_at: msg.created_at,
+        };
+
+        Ok(EventBuilder::typed(&payload)?
+            .timestamp(msg.created_at)
+            // Message ids are stable, so a re-pushed message is deduplicated by Agora
+            .idempotency_key(msg.id.to_string())
+            // Lets Agora merge a burst from one sender, if configured to
+            .coalesce_key(&msg.sender))
+    }
+
+    fn schemas(&self) -> HashMap<String, serde_json::Value> {
+        let schema = json!({
+            "type": "object",
+            "required": ["id", "content", "sender", "created_at"],
+            "properties": {
+                "id": { "type": "integer" },
+                "content": { "type": "string" },
+                "sender": { "type": "string" },
+                "created_at": { "type": "string", "format": "date-time" },
+            },
+        });
+        HashMap::from([(ChatMessage::EVENT_TYPE.to_string(), schema)])
+    }
+}
@@ -3,17 +3,20 @@
 //! This herald produces chat.message events from atrium.
 
 mod config;
-mod poller;
+mod herald;
 
+use agora_herald::{AgoraClient, HeraldRunner, shutdown_signal};
+use atrium_client::AuthenticatedClient;
 use clap::Parser;
 use reqwest::Client;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
-use tracing::{error, info, warn};
+use tracing::{debug, info, warn};
 use tracing_subscriber::prelude::*;
 
 use crate::config::Config;
-use crate::poller::Poller;
+use crate::herald::AtriumHerald;
 
 /// Atrium herald configuration.
 #[derive(Parser)]
@@ -39,7 +42,7 @@ async fn main() -> anyhow::Result<()> {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "atrium_herald=info".into()),
+                .unwrap_or_else(|_| "atrium_herald=info,agora_herald=info".into()),
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
@@ -69,7 +72,7 @@ async fn main() -> anyhow::Result<()> {
     let mut attempt = 0u32;
     let mut delay = Duration::from_secs(1);
     let atrium_client = loop {
-        match atrium_client::AuthenticatedClient::connect_and_login_or_register(
+        match AuthenticatedClient::connect_and_login_or_register(
             &config.atrium_url,
             config.username.clone(),
             config.password.clone(),
@@ -94,22 +97,30 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    // Create poller
-    let mut poller = Poller::new(
-        atrium_client,
-        "atrium-herald",
-        &config.agora_url,
-        Duration::from_millis(config.poll_interval_ms),
-        Duration::from_millis(config.heartbeat_interval_ms),
+    // Keep the herald's atrium user online
+    tokio::spawn(atrium_heartbeat(
+        atrium_client.clone(),
         Duration::from_millis(config.atrium_heartbeat_interval_ms),
-        http_client,
-    );
+    ));
 
-    // Run the poller
-    if let Err(e) = poller.run().await {
-        error!("Atrium herald failed: {}", e);
-        return Err(e);
-    }
+    let agora_client = AgoraClient::new(&config.agora_url, http_client)
+        .with_herald_token(config.agora_token.clone());
 
-    Ok(())
+    HeraldRunner::new(AtriumHerald::new(atrium_client), Arc::new(agora_client))
+        .with_poll_interval(Duration::from_millis(config.poll_interval_ms))
+        .with_heartbeat_interval(Duration::from_millis(config.heartbeat_interval_ms))
+        .run(shutdown_signal())
+        .await
+}
+
+async fn atrium_heartbeat(atrium_client: AuthenticatedClient, period: Duration) {
+    let mut tick = tokio::time::interval(period);
+    loop {
+        tick.tick().await;
+        debug!("Sending heartbeat to Atrium");
+        match atrium_client.send_heartbeat().await {
+            Ok(_) => debug!("Atrium heartbeat sent successfully"),
+            Err(e) => warn!("Atrium heartbeat failed: {}", e),
+        }
+    }
 }
@@ -10,13 +10,14 @@ use crate::tools::{
     ContextEvict, MemoryGet, MemoryPin, MemoryRecent, MemoryTimeline, MemoryUnpin, StateTransition,
     ToolDispatch,
 };
-use agora_client::{AgoraClient, AgoraClientTrait};
+use agora_client::{AgoraClient, AgoraClientTrait, BatchAckMode, Event, EventSubscription};
 use anyhow::Context;
 use llm::builder::{LLMBackend, LLMBuilder};
 use llm::chat::ChatMessage;
 use llm::{FunctionCall, LLMProvider, ToolCall};
 use loom_client::LoomClientTrait;
 use loom_client::memory::{MemoryFragment, MemoryKind};
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
@@ -32,6 +33,15 @@ fn preserve_raw_json(s: &str) -> serde_json::Value {
     serde_json::from_str(s).unwrap_or(serde_json::json!(s))
 }
 
+/// Drop repeated deliveries of the same event, keeping the first.
+///
+/// An event streamed while Dormant can be redelivered by a later fetch
+/// before its ack lands; it must enter the context only once.
+fn dedup_events(events: Vec<Event>) -> Vec<Event> {
+    let mut seen = HashSet::new();
+    events.into_iter().filter(|e| seen.insert(e.id)).collect()
+}
+
 /// Estimate the static token overhead from system prompt and tool definitions.
 fn compute_static_overhead(system_prompt: &str, tool_definitions: &[llm::chat::Tool]) -> usize {
     let json = serde_json::json!({
@@ -48,6 +58,10 @@ pub struct EphemeraAI {
     tool_definitions: Vec<llm::chat::Tool>,
     context: Arc<Mutex<EphemeraContext>>,
     agora_client: Arc<dyn AgoraClientTrait>,
+    /// Events received while Dormant, handled on the next cognitive cycle.
+    received_events: Vec<Event>,
+    /// Agora event stream, held open for as long as the AI stays Dormant.
+    subscription: Option<EventSubscription>,
     config: crate::config::Config,
     /// True when no prior memories existed in loom at startup (first ever run).
     is_first_awakening: bool,
@@ -202,6 +216,8 @@ impl EphemeraAI {
             tool_definitions,
             context: context_data,
             agora_client,
+            received_events: Vec::new(),
+            subscription: None,
             config,
             is_first_awakening,
         })
@@ -259,11 +275,11 @@ impl EphemeraAI {
             match state {
                 State::Active => {
                     // Full speed - no delay
+                    self.subscription = None;
                     self.cognitive_cycle().await?;
                 }
                 State::Dormant => {
-                    tokio::time::sleep(Duration::from_millis(self.config.dormant_tick_interval_ms))
-                        .await;
+                    self.wait_dormant_tick().await;
                     self.cognitive_cycle().await?;
                 }
                 State::Suspended => {
@@ -284,9 +300,66 @@ impl EphemeraAI {
         self.context.lock().await.add_activity(fragment);
     }
 
+    /// Wait for the dormant tick, waking early when Agora pushes an event.
+    ///
+    /// One event stream is kept open across the ticks of a Dormant phase, so
+    /// no stream is left behind to claim events nobody reads. It is closed
+    /// once the AI turns Active, so unacked events redelivered during a long
+    /// Active phase cannot pile up in it. Falls back to long-polling while
+    /// the stream is unavailable.
+    async fn wait_dormant_tick(&mut self) {
+        let tick_ms = self.config.dormant_tick_interval_ms;
+        let tick = Duration::from_millis(tick_ms);
+
+        let subscription = match self.subscription.take() {
+            Some(subscription) => Ok(subscription),
+            None => self.agora_client.subscribe(None).await,
+        };
+        let mut subscription = match subscription {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                warn!(
+                    "Failed to subscribe to Agora events, long-polling instead: {}",
+                    e
+                );
+                match self.agora_client.fetch_events_wait(None, tick_ms).await {
+                    Ok(events) => self.received_events.extend(events),
+                    Err(e) => {
+                        warn!("Agora long-poll failed: {}", e);
+                        tokio::time::sleep(tick).await;
+                    }
+                }
+                return;
+            }
+        };
+
+        let received = tokio::select! {
+            _ = tokio::time::sleep(tick) => {
+                self.subscription = Some(subscription);
+                return;
+            }
+            received = subscription.next() => received,
+        };
+        match received {
+            Some(Ok(event)) => {
+                info!("Woken by Agora event {} ({})", event.id, event.event_type);
+                self.received_events.push(event);
+                // Keep events that arrived in the same batch
+                while let Some(Ok(event)) = subscription.try_next() {
+                    self.received_events.push(event);
+                }
+                self.subscription = Some(subscription);
+            }
+            Some(Err(e)) => warn!("Agora event stream failed: {}", e),
+            None => warn!("Agora event stream closed"),
+        }
+    }
+
     async fn cognitive_cycle(&mut self) -> anyhow::Result<()> {
-        // 1. Fetch events from Agora (POST /events/fetch)
-        let events = self.agora_client.fetch_events(None).await?;
+        // 1. Collect received events, then fetch the rest from Agora (POST /events/fetch)
+        let mut events = std::mem::take(&mut self.received_events);
+        events.extend(self.agora_client.fetch_events(None).await?);
+        let events = dedup_events(events);
 
         if !events.is_empty() {
             // Collect event IDs for acknowledgment
@@ -296,7 +369,16 @@ impl EphemeraAI {
             self.context.lock().await.add_agora_events(events);
 
             // Acknowledge processed events
-            self.agora_client.ack_events(event_ids).await?;
+            let acked = self
+                .agora_client
+                .ack_events(event_ids, BatchAckMode::BestEffort)
+                .await?;
+            if !acked.missing_ids.is_empty() || !acked.failed_ids.is_empty() {
+                warn!(
+                    "Failed to ack events (missing: {:?}, failed: {:?})",
+                    acked.missing_ids, acked.failed_ids
+                );
+            }
         }
 
         // 2. Build chat_history from memory (replaces context.serialize())
@@ -554,6 +636,33 @@ mod tests {
         assert!(deserialized.tool_calls[2].result.contains("Skipped"));
     }
 
+    #[test]
+    fn dedup_events_keeps_first_delivery() {
+        use agora_common::event::{EventPriority, EventStatus};
+
+        let event = |id: u64, text: &str| Event {
+            id,
+            event_type: "chat.message".to_string(),
+            herald_id: "herald_user".to_string(),
+            payload: serde_json::json!({"text": text}),
+            timestamp: time::OffsetDateTime::now_utc(),
+            priority: EventPriority::Normal,
+            status: EventStatus::Delivered,
+            deliver_after: None,
+            expires_at: None,
+        };
+
+        let events = dedup_events(vec![
+            event(1, "streamed"),
+            event(2, "fetched"),
+            event(1, "redelivered"),
+        ]);
+
+        let ids: Vec<u64> = events.iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(events[0].payload["text"], "streamed");
+    }
+
     // ---------------------------------------------------------------------------
     // Visual render test for context serialization output.
     // Intentionally has no assertions — run with `--ignored` to inspect
@@ -628,6 +737,8 @@ mod tests {
             timestamp: time::OffsetDateTime::now_utc() - time::Duration::hours(48),
             priority: EventPriority::Normal,
             status: EventStatus::Acked,
+            deliver_after: None,
+            expires_at: None,
         };
         let pinned_event = loom_client::PinnedMemory {
             fragment: make_fragment(
@@ -704,6 +815,8 @@ mod tests {
             timestamp: time::OffsetDateTime::now_utc() - time::Duration::minutes(10),
             priority: EventPriority::Normal,
             status: EventStatus::Acked,
+            deliver_after: None,
+            expires_at: None,
         };
         ctx.add_activity(make_fragment(
             200,
@@ -790,6 +903,8 @@ mod tests {
             timestamp: time::OffsetDateTime::now_utc() - time::Duration::minutes(5),
             priority: EventPriority::Low,
             status: EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
         };
         ctx.add_activity(make_fragment(
             205,
@@ -825,6 +940,8 @@ mod tests {
                         timestamp: time::OffsetDateTime::now_utc() - time::Duration::hours(72),
                         priority: EventPriority::High,
                         status: EventStatus::Delivered,
+                        deliver_after: None,
+                        expires_at: None,
                     })
                     .unwrap(),
                 })
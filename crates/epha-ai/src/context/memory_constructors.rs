@@ -54,6 +54,8 @@ pub fn lifecycle_startup_event(is_first_awakening: bool) -> loom_client::memory:
         timestamp: time::OffsetDateTime::now_utc(),
         priority: EventPriority::Normal,
         status: EventStatus::Pending,
+        deliver_after: None,
+        expires_at: None,
     };
 
     from_agora_event(event)
@@ -73,6 +75,8 @@ mod tests {
             timestamp: time::OffsetDateTime::now_utc(),
             priority: agora_common::event::EventPriority::Normal,
             status: agora_common::event::EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
         };
         let fragment = from_agora_event(event);
         assert_eq!(fragment.kind, MemoryKind::Event);
@@ -338,6 +338,8 @@ mod tests {
             timestamp: time::OffsetDateTime::now_utc(),
             priority: agora_common::event::EventPriority::Normal,
             status: agora_common::event::EventStatus::Pending,
+            deliver_after: None,
+            expires_at: None,
         };
         let text = serde_json::to_string(&event).unwrap();
         let content = EventContent { text };
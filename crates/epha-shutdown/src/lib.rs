@@ -0,0 +1,78 @@
+//! Graceful shutdown shared by the Ephemera AI services and heralds.
+
+use std::time::Duration;
+
+use axum::Router;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Completes on Ctrl-C or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl-C"),
+        _ = terminate => info!("Received SIGTERM"),
+    }
+}
+
+/// Serves `app` until a shutdown signal arrives or `shutdown` is cancelled.
+///
+/// On a signal, `shutdown` is cancelled so long-lived handlers can finish, and
+/// in-flight requests get `drain_timeout` to complete before they are dropped.
+/// `shutdown` is always cancelled by the time this returns, so background tasks
+/// watching it can be awaited afterwards.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    shutdown: CancellationToken,
+    drain_timeout: Duration,
+) -> anyhow::Result<()> {
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+        .into_future();
+    tokio::pin!(server);
+
+    let result = tokio::select! {
+        result = &mut server => result,
+        _ = shutdown_signal() => {
+            info!("Shutting down; draining in-flight requests");
+            shutdown.cancel();
+            match tokio::time::timeout(drain_timeout, &mut server).await {
+                Ok(result) => result,
+                Err(_) => {
+                    warn!(
+                        "In-flight requests did not finish within {:?}; dropping them",
+                        drain_timeout
+                    );
+                    Ok(())
+                }
+            }
+        }
+    };
+
+    shutdown.cancel();
+    result.map_err(|e| anyhow::anyhow!("Server error: {}", e))
+}
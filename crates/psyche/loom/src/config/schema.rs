@@ -14,6 +14,13 @@ pub struct MySqlConfig {
 pub struct Config {
     pub mysql: MySqlConfig,
     pub port: u16,
+    /// How long shutdown waits for in-flight requests to finish (milliseconds)
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout_ms: u64,
+}
+
+fn default_shutdown_timeout() -> u64 {
+    30_000
 }
 
 impl Config {
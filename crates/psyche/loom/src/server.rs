@@ -4,7 +4,8 @@ use sea_orm_migration::MigratorTrait;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
-use tracing::{info, warn};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::config::Config;
@@ -40,7 +41,10 @@ impl LoomServer {
         Ok(Self { config, memory_manager })
     }
 
-    /// Start the server
+    /// Run the server until SIGTERM or Ctrl-C
+    ///
+    /// On shutdown, Loom stops accepting connections and gives in-flight
+    /// requests up to `shutdown_timeout_ms` to finish, then closes the MySQL pool.
     pub async fn run(self) -> anyhow::Result<()> {
         use axum::routing::{delete, get, post};
         use tower_http::{
@@ -90,9 +94,14 @@ impl LoomServer {
             .await
             .map_err(|e| anyhow::anyhow!("Failed to bind to address: {}", e))?;
 
-        axum::serve(listener, app)
-            .await
-            .map_err(|e| anyhow::anyhow!("Server error: {}", e))?;
+        let shutdown = CancellationToken::new();
+        let drain_timeout = Duration::from_millis(self.config.shutdown_timeout_ms);
+        epha_shutdown::serve(listener, app, shutdown, drain_timeout).await?;
+
+        if let Err(e) = self.memory_manager.close().await {
+            error!("Failed to close database connection: {}", e);
+        }
+        info!("Loom stopped");
 
         Ok(())
     }
@@ -43,6 +43,11 @@ impl MemoryManager {
         Self { db }
     }
 
+    /// Close the database connection pool, on shutdown
+    pub async fn close(&self) -> Result<(), DbErr> {
+        self.db.close_by_ref().await
+    }
+
     /// Append memory fragments to the store
     pub async fn append(&self, fragments: &mut [MemoryFragment]) -> Result<Vec<i64>, MemoryError> {
         if fragments.is_empty() {
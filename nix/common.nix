@@ -69,6 +69,7 @@ let
     epha-ai = "crates/epha-ai";
     epha-ctl = "crates/epha-ctl";
     agora = "crates/agora";
+    agora-cli = "crates/agora-cli";
     kairos = "crates/chronikos/kairos";
     kairos-cli = "crates/chronikos/kairos-cli";
     kairos-herald = "crates/chronikos/kairos-herald";
@@ -80,8 +81,10 @@ let
 
   # Library-only crates (only needed for fileset dependencies, not built separately)
   libraryCratePaths = mapToAbsolute {
+    epha-shutdown = "crates/epha-shutdown";
     agora-common = "crates/agora-common";
     agora-client = "crates/agora-client";
+    agora-herald = "crates/agora-herald";
     kairos-common = "crates/chronikos/kairos-common";
     kairos-client = "crates/chronikos/kairos-client";
     loom-common = "crates/psyche/loom-common";
@@ -20,6 +20,12 @@ in
       description = "The agora package to use";
     };
 
+    cliPackage = lib.mkOption {
+      type = lib.types.package;
+      default = ephaPkgs.agora-cli;
+      description = "The agora-cli package to use";
+    };
+
     log_level = lib.mkOption {
       type = lib.types.str;
       default = "info";
@@ -37,10 +43,23 @@ in
         description = "Port for agora service";
       };
 
+      storage = lib.mkOption {
+        type = lib.types.enum [
+          "sqlite"
+          "memory"
+        ];
+        default = "sqlite";
+        description = ''
+          Where events, consumers and heralds are stored. "memory" keeps
+          everything in process memory and loses it on restart; meant for
+          tests and ephemeral deployments.
+        '';
+      };
+
       database_path = lib.mkOption {
         type = lib.types.str;
         description = ''
-          Path to SQLite database file.
+          Path to SQLite database file (unused with in-memory storage).
 
           For user services, prefer XDG data directory:
             `${"\${config.xdg.dataHome}"}/ephemera/agora.db`
@@ -58,6 +77,211 @@ in
         description = "Milliseconds before marking herald as Disconnected";
       };
 
+      shutdown_timeout_ms = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 30000;
+        description = "How long shutdown waits for in-flight requests to finish (ms)";
+      };
+
+      idempotency_window_ms = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 86400000;
+        description = "How long an event's idempotency key is remembered, so re-pushes are deduplicated (ms)";
+      };
+
+      on_expire = lib.mkOption {
+        type = lib.types.enum [
+          "drop"
+          "dead_letter"
+        ];
+        default = "drop";
+        description = "What happens to events that pass their expires_at before being acked";
+      };
+
+      coalesce = lib.mkOption {
+        type = lib.types.attrsOf (
+          lib.types.submodule {
+            options.max_events = lib.mkOption {
+              type = lib.types.ints.positive;
+              default = 100;
+              description = "Most events merged into one; later ones start a new event";
+            };
+          }
+        );
+        default = { };
+        description = ''
+          Coalescing rules keyed by event type. Undelivered events of these
+          types that share a coalesce_key are merged into one event; once a
+          second event merges, its payload becomes an array of the payloads.
+        '';
+      };
+
+      archive = {
+        enabled = lib.mkOption {
+          type = lib.types.bool;
+          default = false;
+          description = "Keep acked events in an archive, queryable via GET /events/history";
+        };
+
+        retention_ms = lib.mkOption {
+          type = lib.types.ints.positive;
+          default = 604800000;
+          description = "How long archived events are kept (ms)";
+        };
+      };
+
+      herald_status_events = {
+        enabled = lib.mkOption {
+          type = lib.types.bool;
+          default = true;
+          description = "Push system.herald.disconnected / system.herald.reconnected events";
+        };
+
+        flap_suppression_ms = lib.mkOption {
+          type = lib.types.ints.unsigned;
+          default = 300000;
+          description = ''
+            A herald's disconnect is not reported again within this long (ms)
+            of its last reported one, unless it stays disconnected past it
+          '';
+        };
+      };
+
+      herald_limits = lib.mkOption {
+        type = lib.types.submodule {
+          options = {
+            events_per_minute = lib.mkOption {
+              type = lib.types.nullOr lib.types.ints.positive;
+              default = null;
+              description = "Most events accepted per minute (null for no limit)";
+            };
+
+            max_pending = lib.mkOption {
+              type = lib.types.nullOr lib.types.ints.positive;
+              default = null;
+              description = "Most of the herald's events queued at once (null for no limit)";
+            };
+
+            max_payload_bytes = lib.mkOption {
+              type = lib.types.nullOr lib.types.ints.positive;
+              default = null;
+              description = "Largest payload accepted, in bytes of JSON (null for no limit)";
+            };
+
+            on_exceed = lib.mkOption {
+              type = lib.types.enum [
+                "reject"
+                "drop_oldest"
+              ];
+              default = "reject";
+              description = ''
+                What happens to a push beyond max_pending: rejected with 429, or
+                the herald's lowest-priority, oldest undelivered event is dropped
+              '';
+            };
+          };
+        };
+        default = { };
+        description = "Limits on the pushes of heralds without limits of their own";
+      };
+
+      heralds = lib.mkOption {
+        type = lib.types.attrsOf (
+          lib.types.submodule {
+            options.token = lib.mkOption {
+              type = lib.types.nullOr lib.types.str;
+              default = null;
+              description = ''
+                Preconfigured bearer token for this herald. Without one, Agora
+                issues a token when the herald first registers. It ends up in
+                the world-readable Nix store; prefer token_file.
+              '';
+            };
+
+            options.token_file = lib.mkOption {
+              type = lib.types.nullOr lib.types.str;
+              default = null;
+              description = ''
+                Path to a file holding this herald's preconfigured token, read
+                when Agora starts. Keeps the secret out of the Nix store.
+              '';
+            };
+
+            options.status_events = lib.mkOption {
+              type = lib.types.bool;
+              default = true;
+              description = "Push system.herald.* events when this herald disconnects or reconnects";
+            };
+
+            options.limits = lib.mkOption {
+              type = lib.types.nullOr (lib.types.submodule {
+                options = {
+                  events_per_minute = lib.mkOption {
+                    type = lib.types.nullOr lib.types.ints.positive;
+                    default = null;
+                    description = "Most events accepted per minute (null for no limit)";
+                  };
+
+                  max_pending = lib.mkOption {
+                    type = lib.types.nullOr lib.types.ints.positive;
+                    default = null;
+                    description = "Most of the herald's events queued at once (null for no limit)";
+                  };
+
+                  max_payload_bytes = lib.mkOption {
+                    type = lib.types.nullOr lib.types.ints.positive;
+                    default = null;
+                    description = "Largest payload accepted, in bytes of JSON (null for no limit)";
+                  };
+
+                  on_exceed = lib.mkOption {
+                    type = lib.types.enum [
+                      "reject"
+                      "drop_oldest"
+                    ];
+                    default = "reject";
+                    description = ''
+                      What happens to a push beyond max_pending: rejected with 429, or
+                      the herald's lowest-priority, oldest undelivered event is dropped
+                    '';
+                  };
+                };
+              });
+              default = null;
+              description = "Limits on this herald's pushes, replacing herald_limits";
+            };
+          }
+        );
+        default = { };
+        description = "Per-herald settings, keyed by herald ID";
+      };
+
+      admin_token_file = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = ''
+          Path to a file holding the admin bearer token, read when Agora
+          starts. It authorizes admin requests such as unregistering a herald
+          that lost its issued token. Without one, none are accepted.
+        '';
+      };
+
+      schemas = lib.mkOption {
+        type = lib.types.attrsOf settingsFormat.type;
+        default = { };
+        example = {
+          "chat.message" = {
+            type = "object";
+            required = [ "content" ];
+          };
+        };
+        description = ''
+          JSON Schemas for event payloads, keyed by event type. Pushes whose
+          payload does not conform are rejected with 422. Heralds can register
+          schemas of their own, but not replace these.
+        '';
+      };
+
       retry = {
         base_interval_ms = lib.mkOption {
           type = lib.types.ints.positive;
@@ -73,6 +297,12 @@ in
           type = lib.types.ints.positive;
           description = "Max retry interval (ms)";
         };
+
+        max_retries = lib.mkOption {
+          type = lib.types.nullOr lib.types.ints.unsigned;
+          default = null;
+          description = "Max redeliveries before an event is dead-lettered (null retries forever)";
+        };
       };
     };
 
@@ -86,6 +316,9 @@ in
   config = {
     services.ephemera.agora._configJson = settingsFormat.generate "agora.json" cfg.settings;
 
+    # Auto-include agora-cli
+    home.packages = lib.mkIf cfg.enable [ cfg.cliPackage ];
+
     systemd.user.services.agora = lib.mkIf cfg.enable {
       Unit = {
         Description = "Agora Event Hub";
@@ -102,6 +335,8 @@ in
         ExecStart = "${cfg.package}/bin/agora --config-dir ${config.services.ephemera._configDir}/agora";
         Restart = "on-failure";
         RestartSec = "3";
+        # Leave time to drain in-flight requests and close the database before SIGKILL
+        TimeoutStopSec = "${toString (cfg.settings.shutdown_timeout_ms / 1000 + 15)}";
       };
 
       Install = {
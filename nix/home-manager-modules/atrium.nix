@@ -30,6 +30,8 @@ let
     poll_interval_ms = cfg.herald.poll_interval_ms;
     heartbeat_interval_ms = cfg.herald.heartbeat_interval_ms;
     atrium_heartbeat_interval_ms = cfg.herald.atrium_heartbeat_interval_ms;
+    agora_token = cfg.herald.agora_token;
+    agora_token_file = cfg.herald.agora_token_file;
   };
 
   cliDefaultConfig = settingsFormat.generate "config.json" {
@@ -131,6 +133,18 @@ in
         type = lib.types.ints.positive;
         description = "Atrium heartbeat interval for user online status (ms)";
       };
+
+      agora_token = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Agora token for atrium-herald (must match services.ephemera.agora.settings.heralds.atrium-herald.token); ends up in the Nix store, prefer agora_token_file";
+      };
+
+      agora_token_file = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Path to a file holding the Agora token for atrium-herald, read at startup (typically the same file as services.ephemera.agora.settings.heralds.atrium-herald.token_file)";
+      };
     };
 
     # Internal options for unified config derivation
@@ -64,6 +64,18 @@ in
         type = lib.types.ints.positive;
         description = "Interval for checking scheduled events (ms)";
       };
+
+      misfire_grace_ms = lib.mkOption {
+        type = lib.types.ints.unsigned;
+        default = 60000;
+        description = "How late an occurrence can fire before it counts as missed and its schedule's misfire policy applies (ms)";
+      };
+
+      shutdown_timeout_ms = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 30000;
+        description = "How long shutdown waits for in-flight requests to finish (ms)";
+      };
     };
 
     heraldSettings = {
@@ -86,6 +98,18 @@ in
         type = lib.types.ints.positive;
         description = "Heartbeat interval in seconds";
       };
+
+      agora_token = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Agora token for kairos-herald (must match services.ephemera.agora.settings.heralds.kairos-herald.token); ends up in the Nix store, prefer agora_token_file";
+      };
+
+      agora_token_file = lib.mkOption {
+        type = lib.types.nullOr lib.types.str;
+        default = null;
+        description = "Path to a file holding the Agora token for kairos-herald, read at startup (typically the same file as services.ephemera.agora.settings.heralds.kairos-herald.token_file)";
+      };
     };
 
     # Internal options for unified config derivation
@@ -125,6 +149,8 @@ in
         ExecStart = "${cfg.package}/bin/kairos --config-dir ${config.services.ephemera._configDir}/kairos";
         Restart = "on-failure";
         RestartSec = "3";
+        # Leave time to drain in-flight requests and close the database before SIGKILL
+        TimeoutStopSec = "${toString (cfg.settings.shutdown_timeout_ms / 1000 + 15)}";
       };
 
       Install = {
@@ -58,6 +58,12 @@ in
         description = "Port for loom service";
       };
 
+      shutdown_timeout_ms = lib.mkOption {
+        type = lib.types.ints.positive;
+        default = 30000;
+        description = "How long shutdown waits for in-flight requests to finish (ms)";
+      };
+
       mysql = {
         max_connections = lib.mkOption {
           type = lib.types.ints.positive;
@@ -102,6 +108,8 @@ in
         ExecStart = "${cfg.package}/bin/loom --config-dir ${config.services.ephemera._configDir}/loom";
         Restart = "on-failure";
         RestartSec = "3";
+        # Leave time to drain in-flight requests and close the database before SIGKILL
+        TimeoutStopSec = "${toString (cfg.settings.shutdown_timeout_ms / 1000 + 15)}";
       };
 
       Install = {
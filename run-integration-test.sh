@@ -65,6 +65,14 @@ sudo nixos-container run "$CONTAINER_NAME" -- su - ephemera -c '
   sed -i "s|log_level = \"info\";|log_level = \"debug,hyper=warn,reqwest=warn,h2=warn,tower=warn,sqlx=warn\";|g" /home/ephemera/.config/home-manager/ephemera-ai.nix
 '
 
+# Agora tokens the template reads from files outside the Nix store
+sudo nixos-container run "$CONTAINER_NAME" -- su - ephemera -c '
+  mkdir -p ~/.config/ephemera/secrets && chmod 700 ~/.config/ephemera/secrets
+  for f in agora-admin kairos-herald atrium-herald; do
+    (umask 077 && head -c 32 /dev/urandom | od -An -tx1 | tr -d " \n" > ~/.config/ephemera/secrets/$f-token)
+  done
+'
+
 # 6. Run home-manager switch
 # echo "Running home-manager switch..."
 sudo nixos-container run "$CONTAINER_NAME" -- su - ephemera -c "
@@ -1,4 +1,13 @@
 { config, username, ... }:
+let
+  # Agora secrets are read from these files at startup, so they stay out of
+  # the world-readable Nix store. Create each one before the first start:
+  #   mkdir -p ~/.config/ephemera/secrets && chmod 700 ~/.config/ephemera/secrets
+  #   for f in agora-admin kairos-herald atrium-herald; do
+  #     (umask 077 && head -c 32 /dev/urandom | od -An -tx1 | tr -d " \n" > ~/.config/ephemera/secrets/$f-token)
+  #   done
+  secretsDir = "${config.xdg.configHome}/ephemera/secrets";
+in
 {
   # Ephemera AI - Main Agent
   services.ephemera.epha-ai = {
@@ -70,6 +79,16 @@
       heartbeat_check_interval_ms = 5000;
       timeout_ms = 30000;
 
+      # Bearer tokens for the bundled heralds. They keep no state across
+      # restarts, so they need fixed tokens rather than ones Agora issues.
+      heralds = {
+        kairos-herald.token_file = "${secretsDir}/kairos-herald-token";
+        atrium-herald.token_file = "${secretsDir}/atrium-herald-token";
+      };
+
+      # Lets `agora-cli heralds unregister` free a herald that lost its token
+      admin_token_file = "${secretsDir}/agora-admin-token";
+
       retry = {
         base_interval_ms = 5000;
         multiplier = 2;
@@ -94,6 +113,7 @@
       agora_url = "http://localhost:3000";
       poll_interval_ms = 1000;
       heartbeat_interval_sec = 30;
+      agora_token_file = "${secretsDir}/kairos-herald-token";
     };
   };
 
@@ -119,6 +139,7 @@
       poll_interval_ms = 1000;
       heartbeat_interval_ms = 30000;
       atrium_heartbeat_interval_ms = 30000;
+      agora_token_file = "${secretsDir}/atrium-herald-token";
     };
   };
 